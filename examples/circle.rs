@@ -0,0 +1,36 @@
+//! Fits a circle to points with outliers, showing the typical use of ARRSAC.
+//!
+//! Run with `cargo run --example circle`.
+
+#[path = "../tests/common/circle.rs"]
+mod circle;
+
+use arrsac::prelude::*;
+use circle::{circle_points, outliers, random_circle, CircleEstimator};
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+fn main() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    // Noisy points on a circle mixed with as many outliers.
+    let circle = random_circle(&mut rng);
+    let mut points = circle_points(&mut rng, &circle, 1.0, 500);
+    points.extend(outliers(&mut rng, 500));
+    points.shuffle(&mut rng);
+
+    // The inlier threshold should be a bit above the noise of the inliers.
+    let mut arrsac = Arrsac::new(2.0, rng);
+    let Some((model, inliers)) = arrsac.model_inliers(&CircleEstimator, points.iter().copied())
+    else {
+        println!("no circle was found");
+        return;
+    };
+    println!("true circle: {circle:?}");
+    println!("estimated circle: {model:?}");
+    println!("{} of {} points are inliers", inliers.len(), points.len());
+    let report = arrsac.report();
+    println!(
+        "generated {} hypotheses, epsilon {:?}, inlier residuals {:?}",
+        report.hypotheses_generated, report.epsilon, report.inlier_residuals
+    );
+}
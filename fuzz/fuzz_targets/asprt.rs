@@ -0,0 +1,50 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use arrsac::{
+    fuzzing::{asprt, ByteRng},
+    WeightFn,
+};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+enum Weight {
+    Hard,
+    Tukey,
+    TriCube,
+}
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    residuals: Vec<f64>,
+    inlier_threshold: f64,
+    likelihood_ratio_threshold: f32,
+    weight: Weight,
+    positive_likelihood_ratio: f32,
+    negative_likelihood_ratio: f32,
+    minimum_samples: u8,
+}
+
+fuzz_target!(|input: Input| {
+    let weight_fn = match input.weight {
+        Weight::Hard => WeightFn::Hard,
+        Weight::Tukey => WeightFn::Tukey,
+        Weight::TriCube => WeightFn::TriCube,
+    };
+    let result = asprt(
+        ByteRng::new(&[]),
+        &input.residuals,
+        input.inlier_threshold,
+        input.likelihood_ratio_threshold,
+        weight_fn,
+        input.positive_likelihood_ratio,
+        input.negative_likelihood_ratio,
+        usize::from(input.minimum_samples),
+    );
+    if let Some((inliers, support)) = result {
+        assert!(inliers <= input.residuals.len());
+        assert!(inliers >= usize::from(input.minimum_samples));
+        assert!(!support.is_nan(), "support must never be NaN");
+        assert!(support <= inliers as f64);
+    }
+});
@@ -0,0 +1,26 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use arrsac::fuzzing::{populate_samples, ByteRng};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+struct Input<'a> {
+    num: u8,
+    len: usize,
+    rng: &'a [u8],
+}
+
+fuzz_target!(|input: Input| {
+    let num = usize::from(input.num);
+    // Having less data than samples is a documented panic.
+    if input.len < num {
+        return;
+    }
+    let samples = populate_samples(ByteRng::new(input.rng), num, input.len);
+    assert_eq!(samples.len(), num);
+    for (ix, &sample) in samples.iter().enumerate() {
+        assert!((sample as usize) < input.len);
+        assert!(!samples[..ix].contains(&sample), "samples must be distinct");
+    }
+});
@@ -0,0 +1,788 @@
+use crate::{
+    Arrsac, AsprtOrder, RejectedSamples, Report, Samples, TiePolicy, UsableHypothesisThreshold,
+    WeightFn,
+};
+use alloc::vec::Vec;
+use rand_core::RngCore;
+
+/// The configuration of [`Arrsac`], which doesn't need an RNG until it is built.
+///
+/// This can be kept around to stamp out several identically configured [`Arrsac`] instances.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrsacBuilder {
+    pub(crate) initialization_hypotheses: usize,
+    pub(crate) initialization_blocks: usize,
+    pub(crate) max_candidate_hypotheses: usize,
+    pub(crate) estimations_per_block: usize,
+    pub(crate) block_size: usize,
+    pub(crate) block_growth: f32,
+    pub(crate) score_sample_fraction: f32,
+    pub(crate) block_eval_stride: usize,
+    pub(crate) likelihood_ratio_threshold: f32,
+    pub(crate) target_reject_prob: Option<f32>,
+    pub(crate) inlier_threshold: f64,
+    pub(crate) weight_fn: WeightFn,
+    pub(crate) full_sampling_only: bool,
+    pub(crate) min_full_sampling_rounds: usize,
+    pub(crate) usable_hypothesis_threshold: UsableHypothesisThreshold,
+    pub(crate) incremental_inliers: bool,
+    pub(crate) sample_size: Option<usize>,
+    pub(crate) skip_initial_phase: bool,
+    pub(crate) initial_epsilon: f32,
+    pub(crate) initial_delta: f32,
+    pub(crate) asprt_order: AsprtOrder,
+    pub(crate) dedupe_samples_global: bool,
+    pub(crate) min_delta: f32,
+    pub(crate) adaptive_threshold_percentile: Option<f32>,
+    pub(crate) assert_shuffled: bool,
+    pub(crate) report_threshold: Option<f64>,
+    pub(crate) threshold_tolerance: Option<f64>,
+    pub(crate) patience: usize,
+    pub(crate) initial_eval_size: Option<usize>,
+    pub(crate) max_blocks: usize,
+    pub(crate) min_estimations_per_block: Option<usize>,
+    pub(crate) full_scan_small_data: bool,
+    pub(crate) max_survivors_bytes: Option<usize>,
+    pub(crate) recency_weight: f32,
+    pub(crate) seed_samples: Vec<Vec<u32>>,
+    pub(crate) on_tie: TiePolicy,
+    pub(crate) calibrate_threshold: Option<f64>,
+    pub(crate) max_estimator_calls: Option<usize>,
+    pub(crate) residual_cache: bool,
+    pub(crate) inlier_window: usize,
+}
+
+impl ArrsacBuilder {
+    /// Creates the default configuration with the given `inlier_threshold`, see [`Arrsac::new`].
+    pub fn new(inlier_threshold: f64) -> Self {
+        Self {
+            initialization_hypotheses: 256,
+            initialization_blocks: 4,
+            max_candidate_hypotheses: 64,
+            estimations_per_block: 64,
+            block_size: 64,
+            block_growth: 1.0,
+            score_sample_fraction: 1.0,
+            block_eval_stride: 1,
+            likelihood_ratio_threshold: 1e3,
+            target_reject_prob: None,
+            inlier_threshold,
+            weight_fn: WeightFn::Hard,
+            full_sampling_only: false,
+            min_full_sampling_rounds: 0,
+            usable_hypothesis_threshold: UsableHypothesisThreshold::EpsilonFloor,
+            incremental_inliers: false,
+            sample_size: None,
+            skip_initial_phase: false,
+            initial_epsilon: 0.1,
+            initial_delta: 0.05,
+            asprt_order: AsprtOrder::Input,
+            dedupe_samples_global: false,
+            min_delta: 1e-6,
+            adaptive_threshold_percentile: None,
+            assert_shuffled: false,
+            report_threshold: None,
+            threshold_tolerance: None,
+            patience: usize::MAX,
+            initial_eval_size: None,
+            max_blocks: usize::MAX,
+            min_estimations_per_block: None,
+            full_scan_small_data: false,
+            max_survivors_bytes: None,
+            recency_weight: 1.0,
+            seed_samples: Vec::new(),
+            on_tie: TiePolicy::First,
+            calibrate_threshold: None,
+            max_estimator_calls: None,
+            residual_cache: false,
+            inlier_window: usize::MAX,
+        }
+    }
+
+    /// Creates an [`Arrsac`] with this configuration that uses `rng`.
+    pub fn build<R: RngCore>(self, rng: R) -> Arrsac<R> {
+        Arrsac {
+            config: self,
+            rng,
+            random_samples: Samples::default(),
+            rejected_samples: RejectedSamples::default(),
+            best_inliers: Vec::new(),
+            full_samples: 0,
+            report: Report::default(),
+            residual_cache: Default::default(),
+            inspect: None,
+            terminate: None,
+            #[cfg(feature = "test-util")]
+            fixed_samples: None,
+            #[cfg(feature = "record-models")]
+            recorder: Default::default(),
+            #[cfg(feature = "count-rng")]
+            rng_draws: 0,
+        }
+    }
+
+    /// Number of models generated in the initial step when epsilon and delta are being estimated.
+    ///
+    /// This must be at least one, since no model can be found otherwise, which
+    /// [`Arrsac::try_model_inliers`] checks.
+    ///
+    /// Default: `256`
+    #[must_use]
+    pub fn initialization_hypotheses(self, initialization_hypotheses: usize) -> Self {
+        Self {
+            initialization_hypotheses,
+            ..self
+        }
+    }
+
+    /// Number of data blocks used to compute the initial estimate of delta and epsilon
+    /// before proceeding with regular block processing. This is used instead of
+    /// an initial epsilon and delta, which were suggested by the paper.
+    ///
+    /// Default: `4`
+    #[must_use]
+    pub fn initialization_blocks(self, initialization_blocks: usize) -> Self {
+        Self {
+            initialization_blocks,
+            ..self
+        }
+    }
+
+    /// Maximum number of best hypotheses to retain during block processing
+    ///
+    /// This number is halved on each block such that on block `n` the number of
+    /// hypotheses retained is `max_candidate_hypotheses >> n`, but at least one. This means that
+    /// `0` retains a single hypothesis like `1` does, rather than none.
+    ///
+    /// Hypotheses with the same support at the cutoff are retained by the lowest sum of residuals of
+    /// their inliers and then in the order they were generated, the same way ties are broken by
+    /// [`TiePolicy::First`] when the best model is returned, so which hypotheses are retained is
+    /// deterministic.
+    ///
+    /// Default: `64`
+    #[must_use]
+    pub fn max_candidate_hypotheses(self, max_candidate_hypotheses: usize) -> Self {
+        Self {
+            max_candidate_hypotheses,
+            ..self
+        }
+    }
+
+    /// Number of estmations (may generate multiple hypotheses) that will be ran
+    /// for each block of data evaluated
+    ///
+    /// Default: `64`
+    #[must_use]
+    pub fn estimations_per_block(self, estimations_per_block: usize) -> Self {
+        Self {
+            estimations_per_block,
+            ..self
+        }
+    }
+
+    /// Number of data points evaluated before more hypotheses are generated
+    ///
+    /// Default: `64`
+    #[must_use]
+    pub fn block_size(self, block_size: usize) -> Self {
+        Self { block_size, ..self }
+    }
+
+    /// Grows the blocks after the initialization blocks geometrically, such that the `n`-th of them
+    /// evaluates `block_size * factor^n` new data points. This spends more data on each block as
+    /// the number of retained hypotheses shrinks, which reduces the time spent scoring while many
+    /// hypotheses are still alive.
+    ///
+    /// Panics if `factor` is less than `1`.
+    ///
+    /// Default: `1.0`
+    #[must_use]
+    pub fn geometric_blocks(self, factor: f32) -> Self {
+        assert!(factor >= 1.0, "the block growth factor must be at least 1");
+        Self {
+            block_growth: factor,
+            ..self
+        }
+    }
+
+    /// Only search on a random `fraction` of the data after the initialization blocks, drawn with the RNG.
+    /// The surviving hypotheses are still scored over all of the data to select the final model.
+    ///
+    /// This trades a little accuracy for a large speedup on very large datasets.
+    ///
+    /// Panics if `fraction` is not in `(0, 1]`.
+    ///
+    /// Default: `1.0`
+    #[must_use]
+    pub fn score_sample_fraction(self, fraction: f32) -> Self {
+        assert!(
+            fraction > 0.0 && fraction <= 1.0,
+            "the score sample fraction must be in (0, 1]"
+        );
+        Self {
+            score_sample_fraction: fraction,
+            ..self
+        }
+    }
+
+    /// Only search on every `stride`-th datapoint after the initialization blocks. The surviving
+    /// hypotheses are still scored over all of the data to select the final model.
+    ///
+    /// The blocks span as many datapoints as usual, but only every `stride`-th of them is scored,
+    /// which divides the cost of scoring the blocks by about `stride` while they still cover more
+    /// and more of the data. This is a deterministic alternative to
+    /// [`ArrsacBuilder::score_sample_fraction`] that doesn't draw from the RNG. It can be combined
+    /// with a sample fraction, in which case only the datapoints kept by both are searched on.
+    ///
+    /// Panics if `stride` is `0`.
+    ///
+    /// Default: `1`
+    #[must_use]
+    pub fn block_eval_stride(self, stride: usize) -> Self {
+        assert!(stride > 0, "the block evaluation stride must be at least 1");
+        Self {
+            block_eval_stride: stride,
+            ..self
+        }
+    }
+
+    /// Once a model reaches this level of unlikelihood, it is rejected. Set this
+    /// higher to make it less restrictive, usually at the cost of more execution time.
+    ///
+    /// Increasing this will make it more likely to find a good result.
+    ///
+    /// Decreasing this will speed up execution.
+    ///
+    /// This ratio is not exposed as a parameter in the original paper, but is instead computed
+    /// recursively for a few iterations. It is roughly equivalent to the **reciprocal** of the
+    /// **probability of rejecting a good model**. You can use that to control the probability
+    /// that a good model is rejected.
+    ///
+    /// Default: `1e3`
+    #[must_use]
+    pub fn likelihood_ratio_threshold(self, likelihood_ratio_threshold: f32) -> Self {
+        Self {
+            likelihood_ratio_threshold,
+            target_reject_prob: None,
+            ..self
+        }
+    }
+
+    /// Computes the `likelihood_ratio_threshold` at the start of each run from the initial estimates
+    /// of epsilon and delta, as the paper does, instead of using a fixed value.
+    ///
+    /// The threshold is the smallest one for which the probability of rejecting a good model within
+    /// the initialization datapoints is at most `target_reject_prob`. The computed threshold can be
+    /// found in the [`Report`]. Setting [`ArrsacBuilder::likelihood_ratio_threshold`] disables this again.
+    ///
+    /// Panics if `target_reject_prob` is not in `(0, 1)`.
+    #[must_use]
+    pub fn auto_likelihood_threshold(self, target_reject_prob: f32) -> Self {
+        assert!(
+            target_reject_prob > 0.0 && target_reject_prob < 1.0,
+            "target_reject_prob must be in (0, 1)"
+        );
+        Self {
+            target_reject_prob: Some(target_reject_prob),
+            ..self
+        }
+    }
+
+    /// Residual threshold for determining if a data point is an inlier or an outlier of a model
+    ///
+    /// A data point is only an inlier if its residual is finite and below the threshold, so a `NaN`
+    /// or infinite residual always makes it an outlier. See [`ArrsacBuilder::threshold_tolerance`]
+    /// for residuals exactly at the threshold.
+    ///
+    /// With the `f32-residuals` feature, residuals, the threshold and its tolerance are rounded to
+    /// `f32` before they are compared, which avoids `f64` comparisons on targets without hardware
+    /// double precision. This loses precision, so residuals within about `1e-7` times the threshold
+    /// of it may be classified differently, and residuals beyond the range of `f32` are outliers.
+    #[must_use]
+    pub fn inlier_threshold(self, inlier_threshold: f64) -> Self {
+        Self {
+            inlier_threshold,
+            ..self
+        }
+    }
+
+    /// Weighs inliers by how far within the `inlier_threshold` they are and ranks hypotheses
+    /// by their weighted support rather than their inlier count. This reduces the sensitivity
+    /// to the exact value of the `inlier_threshold`.
+    ///
+    /// Default: [`WeightFn::Hard`]
+    #[must_use]
+    pub fn soft_inliers(self, weight_fn: WeightFn) -> Self {
+        Self { weight_fn, ..self }
+    }
+
+    /// The smallest estimate of delta, the inlier ratio of a bad model, that the SPRT uses.
+    ///
+    /// If delta were `0`, a single inlier would make the SPRT unable to ever reject a model.
+    ///
+    /// Panics if `min_delta` is not in `(0, 1)`.
+    ///
+    /// Default: `1e-6`
+    #[must_use]
+    pub fn min_delta(self, min_delta: f32) -> Self {
+        assert!(
+            0.0 < min_delta && min_delta < 1.0,
+            "min_delta must be in (0, 1)"
+        );
+        Self { min_delta, ..self }
+    }
+
+    /// Replaces the initial phase, which estimates epsilon and delta from many hypotheses, with a
+    /// single call to `Estimator::estimate()` before evaluating blocks. This is a diagnostic knob
+    /// which isolates how much the initial estimation contributes on your data.
+    ///
+    /// The fixed [`ArrsacBuilder::initial_estimates`] are used instead of the estimated ones, and
+    /// only the initialization datapoints are still scored before the first block.
+    ///
+    /// Default: `false`
+    #[must_use]
+    pub fn skip_initial_phase(self, skip_initial_phase: bool) -> Self {
+        Self {
+            skip_initial_phase,
+            ..self
+        }
+    }
+
+    /// The inlier ratios of a good model (`epsilon`) and a bad model (`delta`) that are assumed
+    /// when using [`ArrsacBuilder::skip_initial_phase`].
+    ///
+    /// Panics unless `0 < delta < epsilon < 1`.
+    ///
+    /// Default: `0.1` and `0.05`
+    #[must_use]
+    pub fn initial_estimates(self, epsilon: f32, delta: f32) -> Self {
+        assert!(
+            0.0 < delta && delta < epsilon && epsilon < 1.0,
+            "the initial estimates must satisfy 0 < delta < epsilon < 1"
+        );
+        Self {
+            initial_epsilon: epsilon,
+            initial_delta: delta,
+            ..self
+        }
+    }
+
+    /// Always draw minimal samples from all of the data instead of from the inliers of the best
+    /// hypothesis. This is a diagnostic knob which isolates how much sampling from the best
+    /// inliers contributes on your data. It will usually make ARRSAC slower and less accurate.
+    ///
+    /// Default: `false`
+    #[must_use]
+    pub fn full_sampling_only(self, full_sampling_only: bool) -> Self {
+        Self {
+            full_sampling_only,
+            ..self
+        }
+    }
+
+    /// Draws at least `min_full_sampling_rounds` samples from all of the data in every run before
+    /// drawing any from the inliers of the best hypothesis, which includes the
+    /// [`ArrsacBuilder::initialization_hypotheses`].
+    ///
+    /// On hard data, a mediocre hypothesis may already be usable after the initial hypotheses, and
+    /// sampling only from its inliers might then never find a better structure elsewhere in the
+    /// data. Sampling from all of the data for longer gives it a chance to be found first. This has
+    /// no effect unless it exceeds the initial hypotheses, and [`ArrsacBuilder::full_sampling_only`]
+    /// is the limit of never sampling from the inliers.
+    ///
+    /// Default: `0`
+    #[must_use]
+    pub fn min_full_sampling_rounds(self, min_full_sampling_rounds: usize) -> Self {
+        Self {
+            min_full_sampling_rounds,
+            ..self
+        }
+    }
+
+    /// The order in which the SPRT evaluates the datapoints, see [`AsprtOrder`].
+    ///
+    /// Default: [`AsprtOrder::Input`]
+    #[must_use]
+    pub fn asprt_order(self, asprt_order: AsprtOrder) -> Self {
+        Self {
+            asprt_order,
+            ..self
+        }
+    }
+
+    /// Remembers the samples whose hypotheses were all rejected by the SPRT during a run and skips
+    /// drawing them again for the rest of the run instead of estimating the same models again.
+    ///
+    /// Only the most recently rejected samples are remembered to bound the memory used. A skipped
+    /// sample still counts towards the number of estimations, so this mostly saves time on small
+    /// datasets with few distinct samples, where many draws would repeat rejected samples.
+    ///
+    /// Default: `false`
+    #[must_use]
+    pub fn dedupe_samples_global(self, dedupe_samples_global: bool) -> Self {
+        Self {
+            dedupe_samples_global,
+            ..self
+        }
+    }
+
+    /// Replaces the inlier threshold after the initial phase with the `percentile` of the residuals of
+    /// the best initial hypothesis over the initialization datapoints, which must be in `(0, 1)`.
+    ///
+    /// The percentile is estimated while streaming over the residuals, so nothing is sorted or
+    /// allocated. Since the best hypothesis fits the inliers, a percentile slightly below the expected
+    /// inlier ratio puts the threshold at the noise level of the inliers. The initial hypotheses are
+    /// scored again with the new threshold, which the rest of the run uses, see
+    /// [`Report::adaptive_inlier_threshold`]. The configured threshold is kept if the percentile of the
+    /// residuals is zero.
+    ///
+    /// Panics if `percentile` is not in `(0, 1)`.
+    ///
+    /// Default: `None`
+    #[must_use]
+    pub fn adaptive_threshold_percentile(self, adaptive_threshold_percentile: f32) -> Self {
+        assert!(
+            0.0 < adaptive_threshold_percentile && adaptive_threshold_percentile < 1.0,
+            "the adaptive threshold percentile must be in (0, 1)"
+        );
+        Self {
+            adaptive_threshold_percentile: Some(adaptive_threshold_percentile),
+            ..self
+        }
+    }
+
+    /// Checks whether the data looks to be unshuffled, such as when the inliers were concatenated
+    /// before the outliers, and sets [`Report::possibly_unshuffled`] if so.
+    ///
+    /// The check looks at where the inliers of the best initial hypothesis are in all of the data,
+    /// which costs one more pass over the data. If the data is shuffled, their mean index is close
+    /// to the middle of the data, so a mean index far towards the start is flagged. This is a
+    /// heuristic meant for development, which misses many kinds of unshuffled data.
+    ///
+    /// Default: `false`
+    #[must_use]
+    pub fn assert_shuffled(self, assert_shuffled: bool) -> Self {
+        Self {
+            assert_shuffled,
+            ..self
+        }
+    }
+
+    /// Uses a different threshold than the inlier threshold for the inliers that are returned with
+    /// the model, while the inlier threshold still governs the search and the selection of the model.
+    ///
+    /// This allows finding a precise model with a tight inlier threshold and then gathering every
+    /// datapoint that plausibly fits it with a looser `report_threshold` for refinement.
+    /// [`Report::inlier_residuals`] describes these inliers as well. The inliers tracked with
+    /// [`ArrsacBuilder::incremental_inliers`] can't be reused then, so they are found again.
+    ///
+    /// Default: the inlier threshold
+    #[must_use]
+    pub fn report_threshold(self, report_threshold: f64) -> Self {
+        Self {
+            report_threshold: Some(report_threshold),
+            ..self
+        }
+    }
+
+    /// Counts datapoints whose residual is at most the threshold plus `threshold_tolerance` as inliers,
+    /// instead of only datapoints whose residual is strictly below the threshold.
+    ///
+    /// A residual that lands exactly on the threshold, or within rounding of it, is then classified the
+    /// same way on every platform, which keeps the inlier sets reproducible across architectures. This
+    /// applies to the [`ArrsacBuilder::report_threshold`] as well. A `NaN` or infinite residual is
+    /// still an outlier.
+    ///
+    /// Default: a residual must be below the threshold
+    #[must_use]
+    pub fn threshold_tolerance(self, threshold_tolerance: f64) -> Self {
+        Self {
+            threshold_tolerance: Some(threshold_tolerance),
+            ..self
+        }
+    }
+
+    /// Stops the search once the best hypothesis has stayed the best for `patience` consecutive blocks,
+    /// and returns it as the model.
+    ///
+    /// The best hypothesis is the one with the most support after a block, and it stays the best
+    /// while it is still tied for the most support. On easy data it is often
+    /// found in the first blocks, so this cuts the latency of the blocks after it, which usually only
+    /// confirm it. The surviving hypotheses are still scored on all of the data before the final
+    /// selection, so a lower `patience` risks missing a better hypothesis from a later block.
+    ///
+    /// Default: `usize::MAX`, which never stops early
+    #[must_use]
+    pub fn patience(self, patience: usize) -> Self {
+        Self { patience, ..self }
+    }
+
+    /// Number of data points the initial hypotheses are evaluated on, instead of the data points of
+    /// the [`ArrsacBuilder::initialization_blocks`], after which the regular blocks continue.
+    ///
+    /// A smaller evaluation makes the initial phase faster at the cost of a coarser estimate of epsilon
+    /// and delta. It has to be large enough for the SPRT to see the `(1 - delta) / (1 - epsilon)`
+    /// likelihood ratio of enough outliers to exceed the
+    /// [`ArrsacBuilder::likelihood_ratio_threshold`], otherwise no hypothesis is ever rejected. It
+    /// must be at least the sample size, which [`Arrsac::try_model_inliers`] checks.
+    ///
+    /// Default: `initialization_blocks * block_size`
+    #[must_use]
+    pub fn initial_eval_size(self, initial_eval_size: usize) -> Self {
+        Self {
+            initial_eval_size: Some(initial_eval_size),
+            ..self
+        }
+    }
+
+    /// Stops the search after `max_blocks` blocks after the initial phase, even if there is data left,
+    /// and returns the best of the surviving hypotheses.
+    ///
+    /// This bounds the time spent on very large datasets, trusting the SPRT over the datapoints that
+    /// were evaluated. The survivors are still scored on all of the data before the final selection.
+    /// With `0`, the search stops right after the initial phase.
+    ///
+    /// Default: `usize::MAX`, which evaluates blocks until the data runs out
+    #[must_use]
+    pub fn max_blocks(self, max_blocks: usize) -> Self {
+        Self { max_blocks, ..self }
+    }
+
+    /// Scales the estimations of each block between this and
+    /// [`ArrsacBuilder::estimations_per_block`] by how much the two best hypotheses disagree
+    ///
+    /// The disagreement is the Jaccard distance between the inlier sets of the two best hypotheses
+    /// over the datapoints scored so far, which is `0` if they have the same inliers and `1` if they
+    /// have no inliers in common. When the best hypotheses agree closely, generating many new hypotheses
+    /// is mostly wasted, so fewer are generated. When they disagree, up to
+    /// [`ArrsacBuilder::estimations_per_block`] are generated to explore more. A block with fewer than
+    /// two hypotheses always uses the full amount. Finding the inliers takes two passes over the scored
+    /// datapoints every block.
+    ///
+    /// A minimum above [`ArrsacBuilder::estimations_per_block`] is treated as equal to it.
+    ///
+    /// Default: `None`, which always uses [`ArrsacBuilder::estimations_per_block`]
+    #[must_use]
+    pub fn min_estimations_per_block(self, min_estimations_per_block: usize) -> Self {
+        Self {
+            min_estimations_per_block: Some(min_estimations_per_block),
+            ..self
+        }
+    }
+
+    /// Scores every initial hypothesis over all of the data instead of testing them with the SPRT when
+    /// there are no more datapoints than [`ArrsacBuilder::block_size`]
+    ///
+    /// On such small datasets the SPRT saves little, but it can still reject good hypotheses early
+    /// because of an unlucky order of the datapoints. Scanning everything gives exact inlier counts for
+    /// every hypothesis, and since the initial phase then covers all of the data, the best of them is
+    /// returned without evaluating any blocks.
+    ///
+    /// Default: `false`
+    #[must_use]
+    pub fn full_scan_small_data(self, full_scan_small_data: bool) -> Self {
+        Self {
+            full_scan_small_data,
+            ..self
+        }
+    }
+
+    /// Caps the memory taken by the surviving hypotheses after every block to about this many bytes
+    ///
+    /// Each survivor takes `size_of::<E::Model>()` plus a few machine words for its inlier count,
+    /// support and bookkeeping, so the number of survivors is capped to as many as fit into the budget,
+    /// regardless of [`ArrsacBuilder::max_candidate_hypotheses`]. At least one hypothesis always
+    /// survives, even if it doesn't fit. This gives a predictable memory ceiling for deployments with
+    /// tight RAM, such as for large models. The inliers tracked with [`ArrsacBuilder::incremental_inliers`]
+    /// and the new hypotheses of a block before it is truncated aren't included.
+    ///
+    /// Default: `None`, which doesn't cap the survivors
+    #[must_use]
+    pub fn max_survivors_bytes(self, max_survivors_bytes: usize) -> Self {
+        Self {
+            max_survivors_bytes: Some(max_survivors_bytes),
+            ..self
+        }
+    }
+
+    /// Weighs the inliers of every block `recency_weight` times as much as those of the block before
+    /// it when ranking hypotheses. This suits online estimation, where datapoints are appended to the
+    /// data as they arrive and newer datapoints are more reliable, since the best hypothesis is then
+    /// the one that fits the most recent blocks best.
+    ///
+    /// Only the support is weighted, like with [`ArrsacBuilder::soft_inliers`], so epsilon is still
+    /// estimated from the inlier counts. A weight of `1` weighs all datapoints equally.
+    ///
+    /// Panics if `recency_weight` is less than `1` or not finite.
+    ///
+    /// Default: `1.0`
+    #[must_use]
+    pub fn recency_weight(self, recency_weight: f32) -> Self {
+        assert!(
+            (1.0..f32::INFINITY).contains(&recency_weight),
+            "recency_weight must be at least 1 and finite"
+        );
+        Self {
+            recency_weight,
+            ..self
+        }
+    }
+
+    /// Minimal samples that the initial hypotheses are estimated from before any random ones, such
+    /// as a sample that is known to be good or the samples of a run that should be reproduced.
+    ///
+    /// Every seed sample holds the indices of the datapoints to estimate from, and every one of them is
+    /// used once, even if there are more of them than [`ArrsacBuilder::initialization_hypotheses`].
+    /// The remaining initial hypotheses are generated randomly as usual, as are the hypotheses of the
+    /// later blocks.
+    ///
+    /// The indices are relative to the data being searched, so with [`Arrsac::models`] they refer to
+    /// the datapoints that remain after removing the inliers of the previous models, since every model
+    /// is searched for with the same seed samples.
+    ///
+    /// If a seed sample doesn't have exactly `E::MIN_SAMPLES` indices or an index is beyond the data,
+    /// [`Arrsac::try_model_inliers`] returns [`crate::ArrsacError::InvalidConfig`] and running panics
+    /// otherwise.
+    ///
+    /// Default: no seed samples
+    #[must_use]
+    pub fn seed_samples(self, seed_samples: Vec<Vec<u32>>) -> Self {
+        Self {
+            seed_samples,
+            ..self
+        }
+    }
+
+    /// How the returned model is chosen when several models are tied for the most support, such as
+    /// models that fit the exact same datapoints. Use [`Arrsac::best_models`] to get all of them instead.
+    ///
+    /// Default: [`TiePolicy::First`]
+    #[must_use]
+    pub fn on_tie(self, on_tie: TiePolicy) -> Self {
+        Self { on_tie, ..self }
+    }
+
+    /// Calibrates the inlier threshold to the noise of the inliers with a second run, so the inlier
+    /// threshold only needs to be a loose upper bound on the noise rather than a tuned value.
+    ///
+    /// A first run with the inlier threshold finds a rough model. The noise is then estimated
+    /// robustly from the median absolute residual of its inliers, scaled by `1.4826` to be a standard
+    /// deviation for Gaussian noise, and the second run uses `k` times that as its inlier threshold,
+    /// see [`Report::calibrated_inlier_threshold`], but never more than the inlier threshold. A `k`
+    /// of about `2.5` to `3` keeps almost every inlier. The configured threshold is kept if the
+    /// first run finds no model or the median is zero.
+    ///
+    /// This doubles the cost of a run, and the [`Report`] only describes the second run.
+    ///
+    /// Panics if `k` is not positive and finite.
+    ///
+    /// Default: `None`
+    #[must_use]
+    pub fn calibrate_threshold(self, k: f64) -> Self {
+        assert!(
+            k > 0.0 && k.is_finite(),
+            "calibrate_threshold must be positive and finite"
+        );
+        Self {
+            calibrate_threshold: Some(k),
+            ..self
+        }
+    }
+
+    /// Stops generating hypotheses once `Estimator::estimate()` was called `max_estimator_calls`
+    /// times in a run, see [`Report::estimator_calls`].
+    ///
+    /// An estimator can return several models from one sample, so this bounds the cost of estimators
+    /// whose estimation is the expensive part, such as polynomial solvers, more directly than the number
+    /// of hypotheses does. The hypotheses generated until then are still scored over the following
+    /// blocks as usual, so ARRSAC can still narrow them down to the best one.
+    ///
+    /// Default: `None`
+    #[must_use]
+    pub fn max_estimator_calls(self, max_estimator_calls: usize) -> Self {
+        Self {
+            max_estimator_calls: Some(max_estimator_calls),
+            ..self
+        }
+    }
+
+    /// Caches the residuals of models over the data within a data epoch, so that
+    /// [`Arrsac::cached_models`] and [`Arrsac::cached_inliers`] don't compute the residuals of a model
+    /// over the same data again, such as when extracting models repeatedly or sweeping over thresholds.
+    ///
+    /// A cached model is found by its [`Hash`](core::hash::Hash) and confirmed to be the same model
+    /// with [`PartialEq`]. The residuals are cached until the data epoch changes with
+    /// [`Arrsac::bump_data_epoch`], which also happens once a cached model is used with data of
+    /// another length or with other residuals on a few datapoints that are checked every time. This
+    /// uses memory for one residual per datapoint for every cached model, which is
+    /// `O(models × datapoints)`.
+    ///
+    /// Default: `false`
+    #[must_use]
+    pub fn residual_cache(self, residual_cache: bool) -> Self {
+        Self {
+            residual_cache,
+            ..self
+        }
+    }
+
+    /// Controls when the best hypothesis is trusted enough to draw minimal samples from its inliers
+    /// instead of from all of the data. Raising this avoids converging on the inliers of a mediocre
+    /// hypothesis too early, at the cost of generating fewer good hypotheses.
+    ///
+    /// Default: [`UsableHypothesisThreshold::EpsilonFloor`]
+    #[must_use]
+    pub fn usable_hypothesis_threshold(
+        self,
+        usable_hypothesis_threshold: UsableHypothesisThreshold,
+    ) -> Self {
+        Self {
+            usable_hypothesis_threshold,
+            ..self
+        }
+    }
+
+    /// Only the first `inlier_window` datapoints are used to rank the surviving hypotheses and to
+    /// find the inliers of the returned model, while the search still uses all of the data.
+    ///
+    /// This is useful for sliding window estimation, where older datapoints are stale. Put the
+    /// datapoints of the window first, since they are also the ones the search starts with.
+    ///
+    /// Default: all of the data
+    #[must_use]
+    pub fn inlier_window(self, inlier_window: usize) -> Self {
+        Self {
+            inlier_window,
+            ..self
+        }
+    }
+
+    /// Tracks the indices of the inliers of every hypothesis while scoring it, so the inliers of
+    /// the final model are returned without another pass over all of the data. This uses memory
+    /// proportional to the number of inliers of every retained hypothesis.
+    ///
+    /// Default: `false`
+    #[must_use]
+    pub fn incremental_inliers(self, incremental_inliers: bool) -> Self {
+        Self {
+            incremental_inliers,
+            ..self
+        }
+    }
+
+    /// Number of datapoints passed to `Estimator::estimate()` for each hypothesis, which must be at
+    /// least `Estimator::MIN_SAMPLES`.
+    ///
+    /// Some estimators are more stable when given slightly more than the minimal number of
+    /// datapoints. A hypothesis still only needs `Estimator::MIN_SAMPLES` inliers to be accepted.
+    /// Subsets with fewer than `sample_size` datapoints are not sampled from.
+    ///
+    /// Default: `Estimator::MIN_SAMPLES`
+    #[must_use]
+    pub fn sample_size(self, sample_size: usize) -> Self {
+        Self {
+            sample_size: Some(sample_size),
+            ..self
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{samples::mix, Arrsac};
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    any::Any,
+    hash::{Hash, Hasher},
+};
+use rand_core::RngCore;
+use sample_consensus::{Estimator, Model};
+
+/// The number of datapoints at which a cached row is checked against the data, see
+/// [`CachedRow::matches`].
+const FINGERPRINT_CHECKS: usize = 16;
+
+/// Caches the residuals of a model over the data `I` of an [`Arrsac`] `A` and returns their
+/// position, see [`Arrsac::cache_residuals`].
+pub(crate) type CacheResiduals<A, I, M> = fn(&mut A, I, &M) -> usize;
+
+/// The residuals of models over the data of the current data epoch, see
+/// [`crate::ArrsacBuilder::residual_cache`].
+#[derive(Default)]
+pub(crate) struct ResidualCache {
+    epoch: u64,
+    rows: Vec<CachedRow>,
+}
+
+/// The residuals of one model for every datapoint.
+struct CachedRow {
+    /// The hash of the model, which is compared before the model itself.
+    key: u64,
+    model: Box<dyn Any + Send + Sync>,
+    residuals: Vec<f64>,
+}
+
+impl CachedRow {
+    /// Checks if `data` has as many datapoints as the row and `model` has the cached residuals on
+    /// up to [`FINGERPRINT_CHECKS`] of them, which are spread evenly over the data.
+    fn matches<Data, M: Model<Data>>(&self, data: impl Iterator<Item = Data>, model: &M) -> bool {
+        let len = self.residuals.len();
+        let stride = (len / FINGERPRINT_CHECKS).max(1);
+        let mut seen = 0;
+        for (ix, data) in data.enumerate() {
+            if ix >= len {
+                return false;
+            }
+            if ix % stride == 0 && model.residual(&data).to_bits() != self.residuals[ix].to_bits() {
+                return false;
+            }
+            seen += 1;
+        }
+        seen == len
+    }
+}
+
+/// Hashes models into the keys of a [`ResidualCache`].
+#[derive(Default)]
+struct ModelHasher(u64);
+
+impl Hasher for ModelHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = mix(self.0 ^ u64::from(byte));
+        }
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.0 = mix(self.0 ^ i);
+    }
+}
+
+/// The hash of `model` in a [`ResidualCache`].
+fn model_key<M: Hash>(model: &M) -> u64 {
+    let mut hasher = ModelHasher::default();
+    model.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<R> Arrsac<R>
+where
+    R: RngCore,
+{
+    /// Starts a new data epoch, which drops every residual cached with
+    /// [`ArrsacBuilder::residual_cache`](crate::ArrsacBuilder::residual_cache).
+    ///
+    /// A new epoch also starts by itself once a cached model is used with data of another length or
+    /// with other residuals on the datapoints that are checked, but call this whenever the data changes
+    /// in ways that might not be noticed, such as when only a few datapoints are edited.
+    pub fn bump_data_epoch(&mut self) {
+        self.residual_cache.epoch += 1;
+        self.residual_cache.rows.clear();
+    }
+
+    /// The current data epoch, which starts at `0` and is incremented by
+    /// [`Arrsac::bump_data_epoch`], including when the cache notices that the data changed.
+    pub fn data_epoch(&self) -> u64 {
+        self.residual_cache.epoch
+    }
+
+    /// Extracts up to `max_models` models like [`Arrsac::models`], but finds the inliers of every
+    /// model from its cached residuals over all of `data`, see
+    /// [`ArrsacBuilder::residual_cache`](crate::ArrsacBuilder::residual_cache).
+    ///
+    /// The models and inliers are the same as those of [`Arrsac::models`].
+    #[must_use]
+    pub fn cached_models<E, Data, I>(
+        &mut self,
+        estimator: &E,
+        data: I,
+        max_models: usize,
+    ) -> Vec<(E::Model, Vec<usize>)>
+    where
+        E: Estimator<Data>,
+        E::Model: Clone + PartialEq + Hash + Send + Sync + 'static,
+        I: Iterator<Item = Data> + Clone,
+    {
+        self.extract_models(
+            estimator,
+            data,
+            max_models,
+            Some(|arrsac, data, model| arrsac.cache_residuals(data, model)),
+        )
+    }
+
+    /// Finds the inliers of `model` in `data` with `inlier_threshold` like
+    /// [`Arrsac::recompute_inliers`], but from its cached residuals, see
+    /// [`ArrsacBuilder::residual_cache`](crate::ArrsacBuilder::residual_cache).
+    ///
+    /// This makes sweeping over thresholds nearly free after the first one, including for the
+    /// models returned by [`Arrsac::cached_models`].
+    pub fn cached_inliers<Data, M>(
+        &mut self,
+        model: &M,
+        data: impl Iterator<Item = Data> + Clone,
+        inlier_threshold: f64,
+    ) -> Vec<usize>
+    where
+        M: Model<Data> + Clone + PartialEq + Hash + Send + Sync + 'static,
+    {
+        let position = self.cache_residuals(data, model);
+        self.threshold_residuals(self.cached_residuals(position), inlier_threshold)
+    }
+
+    /// Caches the residuals of `model` for every datapoint of `data` unless they are already cached
+    /// in this data epoch, and returns their position for [`Arrsac::cached_residuals`].
+    ///
+    /// Without [`ArrsacBuilder::residual_cache`](crate::ArrsacBuilder::residual_cache), only the
+    /// residuals of the most recent model are kept, which are computed again every time.
+    pub(crate) fn cache_residuals<Data, M>(
+        &mut self,
+        data: impl Iterator<Item = Data> + Clone,
+        model: &M,
+    ) -> usize
+    where
+        M: Model<Data> + Clone + PartialEq + Hash + Send + Sync + 'static,
+    {
+        let key = model_key(model);
+        if !self.config.residual_cache {
+            self.residual_cache.rows.clear();
+        } else if let Some(position) = self.residual_cache.rows.iter().position(|row| {
+            row.key == key
+                && row
+                    .model
+                    .downcast_ref::<M>()
+                    .is_some_and(|cached| cached == model)
+        }) {
+            if self.residual_cache.rows[position].matches(data.clone(), model) {
+                return position;
+            }
+            // The data changed since the row was cached, so none of the rows are valid anymore.
+            self.bump_data_epoch();
+        }
+        self.residual_cache.rows.push(CachedRow {
+            key,
+            model: Box::new(model.clone()),
+            residuals: self.residuals(data, model),
+        });
+        self.residual_cache.rows.len() - 1
+    }
+
+    /// The residuals at `position` of the cache, see [`Arrsac::cache_residuals`].
+    pub(crate) fn cached_residuals(&self, position: usize) -> &[f64] {
+        &self.residual_cache.rows[position].residuals
+    }
+}
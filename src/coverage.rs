@@ -0,0 +1,97 @@
+use crate::{Arrsac, Hypothesis, SearchHooks};
+use alloc::{vec, vec::Vec};
+use rand_core::RngCore;
+use sample_consensus::{Consensus, Estimator, Model};
+
+/// An [`Arrsac`] that only returns models whose inliers cover enough of a grid, created by
+/// [`Arrsac::spatial_coverage`].
+pub struct SpatialCoverage<'a, R, F> {
+    arrsac: &'a mut Arrsac<R>,
+    grid: (usize, usize),
+    min_cells: usize,
+    cell_of: F,
+}
+
+impl<R> Arrsac<R>
+where
+    R: RngCore,
+{
+    /// Rejects the surviving models whose inliers occupy fewer than `min_cells` cells of a grid
+    /// with `grid.0` columns and `grid.1` rows, where `cell_of` gives the column and row of a
+    /// datapoint, such as the cell of a feature within an image.
+    ///
+    /// A model whose inliers all sit in one corner of an image is useless for panorama stitching
+    /// even if it has the most inliers. The best of the remaining survivors is returned, so keep
+    /// more survivors with a larger `max_candidate_hypotheses`, since usually only one survives.
+    /// Cells outside of the grid don't count.
+    pub fn spatial_coverage<F>(
+        &mut self,
+        grid: (usize, usize),
+        min_cells: usize,
+        cell_of: F,
+    ) -> SpatialCoverage<'_, R, F> {
+        SpatialCoverage {
+            arrsac: self,
+            grid,
+            min_cells,
+            cell_of,
+        }
+    }
+}
+
+impl<R, F> SpatialCoverage<'_, R, F>
+where
+    R: RngCore,
+{
+    /// Checks if the inliers of `hypothesis` over `data` occupy at least `min_cells` cells.
+    fn covers<Data, M: Model<Data>>(
+        &self,
+        data: impl Iterator<Item = Data>,
+        hypothesis: &Hypothesis<M>,
+    ) -> bool
+    where
+        F: Fn(&Data) -> (usize, usize),
+    {
+        let (columns, rows) = self.grid;
+        let mut occupied = vec![false; columns.saturating_mul(rows)];
+        let mut cells = 0;
+        for data in data.take(self.arrsac.config.inlier_window) {
+            if !self.arrsac.is_inlier(hypothesis.model.residual(&data)) {
+                continue;
+            }
+            let (column, row) = (self.cell_of)(&data);
+            if column < columns && row < rows && !occupied[row * columns + column] {
+                occupied[row * columns + column] = true;
+                cells += 1;
+            }
+        }
+        cells >= self.min_cells
+    }
+}
+
+impl<E, R, F, Data> Consensus<E, Data> for SpatialCoverage<'_, R, F>
+where
+    E: Estimator<Data>,
+    R: RngCore,
+    F: Fn(&Data) -> (usize, usize),
+{
+    type Inliers = Vec<usize>;
+
+    fn model<I>(&mut self, estimator: &E, data: I) -> Option<E::Model>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        self.model_inliers(estimator, data).map(|(model, _)| model)
+    }
+
+    fn model_inliers<I>(&mut self, estimator: &E, data: I) -> Option<(E::Model, Self::Inliers)>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        let mut hypotheses = self
+            .arrsac
+            .survivors(estimator, data.clone(), SearchHooks::default());
+        hypotheses.retain(|hypothesis| self.covers(data.clone(), hypothesis));
+        self.arrsac.select(hypotheses, data)
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{Arrsac, SearchHooks};
+use alloc::vec::Vec;
+use rand_core::RngCore;
+use sample_consensus::{Consensus, Estimator};
+
+/// An [`Arrsac`] that searches on the data without duplicate datapoints, created by
+/// [`Arrsac::dedupe_data`].
+pub struct DedupeData<'a, R, F> {
+    arrsac: &'a mut Arrsac<R>,
+    is_duplicate: F,
+}
+
+/// An [`Arrsac`] that only keeps the best of the hypotheses with the same model, created by
+/// [`Arrsac::dedupe_hypotheses`].
+pub struct DedupeHypotheses<'a, R, F> {
+    arrsac: &'a mut Arrsac<R>,
+    is_same_model: F,
+}
+
+impl<R> Arrsac<R>
+where
+    R: RngCore,
+{
+    /// Collapses the datapoints that are duplicates of each other according to `is_duplicate`
+    /// before searching, such as exact duplicates in quantized sensor data.
+    ///
+    /// A minimal sample only ever contains distinct indices, but duplicate datapoints at distinct
+    /// indices still make a degenerate sample, which produces bad hypotheses or none at all. With
+    /// this, only the first of every set of duplicates is sampled from and scored on during the
+    /// search. The surviving hypotheses are then scored on all of the data, so the inlier counts
+    /// and the returned inliers still include every duplicate.
+    ///
+    /// This compares every pair of datapoints, so it takes time quadratic in the amount of data.
+    pub fn dedupe_data<F>(&mut self, is_duplicate: F) -> DedupeData<'_, R, F> {
+        DedupeData {
+            arrsac: self,
+            is_duplicate,
+        }
+    }
+
+    /// Removes the hypotheses whose model is the same as the model of a better hypothesis according
+    /// to `is_same_model`, such as models whose parameters are within some tolerance.
+    ///
+    /// Sampling from the inliers of the best hypothesis on structured data often produces the same
+    /// model again, which then takes the place of a distinct hypothesis and is scored again in
+    /// every block. With this, the duplicates are removed before the hypotheses are truncated after
+    /// the initial phase and every block, and counted in [`Report::duplicate_hypotheses`].
+    ///
+    /// This compares every pair of hypotheses, so it takes time quadratic in the number of
+    /// hypotheses.
+    ///
+    /// [`Report::duplicate_hypotheses`]: crate::Report::duplicate_hypotheses
+    pub fn dedupe_hypotheses<F>(&mut self, is_same_model: F) -> DedupeHypotheses<'_, R, F> {
+        DedupeHypotheses {
+            arrsac: self,
+            is_same_model,
+        }
+    }
+}
+
+impl<E, R, F, Data> Consensus<E, Data> for DedupeData<'_, R, F>
+where
+    E: Estimator<Data>,
+    R: RngCore,
+    F: Fn(&Data, &Data) -> bool,
+{
+    type Inliers = Vec<usize>;
+
+    fn model<I>(&mut self, estimator: &E, data: I) -> Option<E::Model>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        self.model_inliers(estimator, data).map(|(model, _)| model)
+    }
+
+    fn model_inliers<I>(&mut self, estimator: &E, data: I) -> Option<(E::Model, Self::Inliers)>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        let hypotheses = self.arrsac.survivors(
+            estimator,
+            data.clone(),
+            SearchHooks {
+                is_duplicate: Some(&self.is_duplicate),
+                ..SearchHooks::default()
+            },
+        );
+        self.arrsac.select(hypotheses, data)
+    }
+}
+
+impl<E, R, F, Data> Consensus<E, Data> for DedupeHypotheses<'_, R, F>
+where
+    E: Estimator<Data>,
+    R: RngCore,
+    F: Fn(&E::Model, &E::Model) -> bool,
+{
+    type Inliers = Vec<usize>;
+
+    fn model<I>(&mut self, estimator: &E, data: I) -> Option<E::Model>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        self.model_inliers(estimator, data).map(|(model, _)| model)
+    }
+
+    fn model_inliers<I>(&mut self, estimator: &E, data: I) -> Option<(E::Model, Self::Inliers)>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        let hypotheses = self.arrsac.survivors(
+            estimator,
+            data.clone(),
+            SearchHooks {
+                is_same_model: Some(&self.is_same_model),
+                ..SearchHooks::default()
+            },
+        );
+        self.arrsac.select(hypotheses, data)
+    }
+}
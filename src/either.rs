@@ -0,0 +1,74 @@
+use crate::Arrsac;
+use alloc::vec::Vec;
+use rand_core::RngCore;
+use sample_consensus::{Consensus, Estimator, Model};
+
+/// A model of one of two types, returned by [`Arrsac::model_inliers_multi`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelEither<A, B> {
+    /// A model of the first estimator.
+    Left(A),
+    /// A model of the second estimator.
+    Right(B),
+}
+
+impl<A, B, Data> Model<Data> for ModelEither<A, B>
+where
+    A: Model<Data>,
+    B: Model<Data>,
+{
+    fn residual(&self, data: &Data) -> f64 {
+        match self {
+            Self::Left(model) => model.residual(data),
+            Self::Right(model) => model.residual(data),
+        }
+    }
+}
+
+/// The model of [`Arrsac::model_inliers_multi`] with its inliers.
+type EitherInliers<E1, E2, Data> = (
+    ModelEither<<E1 as Estimator<Data>>::Model, <E2 as Estimator<Data>>::Model>,
+    Vec<usize>,
+);
+
+impl<R> Arrsac<R>
+where
+    R: RngCore,
+{
+    /// Runs ARRSAC with each of two estimators of different types of models, such as lines and
+    /// circles, and returns the model with the most inliers along with its inliers.
+    ///
+    /// Both runs use the same inlier threshold, so the residuals of both types of models must be on
+    /// the same scale for the comparison to be meaningful. The model of the first estimator is
+    /// returned if both have as many inliers. The [`Report`](crate::Report) describes the run of the
+    /// returned model.
+    #[must_use]
+    pub fn model_inliers_multi<E1, E2, Data, I>(
+        &mut self,
+        e1: &E1,
+        e2: &E2,
+        data: I,
+    ) -> Option<EitherInliers<E1, E2, Data>>
+    where
+        E1: Estimator<Data>,
+        E2: Estimator<Data>,
+        I: Iterator<Item = Data> + Clone,
+    {
+        let first = self.model_inliers(e1, data.clone());
+        let first_report = self.report.clone();
+        let second = self.model_inliers(e2, data);
+        match (first, second) {
+            (Some((first, first_inliers)), Some((_, second_inliers)))
+                if first_inliers.len() >= second_inliers.len() =>
+            {
+                self.report = first_report;
+                Some((ModelEither::Left(first), first_inliers))
+            }
+            (first, None) => {
+                self.report = first_report;
+                first.map(|(model, inliers)| (ModelEither::Left(model), inliers))
+            }
+            (_, Some((second, inliers))) => Some((ModelEither::Right(second), inliers)),
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use core::fmt;
+
+/// The reason a run of [`Arrsac::try_model_inliers`](crate::Arrsac::try_model_inliers) or
+/// [`Arrsac::try_fit`](crate::Arrsac::try_fit) did not find a model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ArrsacError {
+    /// There are fewer datapoints than the number of datapoints in a sample.
+    NotEnoughSamples,
+    /// The configuration can't be used with the estimator, such as a sample size below
+    /// `Estimator::MIN_SAMPLES`, no initialization blocks or hypotheses, an initial evaluation
+    /// smaller than a sample or a seed sample that isn't a minimal sample of the data.
+    InvalidConfig,
+    /// There are no datapoints at all.
+    EmptyData,
+    /// No model survived the run, such as when the estimator never produced a model or every model
+    /// was rejected.
+    NoModelGenerated,
+}
+
+impl fmt::Display for ArrsacError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotEnoughSamples => f.write_str("not enough datapoints to draw a sample"),
+            Self::InvalidConfig => f.write_str("the configuration is invalid for the estimator"),
+            Self::EmptyData => f.write_str("there are no datapoints"),
+            Self::NoModelGenerated => f.write_str("no model was found"),
+        }
+    }
+}
+
+impl core::error::Error for ArrsacError {}
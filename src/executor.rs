@@ -0,0 +1,119 @@
+use crate::{score_block, sprt, Arrsac, Candidate, Hypothesis, Scoring, SearchHooks, Sprt};
+use alloc::vec::Vec;
+use rand_core::RngCore;
+use sample_consensus::{Consensus, Estimator};
+
+/// A task that an [`Executor`] runs.
+pub type Task<'a> = &'a mut (dyn FnMut() + Send);
+
+/// Runs tasks, such as on the threads of a thread pool, which lets [`Arrsac::parallel_scoring`]
+/// score hypotheses in parallel without this crate depending on `std`.
+pub trait Executor {
+    /// Runs every task exactly once, in any order and possibly in parallel, and only returns once
+    /// all of them are done.
+    fn execute(&self, tasks: &mut [Task<'_>]);
+}
+
+/// An [`Executor`] that runs the tasks one after another on the calling thread.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Sequential;
+
+impl Executor for Sequential {
+    fn execute(&self, tasks: &mut [Task<'_>]) {
+        for task in tasks {
+            task();
+        }
+    }
+}
+
+/// An [`Arrsac`] that scores the hypotheses of every block in parallel with an [`Executor`],
+/// created by [`Arrsac::parallel_scoring`].
+pub struct ParallelScoring<'a, R, X> {
+    arrsac: &'a mut Arrsac<R>,
+    executor: X,
+}
+
+impl<R> Arrsac<R>
+where
+    R: RngCore,
+{
+    /// Scores the hypotheses of every block with one task per hypothesis run by `executor`.
+    ///
+    /// Scoring the hypotheses of a block over its datapoints is the bulk of the work with
+    /// expensive residuals, and the hypotheses are scored independently of each other. The SPRT
+    /// of the models estimated for a block also runs with one task per model. The hypotheses,
+    /// the random numbers drawn and so the results are the same as without an executor. The
+    /// datapoints of every block, and those that the SPRT evaluates, are collected before scoring
+    /// them, so they can be shared with the tasks.
+    pub fn parallel_scoring<X>(&mut self, executor: X) -> ParallelScoring<'_, R, X> {
+        ParallelScoring {
+            arrsac: self,
+            executor,
+        }
+    }
+}
+
+impl<E, R, X, Data> Consensus<E, Data> for ParallelScoring<'_, R, X>
+where
+    E: Estimator<Data>,
+    E::Model: Send,
+    R: RngCore,
+    X: Executor,
+    Data: Sync,
+{
+    type Inliers = Vec<usize>;
+
+    fn model<I>(&mut self, estimator: &E, data: I) -> Option<E::Model>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        self.model_inliers(estimator, data).map(|(model, _)| model)
+    }
+
+    fn model_inliers<I>(&mut self, estimator: &E, data: I) -> Option<(E::Model, Self::Inliers)>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        let executor = &self.executor;
+        let scorer =
+            |hypotheses: &mut [Hypothesis<E::Model>], block: &[Data], first, scoring: Scoring| {
+                let mut tasks: Vec<_> = hypotheses
+                    .iter_mut()
+                    .map(|hypothesis| move || score_block(hypothesis, block, first, scoring))
+                    .collect();
+                let mut tasks: Vec<Task<'_>> =
+                    tasks.iter_mut().map(|task| task as Task<'_>).collect();
+                executor.execute(&mut tasks);
+            };
+        let candidate_scorer = |candidates: &mut [Candidate<E::Model>],
+                                data: &[(usize, Data)],
+                                params: Sprt| {
+            let mut tasks: Vec<_> = candidates
+                .iter_mut()
+                .map(|candidate| {
+                    move || {
+                        let data = data.iter().map(|(ix, data)| (*ix, data));
+                        candidate.scored = sprt(
+                            data,
+                            &candidate.model,
+                            params,
+                            candidate.inlier_indices.as_mut(),
+                        );
+                    }
+                })
+                .collect();
+            let mut tasks: Vec<Task<'_>> = tasks.iter_mut().map(|task| task as Task<'_>).collect();
+            executor.execute(&mut tasks);
+        };
+        let hypotheses = self.arrsac.survivors(
+            estimator,
+            data.clone(),
+            SearchHooks {
+                scorer: Some(&scorer),
+                candidate_scorer: Some(&candidate_scorer),
+                ..SearchHooks::default()
+            },
+        );
+        self.arrsac.select(hypotheses, data)
+    }
+}
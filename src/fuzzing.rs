@@ -0,0 +1,97 @@
+//! Entry points into the internals of [`Arrsac`] used by the fuzz targets in `fuzz/`.
+//!
+//! This module is only available with the `fuzzing` feature and is not part of the public API.
+
+use crate::{Arrsac, WeightFn};
+use alloc::vec::Vec;
+use rand_core::RngCore;
+use sample_consensus::Model;
+
+/// A model whose residual for a data point is the data point itself.
+///
+/// This allows arbitrary residual sequences to be fed directly to the SPRT.
+pub struct ResidualModel;
+
+impl Model<f64> for ResidualModel {
+    fn residual(&self, &data: &f64) -> f64 {
+        data
+    }
+}
+
+/// Draws `num` distinct sample indices below `len` with `rng`.
+///
+/// Panics if `len < num`, just like a regular run with too little data.
+pub fn populate_samples<R: RngCore>(rng: R, num: usize, len: usize) -> Vec<u32> {
+    let mut arrsac = Arrsac::new(1.0, rng);
+    arrsac.populate_samples(num, len);
+    arrsac.random_samples.as_slice().to_vec()
+}
+
+/// Runs the SPRT over `residuals` as if they were computed from a model.
+#[allow(clippy::too_many_arguments)]
+pub fn asprt<R: RngCore>(
+    rng: R,
+    residuals: &[f64],
+    inlier_threshold: f64,
+    likelihood_ratio_threshold: f32,
+    weight_fn: WeightFn,
+    positive_likelihood_ratio: f32,
+    negative_likelihood_ratio: f32,
+    minimum_samples: usize,
+) -> Option<(usize, f64)> {
+    let mut arrsac = Arrsac::new(inlier_threshold, rng)
+        .likelihood_ratio_threshold(likelihood_ratio_threshold)
+        .soft_inliers(weight_fn);
+    arrsac.reset_state();
+    arrsac
+        .asprt(
+            residuals.iter().copied().enumerate(),
+            &ResidualModel,
+            positive_likelihood_ratio,
+            negative_likelihood_ratio,
+            minimum_samples,
+            None,
+        )
+        .map(|(inliers, support, _)| (inliers, support))
+}
+
+/// An RNG that yields the fuzzer-provided bytes and then falls back to SplitMix64.
+///
+/// The fallback guarantees that rejection sampling eventually terminates once the bytes run out.
+pub struct ByteRng<'a> {
+    bytes: &'a [u8],
+    state: u64,
+}
+
+impl<'a> ByteRng<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, state: 0 }
+    }
+}
+
+impl RngCore for ByteRng<'_> {
+    fn next_u32(&mut self) -> u32 {
+        if let Some((chunk, rest)) = self.bytes.split_first_chunk::<4>() {
+            self.bytes = rest;
+            return u32::from_le_bytes(*chunk);
+        }
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use crate::Arrsac;
+use alloc::vec::Vec;
+use core::iter::Map;
+use rand_core::RngCore;
+use sample_consensus::{Consensus, Estimator, Model};
+
+/// An [`Estimator`] that can also be given the indices of the datapoints in a minimal sample.
+///
+/// Use [`Arrsac::indexed_samples`] to have its samples estimated with
+/// [`IndexedEstimator::estimate_indexed`], for example to cache work per datapoint or to keep
+/// track of where a model came from.
+pub trait IndexedEstimator<Data>: Estimator<Data> {
+    /// Estimates the models for a minimal sample, where each datapoint comes with its index in the
+    /// data.
+    fn estimate_indexed<I>(&self, data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = (usize, Data)> + Clone;
+}
+
+/// An [`Arrsac`] that passes the indices of the sampled datapoints to an [`IndexedEstimator`],
+/// created by [`Arrsac::indexed_samples`].
+pub struct IndexedSamples<'a, R> {
+    arrsac: &'a mut Arrsac<R>,
+}
+
+impl<R> Arrsac<R>
+where
+    R: RngCore,
+{
+    /// Estimates the minimal samples with [`IndexedEstimator::estimate_indexed`], which is given
+    /// the index of every sampled datapoint along with the datapoint.
+    ///
+    /// The run is otherwise the same as with [`Arrsac`] itself.
+    pub fn indexed_samples(&mut self) -> IndexedSamples<'_, R> {
+        IndexedSamples { arrsac: self }
+    }
+}
+
+impl<E, R, Data> Consensus<E, Data> for IndexedSamples<'_, R>
+where
+    E: IndexedEstimator<Data>,
+    R: RngCore,
+{
+    type Inliers = Vec<usize>;
+
+    fn model<I>(&mut self, estimator: &E, data: I) -> Option<E::Model>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        self.model_inliers(estimator, data).map(|(model, _)| model)
+    }
+
+    fn model_inliers<I>(&mut self, estimator: &E, data: I) -> Option<(E::Model, Self::Inliers)>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        // The datapoints carry their indices through the run so the estimator can see them.
+        self.arrsac
+            .model_inliers(&WithIndices(estimator), data.enumerate())
+            .map(|(WithIndex(model), inliers)| (model, inliers))
+    }
+}
+
+/// Estimates datapoints paired with their indices using an [`IndexedEstimator`].
+struct WithIndices<'a, E>(&'a E);
+
+/// A model of datapoints paired with their indices, which ignores the indices.
+struct WithIndex<M>(M);
+
+impl<M, Data> Model<(usize, Data)> for WithIndex<M>
+where
+    M: Model<Data>,
+{
+    fn residual(&self, (_, data): &(usize, Data)) -> f64 {
+        self.0.residual(data)
+    }
+}
+
+type WithIndexIter<E, Data> = Map<
+    <<E as Estimator<Data>>::ModelIter as IntoIterator>::IntoIter,
+    fn(<E as Estimator<Data>>::Model) -> WithIndex<<E as Estimator<Data>>::Model>,
+>;
+
+impl<E, Data> Estimator<(usize, Data)> for WithIndices<'_, E>
+where
+    E: IndexedEstimator<Data>,
+{
+    type Model = WithIndex<E::Model>;
+    type ModelIter = WithIndexIter<E, Data>;
+    const MIN_SAMPLES: usize = E::MIN_SAMPLES;
+
+    fn estimate<I>(&self, data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = (usize, Data)> + Clone,
+    {
+        self.0.estimate_indexed(data).into_iter().map(WithIndex)
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{Arrsac, SearchHooks};
+use core::iter::{Enumerate, Take};
+use rand_core::RngCore;
+use sample_consensus::{Estimator, Model};
+
+/// The inliers of a model found by [`Arrsac::model_inliers_iter`], which are found lazily as it is
+/// iterated.
+///
+/// This owns the model and a copy of the data iterator, and it borrows the [`Arrsac`] for the
+/// thresholds of the run. The model can be borrowed with [`ModelInliers::model`] while iterating,
+/// and it can be taken out with [`ModelInliers::into_model`] once the inliers aren't needed.
+pub struct ModelInliers<'a, R, M, I> {
+    arrsac: &'a Arrsac<R>,
+    model: M,
+    data: Enumerate<Take<I>>,
+}
+
+impl<R> Arrsac<R>
+where
+    R: RngCore,
+{
+    /// Runs ARRSAC and returns the best model like [`Consensus::model_inliers`], but with its
+    /// inliers as an iterator instead of a `Vec`.
+    ///
+    /// The inliers are found by computing the residual of every datapoint as the iterator
+    /// advances, so nothing is allocated for them. This is useful when the inliers are consumed
+    /// right away, such as to gather them for refitting. The iterator borrows `self` until it is
+    /// dropped.
+    ///
+    /// [`Consensus::model_inliers`]: sample_consensus::Consensus::model_inliers
+    pub fn model_inliers_iter<E, Data, I>(
+        &mut self,
+        estimator: &E,
+        data: I,
+    ) -> Option<ModelInliers<'_, R, E::Model, I>>
+    where
+        E: Estimator<Data>,
+        I: Iterator<Item = Data> + Clone,
+    {
+        let hypotheses = self.survivors(estimator, data.clone(), SearchHooks::default());
+        let best = self.best_hypothesis(hypotheses)?;
+        self.record_returned(data.clone(), &best);
+        Some(ModelInliers {
+            data: data.take(self.config.inlier_window).enumerate(),
+            arrsac: self,
+            model: best.model,
+        })
+    }
+}
+
+impl<R, M, I> ModelInliers<'_, R, M, I> {
+    /// The model the inliers belong to.
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    /// Takes the model, dropping the remaining inliers.
+    pub fn into_model(self) -> M {
+        self.model
+    }
+}
+
+impl<R, M, I, Data> Iterator for ModelInliers<'_, R, M, I>
+where
+    R: RngCore,
+    M: Model<Data>,
+    I: Iterator<Item = Data>,
+{
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let Self {
+            arrsac,
+            model,
+            data,
+        } = self;
+        data.find(|(_, data)| arrsac.is_reported_inlier(model.residual(data)))
+            .map(|(ix, _)| ix)
+    }
+}
@@ -1,11 +1,837 @@
 #![no_std]
 
 extern crate alloc;
-use core::cmp::Reverse;
 
-use alloc::{vec, vec::Vec};
-use rand_core::RngCore;
+/// Logs a message at the given level with the `log` feature, and compiles to nothing without it.
+macro_rules! log {
+    ($level:ident, $($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        log::$level!($($arg)*);
+    };
+}
+
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing;
+
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    ops::{ControlFlow, Deref, Range},
+};
+
+mod builder;
+mod cache;
+mod coverage;
+mod dedupe;
+mod either;
+mod error;
+mod executor;
+mod indexed;
+mod inliers;
+mod locality;
+mod mapped;
+mod prefilter;
+pub mod prelude;
+mod quantile;
+#[cfg(feature = "record-models")]
+mod record;
+mod refine;
+mod replay;
+mod samples;
+mod scale;
+#[cfg(feature = "self-test")]
+mod self_test;
+#[cfg(feature = "serde")]
+mod snapshot;
+mod strata;
+
+pub use builder::ArrsacBuilder;
+pub use coverage::SpatialCoverage;
+pub use dedupe::{DedupeData, DedupeHypotheses};
+pub use either::ModelEither;
+pub use error::ArrsacError;
+pub use executor::{Executor, ParallelScoring, Sequential, Task};
+pub use indexed::{IndexedEstimator, IndexedSamples};
+pub use inliers::ModelInliers;
+pub use locality::LocalityBias;
+pub use prefilter::ResidualPrefilter;
+use quantile::P2Quantile;
+use rand_core::{RngCore, SeedableRng};
+#[cfg(feature = "record-models")]
+pub use record::RecordedModel;
+pub use refine::{Refined, RefiningEstimator};
+pub use replay::SliceRng;
 use sample_consensus::{Consensus, Estimator, Model};
+use samples::{RejectedSamples, Samples};
+pub use scale::ResidualScale;
+#[cfg(feature = "serde")]
+pub use snapshot::ArrsacSnapshot;
+pub use strata::StratifiedInit;
+
+/// Determines how much an inlier contributes to the support of a hypothesis.
+///
+/// Regardless of the weighting, a data point is only ever an inlier if its residual is below
+/// the inlier threshold, so the returned inliers are the same for every weighting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WeightFn {
+    /// Every inlier contributes `1`, which is regular inlier counting.
+    #[default]
+    Hard,
+    /// Tukey's biweight `(1 - (r / t)^2)^2`.
+    Tukey,
+    /// The tri-cube weight `(1 - (r / t)^3)^3`.
+    TriCube,
+}
+
+impl WeightFn {
+    /// Computes the weight in `[0, 1]` of an inlier with the given `residual`.
+    pub fn weight(self, residual: f64, inlier_threshold: f64) -> f64 {
+        let ratio = residual / inlier_threshold;
+        let weight = match self {
+            Self::Hard => return 1.0,
+            Self::Tukey => {
+                let inv = 1.0 - ratio * ratio;
+                inv * inv
+            }
+            Self::TriCube => {
+                let inv = 1.0 - ratio * ratio * ratio;
+                inv * inv * inv
+            }
+        };
+        // A NaN ratio (e.g. from infinite residuals and thresholds) must not poison the support.
+        if weight.is_nan() {
+            0.0
+        } else {
+            weight.clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// Decides when the best hypothesis is trusted enough to draw minimal samples from its inliers.
+///
+/// Until the best hypothesis is trusted, minimal samples are drawn from all of the data.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum UsableHypothesisThreshold {
+    /// Always trust the best hypothesis, since the current estimate of epsilon is its inlier ratio.
+    #[default]
+    EpsilonFloor,
+    /// Trust the best hypothesis once it has at least this many inliers.
+    AbsoluteCount(usize),
+    /// Trust the best hypothesis once at least this fraction of the evaluated data are its inliers.
+    FractionOfData(f32),
+}
+
+/// The order in which the SPRT evaluates the datapoints when testing a hypothesis.
+///
+/// The SPRT rejects a bad hypothesis fastest when the datapoints it has high residuals on come first.
+/// Since the SPRT may stop at a different point, a different order can also change which hypotheses
+/// are accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AsprtOrder {
+    /// Evaluate the datapoints in the order of the input.
+    #[default]
+    Input,
+    /// Evaluate the inliers of the best hypothesis so far first. Bad hypotheses are expected to
+    /// have the highest residuals on them, since they are most likely the true inliers.
+    WorstFirst,
+}
+
+/// How the returned model is chosen among several models that are tied for the most support.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TiePolicy {
+    /// Return the model whose inliers have the lowest sum of residuals, and the one that was
+    /// generated first among those that are still tied. This is the order of
+    /// [`Arrsac::model_candidates`].
+    #[default]
+    First,
+    /// Return the model whose inliers have the lowest mean residual, and the one that was generated
+    /// first among those that are still tied. This only differs from [`TiePolicy::First`] when the
+    /// tied models have different numbers of inliers, such as with [`Arrsac::soft_inliers`].
+    LowestResidual,
+}
+
+/// The state of a search between two blocks.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct SearchState<M> {
+    /// The hypotheses that are still candidates, which are sorted after every block.
+    hypotheses: Vec<Hypothesis<M>>,
+    delta: f32,
+    /// How many datapoints the hypotheses have been scored on.
+    num_scored: usize,
+    /// How many datapoints the data had when the search started.
+    data_len: usize,
+    /// The index of the next block.
+    block: usize,
+    /// The end of the previous block and the (fractional) size of the next one.
+    samples_up_to_end_of_previous_block: usize,
+    next_block_size: f32,
+    /// The generation of the best hypothesis and for how many blocks it has stayed the best.
+    best_generation: Option<usize>,
+    blocks_without_improvement: usize,
+    /// There are no more blocks to evaluate.
+    done: bool,
+    /// Where every block that was scored starts, see [`ArrsacBuilder::recency_weight`].
+    block_starts: Vec<usize>,
+    /// The weight of the inliers of the newest block, see [`recency_scale`].
+    recency_scale: f64,
+}
+
+/// A model along with its support over the data it was evaluated on.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Hypothesis<M> {
+    model: M,
+    /// The number of inliers.
+    inliers: usize,
+    /// The sum of the weights of the inliers, see [`WeightFn`].
+    support: f64,
+    /// The sum of the residuals of the inliers, which breaks ties in support.
+    residual_sum: f64,
+    /// The order in which the hypothesis was generated during the run.
+    generation: usize,
+    /// The block in which the hypothesis was generated, where the initial phase is block `0`.
+    block: usize,
+    /// The indices of the inliers, which are only tracked with [`ArrsacBuilder::incremental_inliers`].
+    inlier_indices: Option<Vec<usize>>,
+}
+
+/// Orders hypotheses from best to worst.
+///
+/// Hypotheses with more support are better. Among hypotheses with equal support, the one whose
+/// inliers have the lower sum of residuals fits them more closely and is better, and the one
+/// generated first is better among those that are still tied, which makes the order deterministic.
+fn compare_hypotheses<M>(a: &Hypothesis<M>, b: &Hypothesis<M>) -> Ordering {
+    b.support
+        .total_cmp(&a.support)
+        .then(a.residual_sum.total_cmp(&b.residual_sum))
+        .then(a.generation.cmp(&b.generation))
+}
+
+/// The mean residual of the inliers of `hypothesis`, which is infinite if it has none.
+fn mean_inlier_residual<M>(hypothesis: &Hypothesis<M>) -> f64 {
+    if hypothesis.inliers == 0 {
+        f64::INFINITY
+    } else {
+        hypothesis.residual_sum / hypothesis.inliers as f64
+    }
+}
+
+/// Sorts hypotheses from best to worst, see [`compare_hypotheses`].
+fn sort_hypotheses<M>(hypotheses: &mut [Hypothesis<M>]) {
+    hypotheses.sort_unstable_by(compare_hypotheses);
+}
+
+/// The likelihood ratios of an inlier and an outlier in the SPRT, see [`Arrsac::likelihood_ratios`].
+fn likelihood_ratios(epsilon: f64, delta: f64) -> (f64, f64) {
+    // Keep both ratios finite, even for an epsilon of one or an epsilon of zero.
+    let clamp = |ratio: f64| ratio.clamp(f64::EPSILON, 1.0 - f64::EPSILON);
+    let (epsilon, delta) = (clamp(epsilon), clamp(delta));
+    (delta / epsilon, (1.0 - delta) / (1.0 - epsilon))
+}
+
+/// Probability that the SPRT rejects a good model with inlier ratio `epsilon` within `len` datapoints
+/// when outlier models have inlier ratio `delta` and models are rejected above `threshold`.
+///
+/// This is computed exactly by tracking the probability of every number of outliers seen so far,
+/// since the likelihood ratio only depends on the number of inliers and outliers.
+fn rejection_probability(epsilon: f64, delta: f64, len: usize, threshold: f64) -> f64 {
+    let (positive_likelihood_ratio, negative_likelihood_ratio) = likelihood_ratios(epsilon, delta);
+    // `alive[m]` is the probability of having seen `m` outliers without being rejected
+    // and `ratios[m]` is the likelihood ratio after seeing them.
+    let mut alive = vec![0.0f64; len + 1];
+    let mut ratios = vec![1.0f64; len + 1];
+    alive[0] = 1.0;
+    let mut rejected = 0.0;
+    for n in 1..=len {
+        // Iterate backwards so each step only reads the previous step's values.
+        for m in (0..=n).rev() {
+            let (outlier, ratio) = if m > 0 {
+                (
+                    alive[m - 1] * (1.0 - epsilon),
+                    ratios[m - 1] * negative_likelihood_ratio,
+                )
+            } else {
+                (0.0, ratios[m] * positive_likelihood_ratio)
+            };
+            let inlier = if m < n { alive[m] * epsilon } else { 0.0 };
+            ratios[m] = ratio;
+            if ratio > threshold {
+                rejected += outlier + inlier;
+                alive[m] = 0.0;
+            } else {
+                alive[m] = outlier + inlier;
+            }
+        }
+    }
+    rejected
+}
+
+/// Computes the smallest likelihood ratio threshold which rejects a good model with inlier ratio `epsilon`
+/// within `len` datapoints with at most `target_reject_prob` probability.
+///
+/// By Wald's bound, the probability of rejecting a good model never exceeds `1 / threshold`, so the
+/// threshold is found by bisecting between `1` and `1 / target_reject_prob`.
+fn sprt_threshold(epsilon: f32, delta: f32, len: usize, target_reject_prob: f32) -> f32 {
+    let (epsilon, delta) = (f64::from(epsilon), f64::from(delta));
+    let target_reject_prob = f64::from(target_reject_prob);
+    let mut low = 1.0;
+    let mut high = 1.0 / target_reject_prob;
+    for _ in 0..32 {
+        let mid = 0.5 * (low + high);
+        if rejection_probability(epsilon, delta, len, mid) > target_reject_prob {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    high as f32
+}
+
+/// Raises `base` to the power of `exp` by squaring, since `core` has no `powi`.
+fn powu(mut base: f32, mut exp: usize) -> f32 {
+    let mut result = 1.0;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result *= base;
+        }
+        base *= base;
+        exp >>= 1;
+    }
+    result
+}
+
+/// Computes `1 - (1 - p)^exp` by squaring like [`powu`], but on the complements of the factors, since
+/// `1 - p` rounds to `1` for a small `p` and `core` has no `ln_1p`. The complement of the product
+/// of two factors with complements `a` and `b` is `a + b - a * b`, which doesn't cancel.
+fn complement_powu(p: f64, mut exp: usize) -> f64 {
+    let (mut result, mut base) = (0.0, p);
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result + base - result * base;
+        }
+        base = 2.0 * base - base * base;
+        exp >>= 1;
+    }
+    result
+}
+
+/// The square root of the non-negative `x` with Newton's method, since `core` has no `sqrt`.
+fn sqrt(x: f64) -> f64 {
+    if x == 0.0 || !x.is_finite() {
+        return x;
+    }
+    // Halving the exponent gives a guess within a factor of two, which Newton's method refines to
+    // full precision well within the iterations.
+    let mut root = f64::from_bits((x.to_bits() >> 1) + (1023 << 51));
+    for _ in 0..8 {
+        root = 0.5 * (root + x / root);
+    }
+    root
+}
+
+/// The spread of the `residuals` of the inliers of a model, which is `None` if there are none.
+fn residual_stats(residuals: impl Iterator<Item = f64>) -> Option<ResidualStats> {
+    // Welford's algorithm keeps the mean and the sum of the squared deviations from it, which is
+    // numerically stable unlike summing the squares.
+    let (count, min, max, mean, deviations) = residuals.fold(
+        (0usize, f64::INFINITY, f64::NEG_INFINITY, 0.0, 0.0),
+        |(count, min, max, mean, deviations), residual| {
+            let count = count + 1;
+            let delta = residual - mean;
+            let new_mean = mean + delta / count as f64;
+            (
+                count,
+                min.min(residual),
+                max.max(residual),
+                new_mean,
+                deviations + delta * (residual - new_mean),
+            )
+        },
+    );
+    (count > 0).then(|| ResidualStats {
+        min,
+        max,
+        mean,
+        std_dev: sqrt(deviations / count as f64),
+    })
+}
+
+/// The type that residuals are compared in, see [`ArrsacBuilder::inlier_threshold`].
+#[cfg(not(feature = "f32-residuals"))]
+type Compared = f64;
+#[cfg(feature = "f32-residuals")]
+type Compared = f32;
+
+/// Converts `x` to the type that residuals are compared in.
+fn compared(x: f64) -> Compared {
+    x as Compared
+}
+
+/// The bound on the residuals of inliers, which is converted once so that comparisons only convert
+/// the residual.
+#[derive(Debug, Clone, Copy)]
+struct InlierBound {
+    limit: Compared,
+    /// Residuals at the limit are within the bound, see [`ArrsacBuilder::threshold_tolerance`].
+    inclusive: bool,
+}
+
+impl InlierBound {
+    /// The bound of `threshold` allowing for `tolerance`, see [`ArrsacBuilder::threshold_tolerance`].
+    fn new(threshold: f64, tolerance: Option<f64>) -> Self {
+        match tolerance {
+            Some(tolerance) => Self {
+                limit: compared(threshold) + compared(tolerance),
+                inclusive: true,
+            },
+            None => Self {
+                limit: compared(threshold),
+                inclusive: false,
+            },
+        }
+    }
+
+    /// Checks if `residual` is within the bound, which non-finite residuals never are.
+    fn contains(self, residual: f64) -> bool {
+        let residual = compared(residual);
+        residual.is_finite()
+            && if self.inclusive {
+                residual <= self.limit
+            } else {
+                residual < self.limit
+            }
+    }
+}
+
+/// How datapoints are scored during a run, which can be shared with other threads, see
+/// [`Arrsac::parallel_scoring`].
+#[derive(Debug, Clone, Copy)]
+struct Scoring {
+    inlier_threshold: f64,
+    bound: InlierBound,
+    weight_fn: WeightFn,
+    /// The weight of the block being scored, see [`ArrsacBuilder::recency_weight`].
+    recency_scale: f64,
+}
+
+impl Scoring {
+    /// Checks if a datapoint with `residual` is an inlier.
+    fn is_inlier(self, residual: f64) -> bool {
+        self.bound.contains(residual)
+    }
+
+    /// The weight of an inlier with the given residual.
+    fn weight(self, residual: f64) -> f64 {
+        self.weight_fn.weight(residual, self.inlier_threshold) * self.recency_scale
+    }
+}
+
+/// How the SPRT tests the hypotheses of a block, see [`sprt`].
+#[derive(Debug, Clone, Copy)]
+struct Sprt {
+    scoring: Scoring,
+    positive_likelihood_ratio: f32,
+    negative_likelihood_ratio: f32,
+    likelihood_ratio_threshold: f32,
+    /// The number of inliers below which a hypothesis is rejected at the end.
+    minimum_samples: usize,
+}
+
+/// A model of a block that the SPRT decides on.
+struct Candidate<M> {
+    model: M,
+    generation: usize,
+    inlier_indices: Option<Vec<usize>>,
+    /// The number of inliers, the support and the sum of the residuals of the inliers if the model
+    /// was accepted.
+    scored: Option<(usize, f64, f64)>,
+}
+
+/// Algorithm 1 in "Randomized RANSAC with Sequential Probability Ratio Test".
+///
+/// This tests if a model is accepted. Returns `Some((inliers, support, residual_sum))` if accepted or
+/// `None` if rejected.
+/// The datapoints come with their indices, which are pushed to `inlier_indices` for the inliers if provided.
+fn sprt<Data, D: Borrow<Data>, M: Model<Data>>(
+    data: impl Iterator<Item = (usize, D)>,
+    model: &M,
+    sprt: Sprt,
+    mut inlier_indices: Option<&mut Vec<usize>>,
+) -> Option<(usize, f64, f64)> {
+    let mut likelihood_ratio = 1.0;
+    let mut inliers = 0;
+    let mut support = 0.0;
+    let mut residual_sum = 0.0;
+    for (ix, data) in data {
+        let residual = model.residual(data.borrow());
+        likelihood_ratio *= if sprt.scoring.is_inlier(residual) {
+            inliers += 1;
+            support += sprt.scoring.weight(residual);
+            residual_sum += residual;
+            if let Some(inlier_indices) = inlier_indices.as_deref_mut() {
+                inlier_indices.push(ix);
+            }
+            sprt.positive_likelihood_ratio
+        } else {
+            sprt.negative_likelihood_ratio
+        };
+
+        if likelihood_ratio > sprt.likelihood_ratio_threshold || likelihood_ratio.is_nan() {
+            return None;
+        }
+    }
+
+    (inliers >= sprt.minimum_samples).then_some((inliers, support, residual_sum))
+}
+
+/// Scores `hypothesis` over `block`, whose first datapoint has index `first`.
+fn score_block<Data, M: Model<Data>>(
+    hypothesis: &mut Hypothesis<M>,
+    block: &[Data],
+    first: usize,
+    scoring: Scoring,
+) {
+    for (ix, datapoint) in block.iter().enumerate() {
+        let residual = hypothesis.model.residual(datapoint);
+        if scoring.is_inlier(residual) {
+            hypothesis.inliers += 1;
+            hypothesis.support += scoring.weight(residual);
+            hypothesis.residual_sum += residual;
+            if let Some(inlier_indices) = &mut hypothesis.inlier_indices {
+                inlier_indices.push(first + ix);
+            }
+        }
+    }
+}
+
+/// The weight of the inliers of the block after one whose inliers weigh `scale`, and whether the
+/// support of everything before it must be divided by [`MAX_RECENCY_SCALE`] to keep up.
+///
+/// Dividing the older support by the weight for every block instead underflows to zero on long
+/// runs, while scaling the newer blocks up without bound overflows, so the scale is normalized once
+/// it gets large.
+fn recency_scale(scale: f64, recency_weight: f32) -> (f64, bool) {
+    let scale = scale * f64::from(recency_weight);
+    if scale > MAX_RECENCY_SCALE {
+        (scale / MAX_RECENCY_SCALE, true)
+    } else {
+        (scale, false)
+    }
+}
+
+/// Coalesces sorted indices into ranges of consecutive indices.
+fn inlier_ranges(inliers: &[usize]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = vec![];
+    for &ix in inliers {
+        match ranges.last_mut() {
+            Some(range) if range.end == ix => range.end += 1,
+            _ => ranges.push(ix..ix + 1),
+        }
+    }
+    ranges
+}
+
+/// The datapoints of `data` that `keep` marks.
+fn masked<'a, Data: 'a>(
+    data: impl Iterator<Item = Data> + Clone + 'a,
+    keep: &'a [bool],
+) -> impl Iterator<Item = Data> + Clone + 'a {
+    data.zip(keep)
+        .filter_map(|(data, &keep)| keep.then_some(data))
+}
+
+/// Marks the first datapoint of every set of datapoints that are duplicates of each other.
+fn first_occurrences<Data>(
+    data: impl Iterator<Item = Data> + Clone,
+    is_duplicate: &dyn Fn(&Data, &Data) -> bool,
+) -> Vec<bool> {
+    let mut first = Vec::new();
+    for datapoint in data.clone() {
+        let duplicate = data
+            .clone()
+            .zip(first.iter())
+            .any(|(other, &first)| first && is_duplicate(&other, &datapoint));
+        first.push(!duplicate);
+    }
+    first
+}
+
+/// The Jaccard distance between two sorted sets of indices, which is `1` if both are empty.
+fn jaccard_distance(a: &[usize], b: &[usize]) -> f64 {
+    let (mut i, mut j, mut common) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                common += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    let union = a.len() + b.len() - common;
+    if union == 0 {
+        return 1.0;
+    }
+    1.0 - common as f64 / union as f64
+}
+
+/// Removes the hypotheses whose model is the same as the model of an earlier hypothesis according
+/// to `is_same_model`, so the best of them is kept if the hypotheses are sorted. Returns how many
+/// were removed.
+fn dedupe_hypotheses<M>(
+    hypotheses: &mut Vec<Hypothesis<M>>,
+    is_same_model: &dyn Fn(&M, &M) -> bool,
+) -> usize {
+    let len = hypotheses.len();
+    let mut kept: Vec<Hypothesis<M>> = Vec::with_capacity(len);
+    for hypothesis in hypotheses.drain(..) {
+        if !kept
+            .iter()
+            .any(|other| is_same_model(&other.model, &hypothesis.model))
+        {
+            kept.push(hypothesis);
+        }
+    }
+    *hypotheses = kept;
+    len - hypotheses.len()
+}
+
+/// An optional relation between two datapoints, see [`Arrsac::locality_bias`] and
+/// [`Arrsac::dedupe_data`].
+type Relation<'a, Data> = Option<&'a dyn Fn(&Data, &Data) -> bool>;
+
+/// An optional relation between two models, see [`Arrsac::dedupe_hypotheses`].
+type ModelRelation<'a, M> = Option<&'a dyn Fn(&M, &M) -> bool>;
+
+/// An optional assignment of datapoints to one of a number of strata, see
+/// [`Arrsac::stratified_init`].
+type Strata<'a, Data> = Option<(&'a dyn Fn(&Data) -> usize, usize)>;
+
+/// An optional way to score hypotheses over a block of datapoints whose first datapoint has the
+/// given index, see [`Arrsac::parallel_scoring`].
+type BlockScorer<'a, Data, M> = Option<&'a dyn Fn(&mut [Hypothesis<M>], &[Data], usize, Scoring)>;
+
+/// An optional way to run the SPRT of the candidates of one estimation over the datapoints that the
+/// SPRT evaluates, in order, see [`Arrsac::parallel_scoring`].
+type CandidateScorer<'a, Data, M> = Option<&'a dyn Fn(&mut [Candidate<M>], &[(usize, Data)], Sprt)>;
+
+/// The optional hooks of a search, which are set by the adapters of [`Arrsac`].
+struct SearchHooks<'a, Data, M> {
+    /// See [`Arrsac::locality_bias`].
+    is_local: Relation<'a, Data>,
+    /// See [`Arrsac::dedupe_data`].
+    is_duplicate: Relation<'a, Data>,
+    /// See [`Arrsac::dedupe_hypotheses`].
+    is_same_model: ModelRelation<'a, M>,
+    /// See [`Arrsac::stratified_init`].
+    strata: Strata<'a, Data>,
+    /// See [`Arrsac::parallel_scoring`].
+    scorer: BlockScorer<'a, Data, M>,
+    /// See [`Arrsac::parallel_scoring`].
+    candidate_scorer: CandidateScorer<'a, Data, M>,
+}
+
+impl<Data, M> Default for SearchHooks<'_, Data, M> {
+    fn default() -> Self {
+        Self {
+            is_local: None,
+            is_duplicate: None,
+            is_same_model: None,
+            strata: None,
+            scorer: None,
+            candidate_scorer: None,
+        }
+    }
+}
+
+impl<Data, M> Clone for SearchHooks<'_, Data, M> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Data, M> Copy for SearchHooks<'_, Data, M> {}
+
+/// The hypotheses that [`Arrsac::populate_hypotheses_sprt`] adds in one block.
+#[derive(Debug, Clone, Copy)]
+struct Population {
+    delta: f32,
+    /// How many datapoints the hypotheses are tested on.
+    num_checked: usize,
+    /// How many estimations to generate hypotheses with.
+    estimations: usize,
+    /// The block the hypotheses are generated in.
+    block: usize,
+}
+
+/// The weight of the inliers of a block at which the support is normalized, see [`recency_scale`].
+const MAX_RECENCY_SCALE: f64 = 1e150;
+
+/// The inlier ratio of the worst initial hypothesis above which [`Report::suspicious_threshold`] is set.
+const SUSPICIOUS_INLIER_RATIO: f32 = 0.99;
+
+/// Diagnostics about the most recent run, see [`Arrsac::report`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Report {
+    /// Number of initial hypotheses that `epsilon` and `delta` were estimated from.
+    ///
+    /// If this is `0`, no initial hypotheses were generated, so `delta` could not be estimated
+    /// and the run terminated early. This usually means the estimator failed on all of the
+    /// initial samples.
+    pub delta_estimations: usize,
+    /// The estimator did not produce a single model during the initial phase, so the run
+    /// terminated without processing any blocks.
+    pub no_models_generated: bool,
+    /// The likelihood ratio threshold used by the SPRT, which is
+    /// [`ArrsacBuilder::likelihood_ratio_threshold`] unless it is computed at the start of each run
+    /// when using [`Arrsac::auto_likelihood_threshold`].
+    pub likelihood_ratio_threshold: f32,
+    /// The hook passed to [`Arrsac::inspect`] returned [`ControlFlow::Break`], so the run was
+    /// terminated early with the best hypothesis found until then.
+    pub stopped_early: bool,
+    /// The inlier threshold looks to be on a different scale than the residuals, such as a squared
+    /// distance threshold used with a linear distance residual.
+    ///
+    /// This is a heuristic set when even the worst initial hypothesis has nearly all of the initial
+    /// datapoints as inliers or when even the best one has no inliers besides its own sample. It is
+    /// neither guaranteed to catch a wrong threshold nor to only be set for a wrong threshold.
+    pub suspicious_threshold: bool,
+    /// The inlier ratio of the returned model over the datapoints it was scored on, or `None` if no
+    /// model was found.
+    pub epsilon: Option<f32>,
+    /// The number of datapoints each hypothesis was estimated from, see [`Arrsac::sample_size`].
+    pub sample_size: usize,
+    /// The number of hypotheses the estimator produced.
+    pub hypotheses_generated: usize,
+    /// The estimated inlier ratio of a bad model that the SPRT used, see [`Arrsac::min_delta`].
+    pub delta: f32,
+    /// The inlier ratio of the model with the most inliers that the SPRT used in the last block.
+    pub block_epsilon: f32,
+    /// The spread of the residuals of the inliers of the returned model, or `None` if no model
+    /// with inliers was returned.
+    pub inlier_residuals: Option<ResidualStats>,
+    /// The block in which the returned model was generated, where `0` is the initial phase and the
+    /// first block after it is `initialization_blocks`. This is `None` if no model was returned.
+    pub found_in_block: Option<usize>,
+    /// The inlier threshold used after the initial phase, or `None` if the configured one was used,
+    /// see [`Arrsac::adaptive_threshold_percentile`].
+    pub adaptive_inlier_threshold: Option<f64>,
+    /// The inlier threshold calibrated from the noise of the inliers of a first run, or `None` if
+    /// the configured one was used, see [`Arrsac::calibrate_threshold`].
+    pub calibrated_inlier_threshold: Option<f64>,
+    /// The inliers of the best initial hypothesis are suspiciously concentrated at the start of the
+    /// data, which suggests it wasn't shuffled, see [`Arrsac::assert_shuffled`].
+    pub possibly_unshuffled: bool,
+    /// The number of hypotheses that were removed because a better hypothesis had the same model,
+    /// see [`Arrsac::dedupe_hypotheses`].
+    pub duplicate_hypotheses: usize,
+    /// The number of times `Estimator::estimate()` was called, see
+    /// [`Arrsac::max_estimator_calls`].
+    pub estimator_calls: usize,
+}
+
+/// Summary statistics of the residuals of the inliers of a model, see [`Report::inlier_residuals`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResidualStats {
+    /// The smallest residual.
+    pub min: f64,
+    /// The largest residual.
+    pub max: f64,
+    /// The mean of the residuals.
+    pub mean: f64,
+    /// The (population) standard deviation of the residuals, which is a measure of their
+    /// dispersion that's suitable for accepting or rejecting a fit automatically.
+    pub std_dev: f64,
+}
+
+/// How well a model fits a dataset, see [`Arrsac::validate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidationStats {
+    /// The number of inliers.
+    pub inliers: usize,
+    /// The number of datapoints, including the outliers.
+    pub datapoints: usize,
+    /// The ratio of inliers to datapoints, which is `0.0` without datapoints.
+    pub inlier_ratio: f64,
+    /// The spread of the residuals of the inliers, which is `None` without inliers.
+    pub inlier_residuals: Option<ResidualStats>,
+}
+
+/// The model found by [`Arrsac::fit`] along with its inliers and the [`Report`] of the run.
+///
+/// This dereferences to the model, so it can mostly be used in place of it.
+#[derive(Debug, Clone)]
+#[must_use = "the model is the only result of running ARRSAC"]
+pub struct ModelResult<M> {
+    /// The best model found.
+    pub model: M,
+    /// The indices of the inliers of the model over all of the data.
+    pub inliers: Vec<usize>,
+    /// Diagnostics about the run that found the model.
+    pub report: Report,
+}
+
+impl<M> Deref for ModelResult<M> {
+    type Target = M;
+
+    fn deref(&self) -> &M {
+        &self.model
+    }
+}
+
+/// Something that happened during a run, which is passed to the hook given to [`Arrsac::inspect`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Event {
+    /// The estimator produced a hypothesis, which is the `generation`th one of the run.
+    HypothesisGenerated { generation: usize },
+    /// A hypothesis passed the SPRT with `inliers` out of the checked datapoints.
+    HypothesisAccepted { generation: usize, inliers: usize },
+    /// A hypothesis was rejected by the SPRT.
+    HypothesisRejected { generation: usize },
+    /// A block was evaluated, after which `hypotheses` remain scored on the first `datapoints`.
+    BlockCompleted {
+        block: usize,
+        datapoints: usize,
+        hypotheses: usize,
+    },
+}
+
+/// The hook passed to [`Arrsac::inspect`].
+type Inspect = Box<dyn FnMut(Event) -> ControlFlow<()> + Send + Sync>;
+
+/// The state of a run after a block, which is passed to the criterion given to
+/// [`Arrsac::terminate_when`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct TerminationContext {
+    /// The block that was just evaluated, numbered like [`Event::BlockCompleted`].
+    pub block: usize,
+    /// How many datapoints the hypotheses have been scored on.
+    pub datapoints: usize,
+    /// The number of inliers of the best hypothesis among those `datapoints`.
+    pub best_inliers: usize,
+    /// For how many blocks the best hypothesis has stayed the best, see [`ArrsacBuilder::patience`].
+    pub blocks_without_improvement: usize,
+    /// The inlier ratio of the best hypothesis the SPRT of the block used.
+    pub epsilon: f32,
+    /// The inlier ratio of outlier models the SPRT used.
+    pub delta: f32,
+    /// How many hypotheses have been generated so far.
+    pub hypotheses_generated: usize,
+    /// How many hypotheses survived the block.
+    pub survivors: usize,
+}
+
+/// The criterion passed to [`Arrsac::terminate_when`].
+type Terminate = Box<dyn FnMut(&TerminationContext) -> bool + Send + Sync>;
 
 /// The ARRSAC algorithm for sample consensus.
 ///
@@ -13,16 +839,109 @@ use sample_consensus::{Consensus, Estimator, Model};
 /// using this consensus process. It will not shuffle your data for you.
 /// If you do not shuffle, the output will be biased towards data at the beginning
 /// of the inputs.
+///
+/// The data is cloned and walked many times during a run, so every clone of the data iterator must
+/// yield the same datapoints in the same order, like iterators over slices do. Iterators that read
+/// from a stateful source, such as a channel or a random number generator, silently give wrong
+/// results unless they are collected first. Debug builds check that the number of datapoints is the
+/// same at the start and the end of the search, which catches some, but not all, such iterators.
+///
+/// Datapoints that are duplicates of each other are treated as distinct datapoints, so a minimal
+/// sample can consist of identical datapoints, which is degenerate. Use [`Arrsac::dedupe_data`] if
+/// the data has many duplicates.
+///
+/// When several models are tied for the most support, the one whose inliers have the lowest sum of
+/// residuals is returned, and the one that was generated first among those that are still tied, so
+/// results are reproducible for a given RNG state. See [`Arrsac::on_tie`] and
+/// [`Arrsac::best_models`] for other ways to deal with ties.
+///
+/// Runs are reproducible across platforms, such as x86 and ARM, given the same RNG state, data
+/// and residuals. Samples are drawn with integer arithmetic only, the SPRT multiplies the likelihood
+/// ratios in the order of the data, and everything else only uses basic floating-point arithmetic,
+/// which is rounded the same way on every platform, rather than functions like `exp` or `ln`, whose
+/// rounding depends on the platform. The residuals are computed by the model, so it must avoid such
+/// functions as well for the results to be bit-for-bit identical.
+///
+/// `Arrsac<R>` is [`Send`] and [`Sync`] whenever `R` is, so a configured instance can be moved to
+/// or shared with other threads. Runs take `&mut self`, so running on several threads at once needs
+/// an instance with its own RNG per thread.
 pub struct Arrsac<R> {
-    initialization_hypotheses: usize,
-    initialization_blocks: usize,
-    max_candidate_hypotheses: usize,
-    estimations_per_block: usize,
-    block_size: usize,
-    likelihood_ratio_threshold: f32,
-    inlier_threshold: f64,
+    config: ArrsacBuilder,
     rng: R,
-    random_samples: Vec<u32>,
+    random_samples: Samples,
+    rejected_samples: RejectedSamples,
+    /// The inliers of the best hypothesis of a block, which are kept to reuse the allocation.
+    best_inliers: Vec<usize>,
+    /// The number of random samples drawn from all of the data in this run, see
+    /// [`ArrsacBuilder::min_full_sampling_rounds`].
+    full_samples: usize,
+    report: Report,
+    residual_cache: cache::ResidualCache,
+    inspect: Option<Inspect>,
+    terminate: Option<Terminate>,
+    #[cfg(feature = "test-util")]
+    fixed_samples: Option<samples::FixedSamples>,
+    #[cfg(feature = "record-models")]
+    recorder: record::Recorder,
+    #[cfg(feature = "count-rng")]
+    rng_draws: u64,
+}
+
+impl Arrsac<()> {
+    /// Starts configuring an [`Arrsac`] without providing an RNG until [`ArrsacBuilder::build`].
+    ///
+    /// See [`Arrsac::new`] for how to choose the `inlier_threshold`.
+    pub fn builder(inlier_threshold: f64) -> ArrsacBuilder {
+        ArrsacBuilder::new(inlier_threshold)
+    }
+
+    /// The likelihood ratios the SPRT multiplies for an inlier and an outlier of a model, which are
+    /// `delta / epsilon` and `(1 - delta) / (1 - epsilon)` in that order.
+    ///
+    /// `epsilon` and `delta` are clamped to `[f64::EPSILON, 1 - f64::EPSILON]` first, so both ratios
+    /// are positive and finite even if `epsilon` is one, where the second ratio would otherwise be
+    /// infinite. The SPRT can only tell good models from outlier models if `epsilon` is above
+    /// `delta`, which makes the first ratio smaller than one and the second larger than one.
+    pub fn likelihood_ratios(epsilon: f32, delta: f32) -> (f32, f32) {
+        let (positive, negative) = likelihood_ratios(f64::from(epsilon), f64::from(delta));
+        (positive as f32, negative as f32)
+    }
+
+    /// The smallest number of datapoints after which the SPRT is guaranteed to reject a model
+    /// without any inliers, which is the smallest `n` such that
+    /// `((1 - delta) / (1 - epsilon))^n > likelihood_ratio_threshold`.
+    ///
+    /// Use this to choose a `block_size` (or [`ArrsacBuilder::initial_eval_size`]) for which
+    /// outlier models are rejected within a block, see [`Arrsac::new`]. This is `usize::MAX` if
+    /// `epsilon` isn't above `delta`, since no model is ever rejected then.
+    pub fn required_block_size(epsilon: f32, delta: f32, likelihood_ratio_threshold: f32) -> usize {
+        let (_, ratio) = Self::likelihood_ratios(epsilon, delta);
+        let rejects = |n: usize| powu(ratio, n) > likelihood_ratio_threshold;
+        if ratio.is_nan() || ratio <= 1.0 {
+            return usize::MAX;
+        }
+        if rejects(0) {
+            return 0;
+        }
+        // Find a power of two that rejects, then bisect below it.
+        let mut high = 1usize;
+        while !rejects(high) {
+            high = match high.checked_mul(2) {
+                Some(high) => high,
+                None => return usize::MAX,
+            };
+        }
+        let mut low = high / 2;
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if rejects(mid) {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+        high
+    }
 }
 
 impl<R> Arrsac<R>
@@ -41,135 +960,1376 @@ where
     /// `initial_epsilon` must be higher than `initial_delta`. If you modify these values,
     /// you need to make sure that within one `block_size` the `likelihood_ratio_threshold`
     /// can be reached and a model can be rejected. Basically, make sure that
-    /// `((1.0 - delta) / (1.0 - epsilon))^block_size >>> likelihood_ratio_threshold`.
-    /// This must be done to ensure outlier models are rejected during the initial generation
-    /// phase, which only processes `block_size` datapoints.
+    /// `((1.0 - delta) / (1.0 - epsilon))^block_size >>> likelihood_ratio_threshold`, for which
+    /// [`Arrsac::required_block_size`] computes the smallest `block_size`. This must be done to
+    /// ensure outlier models are rejected during the initial generation phase, which only processes
+    /// `block_size` datapoints.
     ///
     /// `initial_epsilon` should also be as large as you can set it where it is still relatively
     /// pessimistic. This is so that we can more easily reject a model early in the process
     /// to compute an updated value for delta during the adaptive process. This may not be possible
     /// and will depend on your data.
     pub fn new(inlier_threshold: f64, rng: R) -> Self {
+        ArrsacBuilder::new(inlier_threshold).build(rng)
+    }
+
+    /// Creates an [`Arrsac`] like [`Arrsac::new`] that reserves the indices of the inliers of the
+    /// best hypothesis for runs on about `expected_points` datapoints.
+    ///
+    /// These indices are the only buffer of an [`Arrsac`] that grows with the data, so this avoids
+    /// reallocating it when the number of datapoints is roughly known, such as in a loop over frames.
+    /// The hypotheses depend on the model type of each run, so they are still allocated per run. The
+    /// hint is only advisory, so runs on more or fewer datapoints still give the same results and just
+    /// reallocate as needed. The buffer is kept across runs either way.
+    pub fn with_capacity(inlier_threshold: f64, rng: R, expected_points: usize) -> Self {
+        let mut arrsac = Self::new(inlier_threshold, rng);
+        arrsac.best_inliers.reserve(expected_points);
+        arrsac
+    }
+
+    /// Creates an [`Arrsac`] that draws the sample indices from `samples` instead of `rng`.
+    ///
+    /// Every drawn index is the next one in `samples`, which starts over once all of them were used
+    /// and at the start of every run. The indices are used as is, so they must be distinct within
+    /// every sample and are relative to the data being sampled from, which is the subset of inliers
+    /// of the best hypothesis unless [`Arrsac::full_sampling_only`] is used. Drawing an index
+    /// beyond the data being sampled from panics.
+    ///
+    /// `rng` is still used for anything other than drawing samples, such as for
+    /// [`Arrsac::score_sample_fraction`]. This is only intended for testing.
+    #[cfg(feature = "test-util")]
+    pub fn with_fixed_samples(
+        inlier_threshold: f64,
+        rng: R,
+        samples: impl IntoIterator<Item = u32>,
+    ) -> Self {
         Self {
-            initialization_hypotheses: 256,
-            initialization_blocks: 4,
-            max_candidate_hypotheses: 64,
-            estimations_per_block: 64,
-            block_size: 64,
-            likelihood_ratio_threshold: 1e3,
-            inlier_threshold,
-            rng,
-            random_samples: vec![],
+            fixed_samples: Some(samples::FixedSamples::new(samples.into_iter().collect())),
+            ..Self::new(inlier_threshold, rng)
         }
     }
 
-    /// Number of models generated in the initial step when epsilon and delta are being estimated.
-    ///
-    /// Default: `256`
+    /// See [`ArrsacBuilder::initialization_hypotheses`].
     #[must_use]
     pub fn initialization_hypotheses(self, initialization_hypotheses: usize) -> Self {
-        Self {
-            initialization_hypotheses,
-            ..self
-        }
+        self.configure(|config| config.initialization_hypotheses(initialization_hypotheses))
     }
 
-    /// Number of data blocks used to compute the initial estimate of delta and epsilon
-    /// before proceeding with regular block processing. This is used instead of
-    /// an initial epsilon and delta, which were suggested by the paper.
-    ///
-    /// Default: `4`
+    /// See [`ArrsacBuilder::initialization_blocks`].
     #[must_use]
     pub fn initialization_blocks(self, initialization_blocks: usize) -> Self {
-        Self {
-            initialization_blocks,
-            ..self
-        }
+        self.configure(|config| config.initialization_blocks(initialization_blocks))
     }
 
-    /// Maximum number of best hypotheses to retain during block processing
-    ///
-    /// This number is halved on each block such that on block `n` the number of
-    /// hypotheses retained is `max_candidate_hypotheses >> n`.
-    ///
-    /// Default: `64`
+    /// See [`ArrsacBuilder::max_candidate_hypotheses`].
     #[must_use]
     pub fn max_candidate_hypotheses(self, max_candidate_hypotheses: usize) -> Self {
-        Self {
-            max_candidate_hypotheses,
-            ..self
-        }
+        self.configure(|config| config.max_candidate_hypotheses(max_candidate_hypotheses))
     }
 
-    /// Number of estmations (may generate multiple hypotheses) that will be ran
-    /// for each block of data evaluated
-    ///
-    /// Default: `64`
+    /// See [`ArrsacBuilder::estimations_per_block`].
     #[must_use]
     pub fn estimations_per_block(self, estimations_per_block: usize) -> Self {
-        Self {
-            estimations_per_block,
-            ..self
-        }
+        self.configure(|config| config.estimations_per_block(estimations_per_block))
     }
 
-    /// Number of data points evaluated before more hypotheses are generated
-    ///
-    /// Default: `64`
+    /// See [`ArrsacBuilder::block_size`].
     #[must_use]
     pub fn block_size(self, block_size: usize) -> Self {
-        Self { block_size, ..self }
+        self.configure(|config| config.block_size(block_size))
     }
 
-    /// Once a model reaches this level of unlikelihood, it is rejected. Set this
-    /// higher to make it less restrictive, usually at the cost of more execution time.
-    ///
-    /// Increasing this will make it more likely to find a good result.
-    ///
-    /// Decreasing this will speed up execution.
-    ///
-    /// This ratio is not exposed as a parameter in the original paper, but is instead computed
-    /// recursively for a few iterations. It is roughly equivalent to the **reciprocal** of the
-    /// **probability of rejecting a good model**. You can use that to control the probability
-    /// that a good model is rejected.
-    ///
-    /// Default: `1e3`
+    /// See [`ArrsacBuilder::geometric_blocks`].
+    #[must_use]
+    pub fn geometric_blocks(self, factor: f32) -> Self {
+        self.configure(|config| config.geometric_blocks(factor))
+    }
+
+    /// See [`ArrsacBuilder::score_sample_fraction`].
+    #[must_use]
+    pub fn score_sample_fraction(self, fraction: f32) -> Self {
+        self.configure(|config| config.score_sample_fraction(fraction))
+    }
+
+    /// See [`ArrsacBuilder::block_eval_stride`].
+    #[must_use]
+    pub fn block_eval_stride(self, stride: usize) -> Self {
+        self.configure(|config| config.block_eval_stride(stride))
+    }
+
+    /// See [`ArrsacBuilder::likelihood_ratio_threshold`].
     #[must_use]
     pub fn likelihood_ratio_threshold(self, likelihood_ratio_threshold: f32) -> Self {
-        Self {
-            likelihood_ratio_threshold,
-            ..self
-        }
+        self.configure(|config| config.likelihood_ratio_threshold(likelihood_ratio_threshold))
+    }
+
+    /// See [`ArrsacBuilder::auto_likelihood_threshold`].
+    #[must_use]
+    pub fn auto_likelihood_threshold(self, target_reject_prob: f32) -> Self {
+        self.configure(|config| config.auto_likelihood_threshold(target_reject_prob))
     }
 
-    /// Residual threshold for determining if a data point is an inlier or an outlier of a model
+    /// See [`ArrsacBuilder::inlier_threshold`].
     #[must_use]
     pub fn inlier_threshold(self, inlier_threshold: f64) -> Self {
-        Self {
-            inlier_threshold,
-            ..self
-        }
+        self.configure(|config| config.inlier_threshold(inlier_threshold))
     }
 
-    /// Adapted from algorithm 3 from "A Comparative Analysis of RANSAC Techniques Leading to Adaptive
-    /// Real-Time Random Sample Consensus", but it was effectively rewritten to avoid the need for
-    /// initial epsilon and delta.
-    ///
-    /// Returns the initial models (and their num inliers) sorted by decreasing inliers
-    /// and `delta` in that order.
-    fn initial_hypotheses<E, Data>(
-        &mut self,
-        estimator: &E,
-        data: impl Iterator<Item = Data> + Clone,
-    ) -> (Vec<(E::Model, usize)>, f32)
-    where
-        E: Estimator<Data>,
-    {
-        assert!(
-            self.initialization_blocks > 0,
-            "ARRSAC must have at least 1 initialization block"
-        );
-        // NOTE: This whole function is different than that specified in the ARRSAC paper.
+    /// See [`ArrsacBuilder::soft_inliers`].
+    #[must_use]
+    pub fn soft_inliers(self, weight_fn: WeightFn) -> Self {
+        self.configure(|config| config.soft_inliers(weight_fn))
+    }
+
+    /// See [`ArrsacBuilder::skip_initial_phase`].
+    #[must_use]
+    pub fn skip_initial_phase(self, skip_initial_phase: bool) -> Self {
+        self.configure(|config| config.skip_initial_phase(skip_initial_phase))
+    }
+
+    /// See [`ArrsacBuilder::initial_estimates`].
+    #[must_use]
+    pub fn initial_estimates(self, epsilon: f32, delta: f32) -> Self {
+        self.configure(|config| config.initial_estimates(epsilon, delta))
+    }
+
+    /// See [`ArrsacBuilder::full_sampling_only`].
+    #[must_use]
+    pub fn full_sampling_only(self, full_sampling_only: bool) -> Self {
+        self.configure(|config| config.full_sampling_only(full_sampling_only))
+    }
+
+    /// See [`ArrsacBuilder::min_full_sampling_rounds`].
+    #[must_use]
+    pub fn min_full_sampling_rounds(self, min_full_sampling_rounds: usize) -> Self {
+        self.configure(|config| config.min_full_sampling_rounds(min_full_sampling_rounds))
+    }
+
+    /// See [`ArrsacBuilder::incremental_inliers`].
+    #[must_use]
+    pub fn incremental_inliers(self, incremental_inliers: bool) -> Self {
+        self.configure(|config| config.incremental_inliers(incremental_inliers))
+    }
+
+    /// See [`ArrsacBuilder::sample_size`].
+    #[must_use]
+    pub fn sample_size(self, sample_size: usize) -> Self {
+        self.configure(|config| config.sample_size(sample_size))
+    }
+
+    /// See [`ArrsacBuilder::asprt_order`].
+    #[must_use]
+    pub fn asprt_order(self, asprt_order: AsprtOrder) -> Self {
+        self.configure(|config| config.asprt_order(asprt_order))
+    }
+
+    /// See [`ArrsacBuilder::dedupe_samples_global`].
+    #[must_use]
+    pub fn dedupe_samples_global(self, dedupe_samples_global: bool) -> Self {
+        self.configure(|config| config.dedupe_samples_global(dedupe_samples_global))
+    }
+
+    /// See [`ArrsacBuilder::min_delta`].
+    #[must_use]
+    pub fn min_delta(self, min_delta: f32) -> Self {
+        self.configure(|config| config.min_delta(min_delta))
+    }
+
+    /// See [`ArrsacBuilder::inlier_window`].
+    #[must_use]
+    pub fn inlier_window(self, inlier_window: usize) -> Self {
+        self.configure(|config| config.inlier_window(inlier_window))
+    }
+
+    /// See [`ArrsacBuilder::adaptive_threshold_percentile`].
+    #[must_use]
+    pub fn adaptive_threshold_percentile(self, adaptive_threshold_percentile: f32) -> Self {
+        self.configure(|config| config.adaptive_threshold_percentile(adaptive_threshold_percentile))
+    }
+
+    /// See [`ArrsacBuilder::assert_shuffled`].
+    #[must_use]
+    pub fn assert_shuffled(self, assert_shuffled: bool) -> Self {
+        self.configure(|config| config.assert_shuffled(assert_shuffled))
+    }
+
+    /// See [`ArrsacBuilder::report_threshold`].
+    #[must_use]
+    pub fn report_threshold(self, report_threshold: f64) -> Self {
+        self.configure(|config| config.report_threshold(report_threshold))
+    }
+
+    /// See [`ArrsacBuilder::threshold_tolerance`].
+    #[must_use]
+    pub fn threshold_tolerance(self, threshold_tolerance: f64) -> Self {
+        self.configure(|config| config.threshold_tolerance(threshold_tolerance))
+    }
+
+    /// See [`ArrsacBuilder::patience`].
+    #[must_use]
+    pub fn patience(self, patience: usize) -> Self {
+        self.configure(|config| config.patience(patience))
+    }
+
+    /// See [`ArrsacBuilder::initial_eval_size`].
+    #[must_use]
+    pub fn initial_eval_size(self, initial_eval_size: usize) -> Self {
+        self.configure(|config| config.initial_eval_size(initial_eval_size))
+    }
+
+    /// See [`ArrsacBuilder::max_blocks`].
+    #[must_use]
+    pub fn max_blocks(self, max_blocks: usize) -> Self {
+        self.configure(|config| config.max_blocks(max_blocks))
+    }
+
+    /// See [`ArrsacBuilder::min_estimations_per_block`].
+    #[must_use]
+    pub fn min_estimations_per_block(self, min_estimations_per_block: usize) -> Self {
+        self.configure(|config| config.min_estimations_per_block(min_estimations_per_block))
+    }
+
+    /// See [`ArrsacBuilder::full_scan_small_data`].
+    #[must_use]
+    pub fn full_scan_small_data(self, full_scan_small_data: bool) -> Self {
+        self.configure(|config| config.full_scan_small_data(full_scan_small_data))
+    }
+
+    /// See [`ArrsacBuilder::max_survivors_bytes`].
+    #[must_use]
+    pub fn max_survivors_bytes(self, max_survivors_bytes: usize) -> Self {
+        self.configure(|config| config.max_survivors_bytes(max_survivors_bytes))
+    }
+
+    /// See [`ArrsacBuilder::recency_weight`].
+    #[must_use]
+    pub fn recency_weight(self, recency_weight: f32) -> Self {
+        self.configure(|config| config.recency_weight(recency_weight))
+    }
+
+    /// See [`ArrsacBuilder::seed_samples`].
+    #[must_use]
+    pub fn seed_samples(self, seed_samples: Vec<Vec<u32>>) -> Self {
+        self.configure(|config| config.seed_samples(seed_samples))
+    }
+
+    /// See [`ArrsacBuilder::on_tie`].
+    #[must_use]
+    pub fn on_tie(self, on_tie: TiePolicy) -> Self {
+        self.configure(|config| config.on_tie(on_tie))
+    }
+
+    /// See [`ArrsacBuilder::calibrate_threshold`].
+    #[must_use]
+    pub fn calibrate_threshold(self, k: f64) -> Self {
+        self.configure(|config| config.calibrate_threshold(k))
+    }
+
+    /// See [`ArrsacBuilder::max_estimator_calls`].
+    #[must_use]
+    pub fn max_estimator_calls(self, max_estimator_calls: usize) -> Self {
+        self.configure(|config| config.max_estimator_calls(max_estimator_calls))
+    }
+
+    /// See [`ArrsacBuilder::residual_cache`].
+    #[must_use]
+    pub fn residual_cache(self, residual_cache: bool) -> Self {
+        self.configure(|config| config.residual_cache(residual_cache))
+    }
+
+    /// See [`ArrsacBuilder::usable_hypothesis_threshold`].
+    #[must_use]
+    pub fn usable_hypothesis_threshold(
+        self,
+        usable_hypothesis_threshold: UsableHypothesisThreshold,
+    ) -> Self {
+        self.configure(|config| config.usable_hypothesis_threshold(usable_hypothesis_threshold))
+    }
+
+    /// Applies a change to the configuration.
+    fn configure(self, f: impl FnOnce(ArrsacBuilder) -> ArrsacBuilder) -> Self {
+        Self {
+            config: f(self.config),
+            ..self
+        }
+    }
+
+    /// Calls `f` with every [`Event`] of subsequent runs, which replaces any previous hook.
+    ///
+    /// Returning [`ControlFlow::Break`] terminates the run as soon as possible, which then returns
+    /// the best hypothesis found so far, scored over all of the data. No further events are reported
+    /// for that run and [`Report::stopped_early`] is set.
+    ///
+    /// The hook has to be [`Send`] and [`Sync`] so that it doesn't prevent the [`Arrsac`] from being
+    /// either, which can be achieved by keeping any shared state in a `Mutex`.
+    #[must_use]
+    pub fn inspect(self, f: impl FnMut(Event) -> ControlFlow<()> + Send + Sync + 'static) -> Self {
+        Self {
+            inspect: Some(Box::new(f)),
+            ..self
+        }
+    }
+
+    /// Calls `f` after every block of subsequent runs and stops the search once it returns `true`,
+    /// which replaces any previous criterion.
+    ///
+    /// This encodes arbitrary stopping logic on top of the built-in criteria, which still apply.
+    /// For example, [`ArrsacBuilder::max_blocks`] and [`ArrsacBuilder::patience`] are equivalent to
+    /// criteria on [`TerminationContext::block`] and
+    /// [`TerminationContext::blocks_without_improvement`]. The run then returns the best hypothesis
+    /// found so far, scored over all of the data, like when a built-in criterion stops it.
+    ///
+    /// Like [`Arrsac::inspect`], the criterion has to be [`Send`] and [`Sync`].
+    #[must_use]
+    pub fn terminate_when(
+        self,
+        f: impl FnMut(&TerminationContext) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            terminate: Some(Box::new(f)),
+            ..self
+        }
+    }
+
+    /// How many numbers were drawn from the RNG since it was created or last reseeded.
+    ///
+    /// Two runs only consume the RNG identically if they draw the same number of times, so
+    /// comparing the counts helps to find out why two runs that should be identical diverged, such
+    /// as a sample being redrawn a different number of times.
+    #[cfg(feature = "count-rng")]
+    pub fn rng_draws(&self) -> u64 {
+        self.rng_draws
+    }
+
+    /// Draws a number from the RNG, which every use of the RNG goes through so that it can be
+    /// counted, see [`Arrsac::rng_draws`].
+    fn next_u32(&mut self) -> u32 {
+        #[cfg(feature = "count-rng")]
+        {
+            self.rng_draws += 1;
+        }
+        self.rng.next_u32()
+    }
+
+    /// Diagnostics about the most recent run.
+    pub fn report(&self) -> &Report {
+        &self.report
+    }
+
+    /// Clears all scratch state retained between runs, including the [`Report`].
+    ///
+    /// This is called automatically at the start of every run, so it is only necessary to call
+    /// this manually if you want to release the retained state before switching datasets.
+    pub fn reset_state(&mut self) {
+        self.random_samples.clear();
+        self.rejected_samples.clear();
+        self.full_samples = 0;
+        self.report = Report {
+            likelihood_ratio_threshold: self.config.likelihood_ratio_threshold,
+            ..Report::default()
+        };
+        #[cfg(feature = "test-util")]
+        if let Some(fixed_samples) = &mut self.fixed_samples {
+            fixed_samples.rewind();
+        }
+        #[cfg(feature = "record-models")]
+        self.recorder.clear();
+    }
+
+    /// An upper bound on how many hypotheses a run on `data_len` datapoints tests with the SPRT,
+    /// computed from the configuration alone.
+    ///
+    /// This takes the truncation of the hypotheses after every block into account, but assumes that
+    /// every estimation produces one hypothesis and that the run only stops once the truncation
+    /// leaves a single hypothesis, the data runs out or [`Arrsac::max_blocks`] is reached. Multiply
+    /// it by the number of hypotheses an estimator can produce from one sample for estimators that
+    /// produce several. With [`Arrsac::calibrate_threshold`], a run searches the data twice, so this
+    /// counts both searches.
+    pub fn estimated_hypothesis_evaluations(&self, data_len: usize) -> usize {
+        let mut evaluations = if self.config.skip_initial_phase {
+            0
+        } else {
+            self.config.initialization_hypotheses
+        };
+        // This follows the blocks of `search_block`, which only tests hypotheses after a full block.
+        let mut block = self.config.initialization_blocks;
+        let mut block_start = self.initial_datapoints();
+        let mut block_size = self.config.block_size as f32;
+        loop {
+            let block_end = block_start.saturating_add((block_size as usize).max(1));
+            if block_end > data_len
+                || block - self.config.initialization_blocks >= self.config.max_blocks
+            {
+                break;
+            }
+            evaluations = evaluations.saturating_add(self.config.estimations_per_block);
+            if self.retained_hypotheses(block) <= 1 || block_end == usize::MAX {
+                break;
+            }
+            block += 1;
+            block_start = block_end;
+            block_size *= self.config.block_growth;
+        }
+        if self.config.calibrate_threshold.is_some() {
+            evaluations = evaluations.saturating_mul(2);
+        }
+        evaluations
+    }
+
+    /// The probability that at least one of the hypotheses of the most recent run was generated
+    /// from a sample of only inliers, which is `1 - (1 - ε^m)^N`.
+    ///
+    /// Here `ε` is [`Report::epsilon`], `m` is [`Report::sample_size`] and `N` is
+    /// [`Report::hypotheses_generated`]. This assumes every sample was drawn from all of the data,
+    /// so it underestimates the probability when sampling from the inliers of the best hypothesis.
+    /// This is `None` if no model was found.
+    pub fn success_probability(&self) -> Option<f32> {
+        let epsilon = f64::from(self.report.epsilon?);
+        let all_inliers = (0..self.report.sample_size).fold(1.0, |p, _| p * epsilon);
+        Some(complement_powu(all_inliers, self.report.hypotheses_generated) as f32)
+    }
+
+    /// Assigns the generation index of the next generated hypothesis.
+    fn next_generation(&mut self) -> usize {
+        let generation = self.report.hypotheses_generated;
+        self.report.hypotheses_generated += 1;
+        self.emit(Event::HypothesisGenerated { generation });
+        generation
+    }
+
+    /// Passes `event` to the hook given to [`Arrsac::inspect`] unless the run was stopped.
+    fn emit(&mut self, event: Event) {
+        if let Some(inspect) = &mut self.inspect {
+            if !self.report.stopped_early && inspect(event).is_break() {
+                self.report.stopped_early = true;
+            }
+        }
+    }
+
+    /// Runs ARRSAC and returns the surviving hypotheses, each scored over all of the data.
+    ///
+    /// This is empty if no model could be found.
+    fn survivors<E, Data>(
+        &mut self,
+        estimator: &E,
+        data: impl Iterator<Item = Data> + Clone,
+        hooks: SearchHooks<'_, Data, E::Model>,
+    ) -> Vec<Hypothesis<E::Model>>
+    where
+        E: Estimator<Data>,
+    {
+        // Make sure nothing from a previous run leaks into this one.
+        self.reset_state();
+        if let Some(k) = self.config.calibrate_threshold {
+            // Find a rough model with the configured threshold to calibrate the threshold with.
+            let rough = self.search_data(estimator, data.clone(), hooks);
+            let calibrated = rough
+                .into_iter()
+                .min_by(compare_hypotheses)
+                .and_then(|best| self.calibrated_threshold(data.clone(), &best.model, k));
+            self.reset_state();
+            self.report.calibrated_inlier_threshold = calibrated;
+        }
+        self.search_data(estimator, data, hooks)
+    }
+
+    /// Searches `data`, or a sample of it, for the surviving hypotheses, see [`Arrsac::survivors`].
+    fn search_data<E, Data>(
+        &mut self,
+        estimator: &E,
+        data: impl Iterator<Item = Data> + Clone,
+        hooks: SearchHooks<'_, Data, E::Model>,
+    ) -> Vec<Hypothesis<E::Model>>
+    where
+        E: Estimator<Data>,
+    {
+        let Some(keep) = self.search_mask(data.clone(), hooks.is_duplicate) else {
+            return self.search(estimator, data, hooks).0;
+        };
+        let (mut hypotheses, block_starts) =
+            self.search(estimator, masked(data.clone(), &keep), hooks);
+        self.rescore_masked(data, &mut hypotheses, &block_starts, &keep);
+        hypotheses
+    }
+
+    /// Marks the datapoints of `data` to search on, which is `None` to search on all of them, see
+    /// [`Arrsac::dedupe_data`], [`ArrsacBuilder::score_sample_fraction`] and
+    /// [`ArrsacBuilder::block_eval_stride`].
+    fn search_mask<Data>(
+        &mut self,
+        data: impl Iterator<Item = Data> + Clone,
+        is_duplicate: Relation<'_, Data>,
+    ) -> Option<Vec<bool>> {
+        // Only search on the first of every set of duplicates, see [`Arrsac::dedupe_data`].
+        let mut keep =
+            is_duplicate.map(|is_duplicate| first_occurrences(data.clone(), is_duplicate));
+        if self.config.score_sample_fraction < 1.0 {
+            // Randomly choose the data to search on. The initialization datapoints are always kept so
+            // that epsilon and delta are estimated the same way.
+            let initial_datapoints = self.initial_datapoints();
+            let threshold = (f64::from(self.config.score_sample_fraction) * 4294967296.0) as u64;
+            let keep = keep.get_or_insert_with(|| vec![true; data.clone().count()]);
+            for (ix, keep) in keep.iter_mut().enumerate() {
+                *keep =
+                    *keep && (ix < initial_datapoints || u64::from(self.next_u32()) < threshold);
+            }
+        }
+        let stride = self.config.block_eval_stride;
+        if stride > 1 {
+            // Keep every `stride`-th datapoint after the initialization datapoints.
+            let initial_datapoints = self.initial_datapoints();
+            let keep = keep.get_or_insert_with(|| vec![true; data.clone().count()]);
+            // `is_multiple_of` needs a newer Rust than the crate does.
+            #[allow(clippy::manual_is_multiple_of)]
+            for (ix, keep) in keep.iter_mut().enumerate().skip(initial_datapoints) {
+                *keep = *keep && (ix - initial_datapoints) % stride == 0;
+            }
+        }
+        keep
+    }
+
+    /// Scores the survivors of a search on the datapoints of `data` that `keep` marks over all of
+    /// `data`, where the blocks of the search started at `block_starts` of the marked datapoints.
+    fn rescore_masked<Data, M: Model<Data>>(
+        &mut self,
+        data: impl Iterator<Item = Data> + Clone,
+        hypotheses: &mut [Hypothesis<M>],
+        block_starts: &[usize],
+        keep: &[bool],
+    ) {
+        self.score_hypotheses(data.clone(), hypotheses);
+        if self.config.recency_weight != 1.0 {
+            // The blocks start at indices into the searched datapoints, which are a subset of the data.
+            let kept: Vec<usize> = (0..keep.len()).filter(|&ix| keep[ix]).collect();
+            let block_starts: Vec<usize> = block_starts
+                .iter()
+                .map(|&start| kept.get(start).copied().unwrap_or(keep.len()))
+                .collect();
+            self.weigh_recency(data, hypotheses, &block_starts);
+        }
+        self.record_epsilon(hypotheses, keep.len().min(self.config.inlier_window));
+    }
+
+    /// Runs ARRSAC on `data` and returns the surviving hypotheses, each scored over all of `data`, and
+    /// where the blocks of the search started.
+    fn search<E, Data>(
+        &mut self,
+        estimator: &E,
+        data: impl Iterator<Item = Data> + Clone,
+        hooks: SearchHooks<'_, Data, E::Model>,
+    ) -> (Vec<Hypothesis<E::Model>>, Vec<usize>)
+    where
+        E: Estimator<Data>,
+    {
+        match self.start_search(estimator, data.clone(), hooks) {
+            Some(mut state) => {
+                self.advance_search(estimator, data.clone(), &mut state, usize::MAX, hooks);
+                self.finish_search(data, state)
+            }
+            None => (vec![], vec![]),
+        }
+    }
+
+    /// Generates the initial hypotheses, which is `None` if initialization failed.
+    fn start_search<E, Data>(
+        &mut self,
+        estimator: &E,
+        data: impl Iterator<Item = Data> + Clone,
+        hooks: SearchHooks<'_, Data, E::Model>,
+    ) -> Option<SearchState<E::Model>>
+    where
+        E: Estimator<Data>,
+    {
+        let sample_size = self.samples_per_hypothesis(E::MIN_SAMPLES);
+        self.report.sample_size = sample_size;
+        assert!(
+            sample_size >= E::MIN_SAMPLES,
+            "ARRSAC must sample at least the minimum number of samples of the estimator"
+        );
+        // Don't do anything if we don't have enough data.
+        let data_len = data.clone().count();
+        if data_len < sample_size {
+            return None;
+        }
+        // Generate the initial set of hypotheses. This also gets us an estimate of delta.
+        let (mut hypotheses, delta) = if self.config.skip_initial_phase {
+            self.fixed_initial_hypotheses(estimator, data.clone(), hooks)
+        } else {
+            self.initial_hypotheses(estimator, data.clone(), hooks)
+        };
+
+        // If there are no initial hypotheses then initialization failed, so exit early.
+        if hypotheses.is_empty() {
+            return None;
+        }
+        let initial_datapoints = core::cmp::min(self.initial_datapoints(), data_len);
+        if self.config.assert_shuffled {
+            self.report.possibly_unshuffled =
+                self.is_front_loaded(data.clone(), &hypotheses[0].model);
+        }
+        if let Some(percentile) = self.config.adaptive_threshold_percentile {
+            self.adapt_inlier_threshold(
+                data.clone().take(initial_datapoints),
+                &mut hypotheses,
+                percentile,
+            );
+        }
+
+        Some(SearchState {
+            best_generation: hypotheses.first().map(|best| best.generation),
+            blocks_without_improvement: 0,
+            hypotheses,
+            delta,
+            // Track how many datapoints the hypotheses have been scored on.
+            num_scored: initial_datapoints,
+            data_len,
+            // This starts at the first block that was not evaluated in initial_hypotheses.
+            // Every initial hypothesis was counted over all of the initial datapoints, since the SPRT
+            // only accepts a hypothesis after checking all of them, so the blocks continue after them.
+            block: self.config.initialization_blocks,
+            samples_up_to_end_of_previous_block: self.initial_datapoints(),
+            // Every searched datapoint stands for `stride` of all of the data, see
+            // [`ArrsacBuilder::block_eval_stride`], so the blocks span as much of it as usual.
+            next_block_size: (self.config.block_size as f32 / self.config.block_eval_stride as f32)
+                .max(1.0),
+            done: self.config.max_blocks == 0,
+            block_starts: vec![0],
+            recency_scale: 1.0,
+        })
+    }
+
+    /// Checks if the inliers of `model` are concentrated at the start of `data`, see
+    /// [`ArrsacBuilder::assert_shuffled`].
+    fn is_front_loaded<Data, M: Model<Data>>(
+        &self,
+        data: impl Iterator<Item = Data>,
+        model: &M,
+    ) -> bool {
+        let mut len = 0;
+        let mut inliers = 0;
+        let mut index_sum = 0.0;
+        for (ix, data) in data.enumerate() {
+            len += 1;
+            if self.is_inlier(model.residual(&data)) {
+                inliers += 1;
+                index_sum += ix as f64;
+            }
+        }
+        if inliers == 0 {
+            return false;
+        }
+        // The mean of `inliers` indices drawn uniformly from `0..len` has this mean and variance.
+        let inliers = inliers as f64;
+        let expected = (len as f64 - 1.0) / 2.0;
+        let variance = (len as f64 * len as f64 - 1.0) / 12.0 / inliers;
+        let deviation = expected - index_sum / inliers;
+        // Flag a mean index more than four standard deviations towards the start.
+        deviation > 0.0 && deviation * deviation > 16.0 * variance
+    }
+
+    /// Sets the inlier threshold to the `percentile` of the residuals of the best of `hypotheses` over
+    /// the initialization datapoints `data` and scores `hypotheses` with it again.
+    fn adapt_inlier_threshold<Data, M: Model<Data>>(
+        &mut self,
+        data: impl Iterator<Item = Data> + Clone,
+        hypotheses: &mut [Hypothesis<M>],
+        percentile: f32,
+    ) {
+        let mut quantile = P2Quantile::new(f64::from(percentile));
+        for residual in data.clone().map(|data| hypotheses[0].model.residual(&data)) {
+            // Non-finite residuals are always outliers, so they only count as large residuals.
+            quantile.push(if residual.is_finite() {
+                residual
+            } else {
+                f64::MAX
+            });
+        }
+        match quantile.quantile() {
+            Some(threshold) if threshold > 0.0 => {
+                self.report.adaptive_inlier_threshold = Some(threshold)
+            }
+            _ => return,
+        }
+        for hypothesis in hypotheses.iter_mut() {
+            let mut inlier_indices = self.tracked_inliers();
+            (
+                hypothesis.inliers,
+                hypothesis.support,
+                hypothesis.residual_sum,
+            ) = self.count_inliers(data.clone(), &hypothesis.model, inlier_indices.as_mut());
+            hypothesis.inlier_indices = inlier_indices;
+        }
+        sort_hypotheses(hypotheses);
+    }
+
+    /// Evaluates up to `max_blocks` more blocks, stopping early once the search is done.
+    fn advance_search<E, Data>(
+        &mut self,
+        estimator: &E,
+        data: impl Iterator<Item = Data> + Clone,
+        state: &mut SearchState<E::Model>,
+        max_blocks: usize,
+        hooks: SearchHooks<'_, Data, E::Model>,
+    ) where
+        E: Estimator<Data>,
+    {
+        // Gradually increase how many datapoints we are evaluating until we evaluate them all.
+        for _ in 0..max_blocks {
+            if state.done {
+                break;
+            }
+            self.search_block(estimator, data.clone(), state, hooks);
+        }
+    }
+
+    /// Evaluates the next block of the search.
+    fn search_block<E, Data>(
+        &mut self,
+        estimator: &E,
+        data: impl Iterator<Item = Data> + Clone,
+        state: &mut SearchState<E::Model>,
+        hooks: SearchHooks<'_, Data, E::Model>,
+    ) where
+        E: Estimator<Data>,
+    {
+        let block = state.block;
+        state.block += 1;
+        let samples_up_to_beginning_of_block = state.samples_up_to_end_of_previous_block;
+        let samples_up_to_end_of_block = samples_up_to_beginning_of_block
+            .saturating_add((state.next_block_size as usize).max(1));
+        state.samples_up_to_end_of_previous_block = samples_up_to_end_of_block;
+        state.next_block_size *= self.config.block_growth;
+        // Score the hypotheses with the new datapoints.
+        let new_datapoints: Vec<Data> = data
+            .clone()
+            .skip(samples_up_to_beginning_of_block)
+            .take(samples_up_to_end_of_block - samples_up_to_beginning_of_block)
+            .collect();
+        if !new_datapoints.is_empty() {
+            state.block_starts.push(samples_up_to_beginning_of_block);
+            // Everything scored so far is older than the new datapoints.
+            let (scale, normalize) = recency_scale(state.recency_scale, self.config.recency_weight);
+            state.recency_scale = scale;
+            if normalize {
+                for hypothesis in state.hypotheses.iter_mut() {
+                    hypothesis.support /= MAX_RECENCY_SCALE;
+                }
+            }
+        }
+        let scoring = Scoring {
+            recency_scale: state.recency_scale,
+            ..self.scoring()
+        };
+        match hooks.scorer {
+            Some(scorer) => scorer(
+                &mut state.hypotheses,
+                &new_datapoints,
+                samples_up_to_beginning_of_block,
+                scoring,
+            ),
+            None => {
+                for hypothesis in state.hypotheses.iter_mut() {
+                    score_block(
+                        hypothesis,
+                        &new_datapoints,
+                        samples_up_to_beginning_of_block,
+                        scoring,
+                    );
+                }
+            }
+        }
+        if !new_datapoints.is_empty() {
+            state.num_scored = samples_up_to_beginning_of_block + new_datapoints.len();
+        }
+        if samples_up_to_beginning_of_block + new_datapoints.len() < samples_up_to_end_of_block {
+            // We reached the last datapoint, so the search is done.
+            state.done = true;
+            return;
+        }
+        // Sort the hypotheses by their support to find the best.
+        sort_hypotheses(&mut state.hypotheses);
+        // Populate hypotheses with hypotheses that pass SPRT.
+        let estimations = self.block_estimations(
+            data.clone().take(samples_up_to_end_of_block),
+            &state.hypotheses,
+        );
+        let num_scored_hypotheses = state.hypotheses.len();
+        let population = Population {
+            delta: state.delta,
+            num_checked: samples_up_to_end_of_block,
+            estimations,
+            block,
+        };
+        self.populate_hypotheses_sprt(
+            estimator,
+            &mut state.hypotheses,
+            data.clone(),
+            population,
+            hooks,
+        );
+        // The new hypotheses must be weighted like the ones that were scored block by block.
+        if self.config.recency_weight != 1.0 {
+            self.weigh_recency(
+                data.take(samples_up_to_end_of_block),
+                &mut state.hypotheses[num_scored_hypotheses..],
+                &state.block_starts,
+            );
+        }
+        // This will retain at least half of the hypotheses each time
+        // and gradually decrease as the number of samples we are evaluating increases.
+        // NOTE:
+        // The paper says to use a peculiar formula that just results in doing
+        // this basic right shift below, but as written it contained some apparent errors in
+        // where it was ran. This seems to be the correct location to do this.
+        sort_hypotheses(&mut state.hypotheses);
+        self.remove_duplicate_hypotheses(&mut state.hypotheses, hooks.is_same_model);
+        state.hypotheses.truncate(
+            self.retained_hypotheses(block)
+                .min(self.max_survivors::<E::Model>()),
+        );
+        self.emit(Event::BlockCompleted {
+            block,
+            datapoints: state.num_scored,
+            hypotheses: state.hypotheses.len(),
+        });
+        log!(
+            trace,
+            "block {block}: epsilon {}, delta {}, {} hypotheses survive on {} datapoints",
+            self.report.block_epsilon,
+            state.delta,
+            state.hypotheses.len(),
+            state.num_scored
+        );
+        // The best hypothesis stays the best while it is tied for the most support, even if a tied
+        // one now ranks higher because of a lower sum of residuals.
+        let best_support = state.hypotheses.first().map(|best| best.support);
+        let still_best = state
+            .hypotheses
+            .iter()
+            .take_while(|h| Some(h.support) == best_support)
+            .any(|h| Some(h.generation) == state.best_generation);
+        if still_best {
+            state.blocks_without_improvement += 1;
+        } else {
+            state.best_generation = state.hypotheses.first().map(|best| best.generation);
+            state.blocks_without_improvement = 0;
+        }
+        let context = TerminationContext {
+            block,
+            datapoints: state.num_scored,
+            best_inliers: state.hypotheses.first().map_or(0, |best| best.inliers),
+            blocks_without_improvement: state.blocks_without_improvement,
+            epsilon: self.report.block_epsilon,
+            delta: state.delta,
+            hypotheses_generated: self.report.hypotheses_generated,
+            survivors: state.hypotheses.len(),
+        };
+        let terminate = self
+            .terminate
+            .as_mut()
+            .is_some_and(|terminate| terminate(&context));
+        if terminate
+            || state.hypotheses.len() <= 1
+            || self.report.stopped_early
+            || state.blocks_without_improvement >= self.config.patience
+            || state.block - self.config.initialization_blocks >= self.config.max_blocks
+        {
+            state.done = true;
+        }
+    }
+
+    /// Returns the surviving hypotheses of a finished search, each scored over all of `data`, and
+    /// where its blocks started.
+    fn finish_search<Data, M: Model<Data>>(
+        &mut self,
+        data: impl Iterator<Item = Data> + Clone,
+        state: SearchState<M>,
+    ) -> (Vec<Hypothesis<M>>, Vec<usize>) {
+        let SearchState {
+            mut hypotheses,
+            mut num_scored,
+            data_len,
+            block_starts,
+            ..
+        } = state;
+        let len = data.clone().count();
+        // The data was walked for every block in between, so it yielding as many datapoints as when
+        // the search started catches some of the data that doesn't yield the same each time.
+        debug_assert_eq!(
+            len, data_len,
+            "ARRSAC requires every clone of the data to yield the same datapoints"
+        );
+        let window = core::cmp::min(len, self.config.inlier_window);
+        // If we stopped before evaluating all of the data, the survivors are only scored on a prefix
+        // of the data, so score them on all of it before comparing them for the final selection.
+        // The same goes for when they were scored beyond the inlier window.
+        // The tracked inliers must also cover all of the data.
+        if num_scored != window && (hypotheses.len() > 1 || self.config.incremental_inliers) {
+            self.score_hypotheses(data.clone(), &mut hypotheses);
+            if self.config.recency_weight != 1.0 {
+                self.weigh_recency(data, &mut hypotheses, &block_starts);
+            }
+            num_scored = window;
+        }
+        self.record_epsilon(&hypotheses, num_scored);
+        (hypotheses, block_starts)
+    }
+
+    /// Removes the sorted `hypotheses` whose model is the same as a better one, see
+    /// [`Arrsac::dedupe_hypotheses`].
+    fn remove_duplicate_hypotheses<M>(
+        &mut self,
+        hypotheses: &mut Vec<Hypothesis<M>>,
+        is_same_model: ModelRelation<'_, M>,
+    ) {
+        if let Some(is_same_model) = is_same_model {
+            self.report.duplicate_hypotheses += dedupe_hypotheses(hypotheses, is_same_model);
+        }
+    }
+
+    /// Records the inlier ratio of the best of `hypotheses`, which were scored on `num_scored` datapoints.
+    fn record_epsilon<M>(&mut self, hypotheses: &[Hypothesis<M>], num_scored: usize) {
+        self.report.epsilon = hypotheses
+            .iter()
+            .min_by(|a, b| compare_hypotheses(a, b))
+            .map(|best| best.inliers as f32 / num_scored as f32);
+    }
+
+    /// Runs ARRSAC and returns the best model together with its inliers and the [`Report`].
+    ///
+    /// This is the same as [`Consensus::model_inliers`], except that the result can't be silently
+    /// discarded. This is `None` if no model could be found.
+    #[must_use]
+    pub fn fit<E, Data, I>(&mut self, estimator: &E, data: I) -> Option<ModelResult<E::Model>>
+    where
+        E: Estimator<Data>,
+        I: Iterator<Item = Data> + Clone,
+    {
+        let (model, inliers) = self.model_inliers(estimator, data)?;
+        Some(ModelResult {
+            model,
+            inliers,
+            report: self.report.clone(),
+        })
+    }
+
+    /// Runs ARRSAC and returns the best model and its inliers like [`Consensus::model_inliers`], or
+    /// why no model was found.
+    ///
+    /// Unlike [`Consensus::model_inliers`], this doesn't panic if the configuration can't be used
+    /// with the estimator.
+    pub fn try_model_inliers<E, Data, I>(
+        &mut self,
+        estimator: &E,
+        data: I,
+    ) -> Result<(E::Model, Vec<usize>), ArrsacError>
+    where
+        E: Estimator<Data>,
+        I: Iterator<Item = Data> + Clone,
+    {
+        self.check_run::<E, Data>(data.clone().count())?;
+        self.model_inliers(estimator, data)
+            .ok_or(ArrsacError::NoModelGenerated)
+    }
+
+    /// Runs ARRSAC and returns the best model together with its inliers and the [`Report`] like
+    /// [`Arrsac::fit`], or why no model was found.
+    pub fn try_fit<E, Data, I>(
+        &mut self,
+        estimator: &E,
+        data: I,
+    ) -> Result<ModelResult<E::Model>, ArrsacError>
+    where
+        E: Estimator<Data>,
+        I: Iterator<Item = Data> + Clone,
+    {
+        self.check_run::<E, Data>(data.clone().count())?;
+        self.fit(estimator, data)
+            .ok_or(ArrsacError::NoModelGenerated)
+    }
+
+    /// Runs ARRSAC and returns the best model like [`Consensus::model_inliers`], but writes its
+    /// inliers into `inliers` instead of a new [`Vec`].
+    ///
+    /// `inliers` is cleared first, so it stays empty if no model is found. Reusing the same buffer
+    /// across runs avoids allocating the inliers every time.
+    pub fn model_inliers_into<E, Data, I>(
+        &mut self,
+        estimator: &E,
+        data: I,
+        inliers: &mut Vec<usize>,
+    ) -> Option<E::Model>
+    where
+        E: Estimator<Data>,
+        I: Iterator<Item = Data> + Clone,
+    {
+        inliers.clear();
+        let hypotheses = self.survivors(estimator, data.clone(), SearchHooks::default());
+        self.select_into(hypotheses, data, inliers)
+    }
+
+    /// Runs ARRSAC and returns the same model as [`Consensus::model`], but without finding its
+    /// inliers afterwards.
+    ///
+    /// The best model is selected with the inliers counted during the search, so this saves a
+    /// pass over the data when the inliers aren't needed or are found later anyway. The
+    /// [`Report::inlier_residuals`] are `None`, since they would take the same pass.
+    pub fn model_only<E, Data, I>(&mut self, estimator: &E, data: I) -> Option<E::Model>
+    where
+        E: Estimator<Data>,
+        I: Iterator<Item = Data> + Clone,
+    {
+        let hypotheses = self.survivors(estimator, data.clone(), SearchHooks::default());
+        let best = self.best_hypothesis(hypotheses)?;
+        self.record_selected(&best);
+        Some(best.model)
+    }
+
+    /// Runs ARRSAC and returns the best model like [`Consensus::model_inliers`], but with its
+    /// inliers coalesced into ranges of consecutive indices.
+    ///
+    /// The ranges are sorted and neither overlap nor touch each other. This is much more compact than
+    /// the individual indices when the inliers tend to be contiguous, such as for clustered data.
+    pub fn model_inliers_ranges<E, Data, I>(
+        &mut self,
+        estimator: &E,
+        data: I,
+    ) -> Option<(E::Model, Vec<Range<usize>>)>
+    where
+        E: Estimator<Data>,
+        I: Iterator<Item = Data> + Clone,
+    {
+        let mut inliers = vec![];
+        let model = self.model_inliers_into(estimator, data, &mut inliers)?;
+        Some((model, inlier_ranges(&inliers)))
+    }
+
+    /// Checks that a run on `len` datapoints can find a model with the estimator `E`.
+    fn check_run<E: Estimator<Data>, Data>(&self, len: usize) -> Result<(), ArrsacError> {
+        let sample_size = self.samples_per_hypothesis(E::MIN_SAMPLES);
+        if sample_size < E::MIN_SAMPLES
+            || (!self.config.skip_initial_phase
+                && (self.config.initialization_blocks == 0
+                    || self.config.initialization_hypotheses == 0))
+            || self
+                .config
+                .initial_eval_size
+                .is_some_and(|initial_eval_size| initial_eval_size < sample_size)
+        {
+            return Err(ArrsacError::InvalidConfig);
+        }
+        if len == 0 {
+            return Err(ArrsacError::EmptyData);
+        }
+        if len < sample_size {
+            return Err(ArrsacError::NotEnoughSamples);
+        }
+        // The seed samples are only used in the initial phase, see [`ArrsacBuilder::seed_samples`].
+        let invalid_seed = |seed: &Vec<u32>| {
+            seed.len() != E::MIN_SAMPLES || seed.iter().any(|&ix| ix as usize >= len)
+        };
+        if !self.config.skip_initial_phase && self.config.seed_samples.iter().any(invalid_seed) {
+            return Err(ArrsacError::InvalidConfig);
+        }
+        Ok(())
+    }
+
+    /// Runs ARRSAC on `data` that can't be cloned, such as data read from a stream, and returns the
+    /// best model and its inliers like [`Consensus::model_inliers`].
+    ///
+    /// The data is collected into a `Vec` with room for `len` datapoints once, so `data` is never
+    /// cloned. `len` must be the number of datapoints in `data`.
+    #[must_use]
+    pub fn model_inliers_with_len<E, Data, I>(
+        &mut self,
+        estimator: &E,
+        data: I,
+        len: usize,
+    ) -> Option<(E::Model, Vec<usize>)>
+    where
+        E: Estimator<Data>,
+        Data: Clone,
+        I: IntoIterator<Item = Data>,
+    {
+        let mut collected = Vec::with_capacity(len);
+        collected.extend(data);
+        debug_assert_eq!(collected.len(), len, "the length of the data must be `len`");
+        self.model_inliers(estimator, collected.into_iter())
+    }
+
+    /// Runs ARRSAC and returns the `k`-th best surviving model and its inliers over all of the data,
+    /// where `k = 0` is the model that [`Consensus::model_inliers`] returns with [`TiePolicy::First`].
+    ///
+    /// The survivors are ordered like [`Arrsac::model_candidates`]. This is `None` if `k` is at least
+    /// the number of survivors, which is usually small since blocks are evaluated until at most one
+    /// hypothesis remains. Use a larger `max_candidate_hypotheses` to keep more runners-up.
+    #[must_use]
+    pub fn nth_best_model<E, Data, I>(
+        &mut self,
+        estimator: &E,
+        data: I,
+        k: usize,
+    ) -> Option<(E::Model, Vec<usize>)>
+    where
+        E: Estimator<Data>,
+        I: Iterator<Item = Data> + Clone,
+    {
+        let mut hypotheses = self.survivors(estimator, data.clone(), SearchHooks::default());
+        sort_hypotheses(&mut hypotheses);
+        let hypothesis = hypotheses.into_iter().nth(k)?;
+        self.record_returned(data.clone(), &hypothesis);
+        Some(self.take_model_inliers(hypothesis, data))
+    }
+
+    /// Runs ARRSAC and returns every hypothesis that survived along with its inliers over all of the data.
+    ///
+    /// The candidates are sorted by decreasing support, which is their number of inliers unless
+    /// [`Arrsac::soft_inliers`] is used, and ties are broken like [`TiePolicy::First`], so the first
+    /// one is what [`Consensus::model_inliers`] returns with it. This is empty if no model could be found.
+    #[must_use]
+    pub fn model_candidates<E, Data, I>(
+        &mut self,
+        estimator: &E,
+        data: I,
+    ) -> Vec<(E::Model, Vec<usize>)>
+    where
+        E: Estimator<Data>,
+        I: Iterator<Item = Data> + Clone,
+    {
+        let mut hypotheses = self.survivors(estimator, data.clone(), SearchHooks::default());
+        sort_hypotheses(&mut hypotheses);
+        if let Some(best) = hypotheses.first() {
+            self.record_returned(data.clone(), best);
+        }
+        hypotheses
+            .into_iter()
+            .map(|hypothesis| self.take_model_inliers(hypothesis, data.clone()))
+            .collect()
+    }
+
+    /// Runs ARRSAC and returns every model that is tied for the most support along with its
+    /// inliers over all of the data, rather than picking one of them with [`Arrsac::on_tie`].
+    ///
+    /// The models are ordered like [`Arrsac::model_candidates`], so the first one is what
+    /// [`TiePolicy::First`] returns. This is empty if no model could be found.
+    #[must_use]
+    pub fn best_models<E, Data, I>(&mut self, estimator: &E, data: I) -> Vec<(E::Model, Vec<usize>)>
+    where
+        E: Estimator<Data>,
+        I: Iterator<Item = Data> + Clone,
+    {
+        let mut hypotheses = self.survivors(estimator, data.clone(), SearchHooks::default());
+        sort_hypotheses(&mut hypotheses);
+        let Some(support) = hypotheses.first().map(|best| best.support) else {
+            return vec![];
+        };
+        let tied: Vec<_> = hypotheses
+            .into_iter()
+            .take_while(|h| h.support.total_cmp(&support).is_eq())
+            .collect();
+        self.record_returned(data.clone(), &tied[0]);
+        tied.into_iter()
+            .map(|hypothesis| self.take_model_inliers(hypothesis, data.clone()))
+            .collect()
+    }
+
+    /// Runs ARRSAC repeatedly to extract up to `max_models` models, removing the inliers of each
+    /// model from the data before searching for the next one.
+    ///
+    /// The inliers are indices into all of `data`, and no datapoint is an inlier of more than one
+    /// model. This stops early once a search finds no model. The [`Report`] describes the last search.
+    /// The [`ArrsacBuilder::seed_samples`] index into the datapoints that remain for every search.
+    /// Use [`Arrsac::cached_models`] with [`ArrsacBuilder::residual_cache`] to reuse the residuals of
+    /// the models when they are extracted from the same data repeatedly.
+    #[must_use]
+    pub fn models<E, Data, I>(
+        &mut self,
+        estimator: &E,
+        data: I,
+        max_models: usize,
+    ) -> Vec<(E::Model, Vec<usize>)>
+    where
+        E: Estimator<Data>,
+        I: Iterator<Item = Data> + Clone,
+    {
+        self.extract_models(estimator, data, max_models, None)
+    }
+
+    /// Extracts models like [`Arrsac::models`], finding their inliers from the residuals over all of
+    /// the data that `cache` caches for every model if provided, see [`Arrsac::cached_models`].
+    fn extract_models<E, Data, I>(
+        &mut self,
+        estimator: &E,
+        data: I,
+        max_models: usize,
+        cache: Option<cache::CacheResiduals<Self, I, E::Model>>,
+    ) -> Vec<(E::Model, Vec<usize>)>
+    where
+        E: Estimator<Data>,
+        I: Iterator<Item = Data> + Clone,
+    {
+        let len = data.clone().count();
+        let mut removed = vec![false; len];
+        let mut models = vec![];
+        while models.len() < max_models {
+            // The original index of every remaining datapoint.
+            let remaining: Vec<usize> = (0..len).filter(|&ix| !removed[ix]).collect();
+            let remaining_data = data
+                .clone()
+                .zip(removed.iter())
+                .filter_map(|(data, &removed)| (!removed).then_some(data));
+            let hypotheses =
+                self.survivors(estimator, remaining_data.clone(), SearchHooks::default());
+            let Some(best) = self.best_hypothesis(hypotheses) else {
+                break;
+            };
+            self.report.found_in_block = Some(best.block);
+            if let Some(cache) = cache {
+                // The residuals over all of the data are cached, of which the remaining ones are used.
+                let position = cache(self, data.clone(), &best.model);
+                let row = self.cached_residuals(position);
+                let window = &remaining[..remaining.len().min(self.config.inlier_window)];
+                let inliers: Vec<usize> = window
+                    .iter()
+                    .copied()
+                    .filter(|&ix| self.is_reported_inlier(row[ix]))
+                    .collect();
+                self.report.inlier_residuals = residual_stats(inliers.iter().map(|&ix| row[ix]));
+                for &ix in &inliers {
+                    removed[ix] = true;
+                }
+                models.push((best.model, inliers));
+                continue;
+            }
+            self.record_inlier_residuals(remaining_data.clone(), &best.model);
+            let (model, inliers) = self.take_model_inliers(best, remaining_data);
+            let inliers: Vec<usize> = inliers.into_iter().map(|ix| remaining[ix]).collect();
+            for &ix in &inliers {
+                removed[ix] = true;
+            }
+            models.push((model, inliers));
+        }
+        models
+    }
+
+    /// Selects the best of `hypotheses`, which were scored over all of `data`, and its inliers.
+    fn select<Data, M: Model<Data>>(
+        &mut self,
+        hypotheses: Vec<Hypothesis<M>>,
+        data: impl Iterator<Item = Data> + Clone,
+    ) -> Option<(M, Vec<usize>)> {
+        let mut inliers = Vec::new();
+        let model = self.select_into(hypotheses, data, &mut inliers)?;
+        Some((model, inliers))
+    }
+
+    /// Selects the best of the surviving `hypotheses` like [`Arrsac::select`], but pushes its
+    /// inliers to `inliers`.
+    fn select_into<Data, M: Model<Data>>(
+        &mut self,
+        hypotheses: Vec<Hypothesis<M>>,
+        data: impl Iterator<Item = Data> + Clone,
+        inliers: &mut Vec<usize>,
+    ) -> Option<M> {
+        let best = self.best_hypothesis(hypotheses)?;
+        if best.inlier_indices.is_some() && self.config.report_threshold.is_none() {
+            self.record_returned(data.clone(), &best);
+            return Some(self.take_model_inliers_into(best, data, inliers));
+        }
+        self.record_selected(&best);
+        // Find the inliers and the spread of their residuals in the same pass over the data.
+        let residuals =
+            data.take(self.config.inlier_window)
+                .enumerate()
+                .filter_map(|(ix, data)| {
+                    let residual = best.model.residual(&data);
+                    self.is_reported_inlier(residual).then(|| {
+                        inliers.push(ix);
+                        residual
+                    })
+                });
+        let inlier_residuals = residual_stats(residuals);
+        self.report.inlier_residuals = inlier_residuals;
+        Some(best.model)
+    }
+
+    /// Picks the best of `hypotheses`, which were scored over all of the data, breaking ties with
+    /// [`ArrsacBuilder::on_tie`].
+    fn best_hypothesis<M>(&self, hypotheses: Vec<Hypothesis<M>>) -> Option<Hypothesis<M>> {
+        match self.config.on_tie {
+            TiePolicy::First => hypotheses.into_iter().min_by(compare_hypotheses),
+            TiePolicy::LowestResidual => {
+                let support = hypotheses
+                    .iter()
+                    .map(|h| h.support)
+                    .max_by(f64::total_cmp)?;
+                hypotheses
+                    .into_iter()
+                    .filter(|h| h.support.total_cmp(&support).is_eq())
+                    .map(|h| (mean_inlier_residual(&h), h))
+                    .min_by(|(a, ha), (b, hb)| {
+                        a.total_cmp(b).then(ha.generation.cmp(&hb.generation))
+                    })
+                    .map(|(_, h)| h)
+            }
+        }
+    }
+
+    /// Records the diagnostics about the returned `hypothesis`.
+    fn record_returned<Data, M: Model<Data>>(
+        &mut self,
+        data: impl Iterator<Item = Data>,
+        hypothesis: &Hypothesis<M>,
+    ) {
+        self.record_selected(hypothesis);
+        self.record_inlier_residuals(data, &hypothesis.model);
+    }
+
+    /// Records the diagnostics about the selected `hypothesis` that don't need another pass over
+    /// the data.
+    fn record_selected<M>(&mut self, hypothesis: &Hypothesis<M>) {
+        self.report.found_in_block = Some(hypothesis.block);
+        log!(
+            debug,
+            "selected hypothesis {} from block {} with {} inliers",
+            hypothesis.generation,
+            hypothesis.block,
+            hypothesis.inliers
+        );
+    }
+
+    /// Records the spread of the residuals of the inliers of the returned `model`.
+    fn record_inlier_residuals<Data, M: Model<Data>>(
+        &mut self,
+        data: impl Iterator<Item = Data>,
+        model: &M,
+    ) {
+        let residuals = data
+            .take(self.config.inlier_window)
+            .map(|data| model.residual(&data))
+            .filter(|&residual| self.is_reported_inlier(residual));
+        self.report.inlier_residuals = residual_stats(residuals);
+    }
+
+    /// Adapted from algorithm 3 from "A Comparative Analysis of RANSAC Techniques Leading to Adaptive
+    /// Real-Time Random Sample Consensus", but it was effectively rewritten to avoid the need for
+    /// initial epsilon and delta.
+    ///
+    /// Returns the initial hypotheses sorted by decreasing support and `delta` in that order.
+    fn initial_hypotheses<E, Data>(
+        &mut self,
+        estimator: &E,
+        data: impl Iterator<Item = Data> + Clone,
+        hooks: SearchHooks<'_, Data, E::Model>,
+    ) -> (Vec<Hypothesis<E::Model>>, f32)
+    where
+        E: Estimator<Data>,
+    {
+        assert!(
+            self.config.initialization_blocks > 0,
+            "ARRSAC must have at least 1 initialization block"
+        );
+        // NOTE: This whole function is different than that specified in the ARRSAC paper.
         // The reason is that you needed to provide a good initial guess for epsilon and delta
         // otherwise it could lead to delta exceeding epsilon or situations where models could no
         // longer be rejected or were almost always rejected. This solution is an imperfect compomise
@@ -180,37 +2340,67 @@ where
         // set of initial datapoints is required to be able to accurately determine epsilon and delta.
         // Therefore a new paremeter is added to separate the normal blocks from the initial generation set.
         let mut hypotheses = vec![];
-        // We don't want more than `block_size` data points to be used to evaluate models initially.
-        let initial_datapoints = core::cmp::min(
-            self.initialization_blocks * self.block_size,
-            data.clone().count(),
-        );
-        // Generate the initial batch of random hypotheses and count their inliers and outliers.
-        for _ in 0..self.initialization_hypotheses {
-            for model in self.generate_random_hypotheses(estimator, data.clone()) {
-                let inliers = self.count_inliers(data.clone().take(initial_datapoints), &model);
-                hypotheses.push((model, inliers));
+        // We don't want more than `block_size` data points to be used to evaluate models initially.
+        let len = data.clone().count();
+        let initial_datapoints = core::cmp::min(self.initial_datapoints(), len);
+        // Generate the initial batch of hypotheses, which starts with the seed samples, and count
+        // their inliers and outliers.
+        let num_seeds = self.config.seed_samples.len();
+        for ix in 0..self.config.initialization_hypotheses.max(num_seeds) {
+            if self.report.stopped_early || self.is_out_of_estimations() {
+                break;
+            }
+            let models = if ix < num_seeds {
+                self.seeded_hypotheses(estimator, data.clone(), len, ix)
+            } else {
+                self.generate_random_hypotheses(
+                    estimator,
+                    data.clone(),
+                    hooks.is_local,
+                    hooks.strata,
+                )
+            };
+            for model in models {
+                let hypothesis = self.new_hypothesis(model, data.clone().take(initial_datapoints));
+                #[cfg(feature = "record-models")]
+                self.record_model(hypothesis.generation, None, true);
+                hypotheses.push(hypothesis);
             }
         }
 
+        self.report.delta_estimations = hypotheses.len();
+
         // Bail early when no hypothesis was found.
         // This will cause execution to terminate.
         if hypotheses.is_empty() {
+            self.report.no_models_generated = true;
             return (hypotheses, 0.0);
         }
 
-        // Sort the hypotheses by their inliers.
-        hypotheses.sort_unstable_by_key(|&(_, inliers)| Reverse(inliers));
+        // Sort the hypotheses by their support.
+        sort_hypotheses(&mut hypotheses);
 
         // Compute epsilon and delta using the best and worst model generated.
+        // A valid model has at least `E::MIN_SAMPLES` inliers, so neither can be seeded below that.
         let epsilon = hypotheses
             .first()
-            .map(|&(_, inliers)| inliers as f32 / initial_datapoints as f32)
+            .map(|h| h.inliers.max(E::MIN_SAMPLES) as f32 / initial_datapoints as f32)
             .unwrap_or_default();
         let delta = hypotheses
             .last()
-            .map(|&(_, inliers)| if inliers < E::MIN_SAMPLES {E::MIN_SAMPLES} else {inliers} as f32 / initial_datapoints as f32)
-            .unwrap_or_default();
+            .map(|h| h.inliers.max(E::MIN_SAMPLES) as f32 / initial_datapoints as f32)
+            .unwrap_or_default()
+            .max(self.config.min_delta);
+        self.report.delta = delta;
+        log!(
+            debug,
+            "initial phase: epsilon {epsilon}, delta {delta} from {} hypotheses on {initial_datapoints} datapoints",
+            hypotheses.len()
+        );
+        // Either every model fits everything or no model fits anything, which is the typical
+        // outcome of a threshold on the wrong scale.
+        self.report.suspicious_threshold = delta >= SUSPICIOUS_INLIER_RATIO
+            || hypotheses[0].inliers <= E::MIN_SAMPLES && initial_datapoints > E::MIN_SAMPLES;
 
         if epsilon < delta {
             // If epsilon is less than delta, then better hypotheses will get rejected and worse accepted,
@@ -220,41 +2410,133 @@ where
             return (hypotheses, delta);
         }
 
+        if let Some(target_reject_prob) = self.config.target_reject_prob {
+            self.report.likelihood_ratio_threshold =
+                sprt_threshold(epsilon, delta, initial_datapoints, target_reject_prob);
+        }
+
         // Populate hypotheses with hypotheses generated from the inliers of the best hypothesis.
         // This will use the initialization datapoints and filter with SPRT.
-        self.populate_hypotheses_sprt(
-            estimator,
-            &mut hypotheses,
+        let population = Population {
             delta,
-            data,
-            initial_datapoints,
-            self.initialization_hypotheses,
-        );
+            num_checked: initial_datapoints,
+            estimations: self.config.initialization_hypotheses,
+            block: 0,
+        };
+        self.populate_hypotheses_sprt(estimator, &mut hypotheses, data, population, hooks);
 
-        // Sort the hypotheses by their inliers.
-        hypotheses.sort_unstable_by_key(|&(_, inliers)| Reverse(inliers));
+        // Sort the hypotheses by their support.
+        sort_hypotheses(&mut hypotheses);
+        self.remove_duplicate_hypotheses(&mut hypotheses, hooks.is_same_model);
 
         // Filter down the hypotheses to just the best ones.
-        hypotheses.truncate(self.max_candidate_hypotheses >> (self.initialization_blocks - 1));
+        hypotheses.truncate(
+            self.retained_hypotheses(self.config.initialization_blocks - 1)
+                .min(self.max_survivors::<E::Model>()),
+        );
+
+        (hypotheses, delta)
+    }
 
+    /// Generates the hypotheses of a single estimation instead of the initial phase, see
+    /// [`ArrsacBuilder::skip_initial_phase`].
+    ///
+    /// Returns the hypotheses sorted by decreasing support and the fixed `delta` in that order.
+    fn fixed_initial_hypotheses<E, Data>(
+        &mut self,
+        estimator: &E,
+        data: impl Iterator<Item = Data> + Clone,
+        hooks: SearchHooks<'_, Data, E::Model>,
+    ) -> (Vec<Hypothesis<E::Model>>, f32)
+    where
+        E: Estimator<Data>,
+    {
+        let initial_datapoints = core::cmp::min(self.initial_datapoints(), data.clone().count());
+        let mut hypotheses = vec![];
+        let models = (!self.is_out_of_estimations()).then(|| {
+            self.generate_random_hypotheses(estimator, data.clone(), hooks.is_local, hooks.strata)
+        });
+        for model in models.into_iter().flatten() {
+            let hypothesis = self.new_hypothesis(model, data.clone().take(initial_datapoints));
+            #[cfg(feature = "record-models")]
+            self.record_model(hypothesis.generation, None, true);
+            hypotheses.push(hypothesis);
+        }
+        if hypotheses.is_empty() {
+            self.report.no_models_generated = true;
+        }
+        sort_hypotheses(&mut hypotheses);
+        if let Some(target_reject_prob) = self.config.target_reject_prob {
+            self.report.likelihood_ratio_threshold = sprt_threshold(
+                self.config.initial_epsilon,
+                self.config.initial_delta,
+                initial_datapoints,
+                target_reject_prob,
+            );
+        }
+        let delta = self.config.initial_delta.max(self.config.min_delta);
+        self.report.delta = delta;
         (hypotheses, delta)
     }
 
+    /// Creates a hypothesis from a newly generated `model` scored on `data`.
+    fn new_hypothesis<Data, M: Model<Data>>(
+        &mut self,
+        model: M,
+        data: impl Iterator<Item = Data>,
+    ) -> Hypothesis<M> {
+        let mut inlier_indices = self.tracked_inliers();
+        let (inliers, support, residual_sum) =
+            self.count_inliers(data, &model, inlier_indices.as_mut());
+        let generation = self.next_generation();
+        Hypothesis {
+            model,
+            inliers,
+            support,
+            residual_sum,
+            generation,
+            block: 0,
+            inlier_indices,
+        }
+    }
+
     /// Populates `self.random_samples` using a len.
-    fn populate_samples(&mut self, num: usize, len: usize) {
+    pub(crate) fn populate_samples(&mut self, num: usize, len: usize) {
         // We can generate no hypotheses if the amout of data is too low.
         if len < num {
             panic!("cannot use arrsac without having enough samples");
         }
-        let len = len as u32;
+        self.random_samples.clear();
+        #[cfg(feature = "record-models")]
+        self.recorder.new_sample();
+        // Drawing no samples must not divide by zero below.
+        if num == 0 {
+            return;
+        }
+        #[cfg(feature = "test-util")]
+        if let Some(fixed_samples) = &mut self.fixed_samples {
+            for _ in 0..num {
+                let s = fixed_samples.next_sample();
+                assert!((s as usize) < len, "fixed sample {s} is out of range");
+                self.random_samples.push(s);
+            }
+            return;
+        }
+        // Indices are stored as `u32`, so only the first `u32::MAX` data points can be sampled.
+        let len = u32::try_from(len).unwrap_or(u32::MAX);
+        self.push_random_samples(num, len, |s| s);
+    }
+
+    /// Pushes `num` distinct samples to `self.random_samples`, which are `candidate(ix)` for
+    /// uniformly drawn `ix` less than `len`.
+    fn push_random_samples(&mut self, num: usize, len: u32, candidate: impl Fn(u32) -> u32) {
         // Threshold generation below adapted from randomize::RandRangeU32.
         let threshold = len.wrapping_neg() % len;
-        self.random_samples.clear();
         for _ in 0..num {
             loop {
-                let mul = u64::from(self.rng.next_u32()).wrapping_mul(u64::from(len));
+                let mul = u64::from(self.next_u32()).wrapping_mul(u64::from(len));
                 if mul as u32 >= threshold {
-                    let s = (mul >> 32) as u32;
+                    let s = candidate((mul >> 32) as u32);
                     if !self.random_samples.contains(&s) {
                         self.random_samples.push(s);
                         break;
@@ -264,131 +2546,623 @@ where
         }
     }
 
+    /// Redraws all but the first of `self.random_samples` from the datapoints that are local to the
+    /// first one, see [`Arrsac::locality_bias`]. The samples are indices into `subset` if it is
+    /// provided, which must be sorted.
+    ///
+    /// The samples are kept if too few datapoints are local to the first one.
+    fn localize_samples<Data>(
+        &mut self,
+        data: impl Iterator<Item = Data> + Clone,
+        subset: Option<&[usize]>,
+        is_local: &dyn Fn(&Data, &Data) -> bool,
+    ) {
+        let num = self.random_samples.as_slice().len();
+        let Some(&first) = self.random_samples.as_slice().first() else {
+            return;
+        };
+        // Sample indices are positions in the subset, if there is one.
+        let sample_ix = |ix: usize| match subset {
+            Some(subset) => subset.binary_search(&ix).ok(),
+            None => Some(ix),
+        };
+        let datapoints = data
+            .enumerate()
+            .filter_map(|(ix, data)| Some((u32::try_from(sample_ix(ix)?).ok()?, data)));
+        let Some((_, anchor)) = datapoints.clone().find(|&(ix, _)| ix == first) else {
+            return;
+        };
+        let candidates: Vec<u32> = datapoints
+            .filter(|(ix, data)| *ix != first && is_local(&anchor, data))
+            .map(|(ix, _)| ix)
+            .collect();
+        if candidates.len() < num - 1 {
+            return;
+        }
+        self.random_samples.clear();
+        self.random_samples.push(first);
+        self.push_random_samples(num - 1, candidates.len() as u32, |ix| {
+            candidates[ix as usize]
+        });
+    }
+
+    /// Redraws `self.random_samples` so that no two of them are in the same stratum, see
+    /// [`Arrsac::stratified_init`]. Distinct strata are drawn uniformly and then a datapoint is drawn
+    /// uniformly from each of them.
+    ///
+    /// The samples are kept if too few strata have datapoints in them.
+    fn stratify_samples<Data>(
+        &mut self,
+        data: impl Iterator<Item = Data> + Clone,
+        stratum: &dyn Fn(&Data) -> usize,
+        num_strata: usize,
+    ) {
+        let num = self.random_samples.as_slice().len();
+        // Datapoints outside of the strata are never drawn.
+        let mut members: Vec<Vec<u32>> = vec![vec![]; num_strata];
+        for (ix, data) in data.enumerate() {
+            let (Ok(ix), Some(members)) = (u32::try_from(ix), members.get_mut(stratum(&data)))
+            else {
+                continue;
+            };
+            members.push(ix);
+        }
+        members.retain(|members| !members.is_empty());
+        if members.len() < num {
+            return;
+        }
+        self.random_samples.clear();
+        self.push_random_samples(num, members.len() as u32, |ix| ix);
+        let strata: Vec<u32> = self.random_samples.as_slice().to_vec();
+        self.random_samples.clear();
+        for stratum in strata {
+            let members = &members[stratum as usize];
+            self.push_random_samples(1, members.len() as u32, |ix| members[ix as usize]);
+        }
+    }
+
     fn populate_hypotheses_sprt<E, Data>(
         &mut self,
         estimator: &E,
-        hypotheses: &mut Vec<(E::Model, usize)>,
-        delta: f32,
+        hypotheses: &mut Vec<Hypothesis<E::Model>>,
         data: impl Iterator<Item = Data> + Clone,
-        num_checked: usize,
-        num_hypotheses: usize,
+        population: Population,
+        hooks: SearchHooks<'_, Data, E::Model>,
     ) where
         E: Estimator<Data>,
     {
-        // Update epsilon using the best model.
+        let Population {
+            delta,
+            num_checked,
+            estimations: num_hypotheses,
+            block,
+        } = population;
+        // Update epsilon using the model with the most inliers, which isn't necessarily the first
+        // one when inliers are weighted.
         // Since epsilon can only increase and delta is fixed, we can be sure that these ratios
         // will still be valid (epsilon > delta).
-        let epsilon = hypotheses[0].1 as f32 / num_checked as f32;
+        // This is computed in `f64` since `f32` loses precision with hundreds of thousands of datapoints.
+        let most_inliers = hypotheses
+            .iter()
+            .map(|h| h.inliers)
+            .max()
+            .unwrap_or_default();
+        let epsilon = most_inliers as f64 / num_checked as f64;
+        self.report.block_epsilon = epsilon as f32;
         // Create the likelihood ratios for inliers and outliers.
-        let positive_likelihood_ratio = delta / epsilon;
-        let negative_likelihood_ratio = (1.0 - delta) / (1.0 - epsilon);
-        // Generate the list of inliers for the best model.
-        let mut inliers = self.inliers(data.clone().take(num_checked), &hypotheses[0].0);
-        if inliers.len() <= E::MIN_SAMPLES {
-            // If we don't have enough samples to generate more models, then we should expand the inliers to
-            // the entire dataset.
-            inliers = self.inliers(data.clone().take(num_checked), &hypotheses[0].0);
+        let (positive_likelihood_ratio, negative_likelihood_ratio) =
+            likelihood_ratios(epsilon, f64::from(delta));
+        let positive_likelihood_ratio = positive_likelihood_ratio as f32;
+        let negative_likelihood_ratio = negative_likelihood_ratio as f32;
+        // Generate the list of inliers for the best model, which isn't needed if we only sample from all data.
+        let subset_sampling =
+            !self.config.full_sampling_only && self.is_usable(hypotheses[0].inliers, num_checked);
+        let worst_first = self.config.asprt_order == AsprtOrder::WorstFirst;
+        let mut inliers = core::mem::take(&mut self.best_inliers);
+        inliers.clear();
+        if subset_sampling || worst_first {
+            let model = &hypotheses[0].model;
+            inliers.extend(
+                data.clone()
+                    .take(num_checked)
+                    .enumerate()
+                    .filter(|(_, data)| self.is_inlier(model.residual(data)))
+                    .map(|(ix, _)| ix),
+            );
+        }
+        // Mark the datapoints the SPRT evaluates first, which are all of them for the input order.
+        let mut first = vec![];
+        if worst_first {
+            first = vec![false; num_checked];
+            for &ix in &inliers {
+                first[ix] = true;
+            }
         }
+        let is_first = |ix: usize| first.get(ix).copied().unwrap_or(true);
+        let prefix = data.clone().take(num_checked).enumerate();
+        let ordered = prefix
+            .clone()
+            .filter(|&(ix, _)| is_first(ix))
+            .chain(prefix.filter(|&(ix, _)| !is_first(ix)));
+        // There must be enough inliers to draw a sample from them. If there are no more inliers than a
+        // minimal sample, sampling from them could only reproduce the best hypothesis, so we should
+        // expand the inliers to the entire dataset instead.
+        let subset_sampling = subset_sampling
+            && inliers.len() > E::MIN_SAMPLES
+            && inliers.len() >= self.samples_per_hypothesis(E::MIN_SAMPLES);
         // We generate hypotheses until we reach the initial num hypotheses.
         // We can't count the number generated because it could generate 0 hypotheses
         // and then the loop would continue indefinitely.
-        let mut random_hypotheses = Vec::new();
+        let inlier_subset = subset_sampling.then_some(inliers.as_slice());
+        let data_len = data.clone().count();
+        // Small datasets may be scored fully instead, see [`ArrsacBuilder::full_scan_small_data`].
+        let full_scan = self.config.full_scan_small_data
+            && block == 0
+            && data.clone().count() <= self.config.block_size;
+        let params = Sprt {
+            scoring: self.scoring(),
+            positive_likelihood_ratio,
+            negative_likelihood_ratio,
+            likelihood_ratio_threshold: self.report.likelihood_ratio_threshold,
+            minimum_samples: E::MIN_SAMPLES,
+        };
+        // The candidates share the datapoints that the SPRT evaluates, see [`Arrsac::parallel_scoring`].
+        let candidate_scorer = hooks.candidate_scorer.filter(|_| !full_scan);
+        let shared: Vec<(usize, Data)> = match candidate_scorer {
+            Some(_) => ordered.clone().collect(),
+            None => vec![],
+        };
+        let mut candidates = Vec::new();
         for _ in 0..num_hypotheses {
-            random_hypotheses.extend(self.generate_random_hypotheses_subset(
-                estimator,
-                data.clone(),
-                &inliers,
-            ));
-            for model in random_hypotheses.drain(..) {
-                if let Some(inliers) = self.asprt(
-                    data.clone().take(num_checked),
-                    &model,
-                    positive_likelihood_ratio,
-                    negative_likelihood_ratio,
-                    E::MIN_SAMPLES,
-                ) {
-                    hypotheses.push((model, inliers));
+            if self.report.stopped_early || self.is_out_of_estimations() {
+                break;
+            }
+            // Sample from all of the data until enough samples were drawn from it, see
+            // [`ArrsacBuilder::min_full_sampling_rounds`].
+            let subset =
+                inlier_subset.filter(|_| self.full_samples >= self.config.min_full_sampling_rounds);
+            if subset.is_none() {
+                self.full_samples += 1;
+            }
+            let sample_len = subset.map_or(data_len, <[usize]>::len);
+            self.populate_samples(self.samples_per_hypothesis(E::MIN_SAMPLES), sample_len);
+            if let Some(is_local) = hooks.is_local {
+                self.localize_samples(data.clone(), subset, is_local);
+            }
+            // Skip samples whose hypotheses were all rejected before.
+            let signature = self
+                .config
+                .dedupe_samples_global
+                .then(|| self.random_samples.signature(subset));
+            if let Some(signature) = signature {
+                if self.rejected_samples.contains(signature) {
+                    continue;
+                }
+            }
+            for model in self.estimate_samples(estimator, data.clone(), subset) {
+                candidates.push(Candidate {
+                    model,
+                    generation: self.next_generation(),
+                    inlier_indices: self.tracked_inliers(),
+                    scored: None,
+                });
+            }
+            match candidate_scorer {
+                Some(candidate_scorer) => candidate_scorer(&mut candidates, &shared, params),
+                None => {
+                    for candidate in candidates.iter_mut() {
+                        let inlier_indices = candidate.inlier_indices.as_mut();
+                        candidate.scored = if full_scan {
+                            let (inliers, support, residual_sum) = self.count_inliers(
+                                data.clone().take(num_checked),
+                                &candidate.model,
+                                inlier_indices,
+                            );
+                            (inliers >= E::MIN_SAMPLES).then_some((inliers, support, residual_sum))
+                        } else {
+                            sprt(ordered.clone(), &candidate.model, params, inlier_indices)
+                        };
+                    }
                 }
             }
+            let mut accepted = false;
+            for candidate in candidates.drain(..) {
+                let Candidate {
+                    model,
+                    generation,
+                    mut inlier_indices,
+                    scored,
+                } = candidate;
+                if let Some((inliers, support, residual_sum)) = scored {
+                    if let Some(inlier_indices) = &mut inlier_indices {
+                        inlier_indices.sort_unstable();
+                    }
+                    hypotheses.push(Hypothesis {
+                        model,
+                        inliers,
+                        support,
+                        residual_sum,
+                        generation,
+                        block,
+                        inlier_indices,
+                    });
+                    self.emit(Event::HypothesisAccepted {
+                        generation,
+                        inliers,
+                    });
+                    #[cfg(feature = "record-models")]
+                    self.record_model(generation, subset, true);
+                    accepted = true;
+                } else {
+                    self.emit(Event::HypothesisRejected { generation });
+                    #[cfg(feature = "record-models")]
+                    self.record_model(generation, subset, false);
+                }
+            }
+            if let (Some(signature), false) = (signature, accepted) {
+                self.rejected_samples.insert(signature);
+            }
+        }
+        self.best_inliers = inliers;
+    }
+
+    /// The number of datapoints the initial hypotheses are evaluated on, see
+    /// [`ArrsacBuilder::initial_eval_size`]. This saturates instead of overflowing.
+    fn initial_datapoints(&self) -> usize {
+        self.config.initial_eval_size.unwrap_or_else(|| {
+            self.config
+                .initialization_blocks
+                .saturating_mul(self.config.block_size)
+        })
+    }
+
+    /// The number of hypotheses retained after `block`, see [`ArrsacBuilder::max_candidate_hypotheses`].
+    ///
+    /// Shifting by `usize::BITS` or more would overflow, which retains the minimum instead.
+    fn retained_hypotheses(&self, block: usize) -> usize {
+        u32::try_from(block)
+            .ok()
+            .and_then(|block| self.config.max_candidate_hypotheses.checked_shr(block))
+            .unwrap_or(0)
+            .max(1)
+    }
+
+    /// The most survivors that fit into [`ArrsacBuilder::max_survivors_bytes`], which is at least one.
+    fn max_survivors<M>(&self) -> usize {
+        self.config.max_survivors_bytes.map_or(usize::MAX, |bytes| {
+            (bytes / core::mem::size_of::<Hypothesis<M>>()).max(1)
+        })
+    }
+
+    /// Checks if no more estimations are allowed, see [`ArrsacBuilder::max_estimator_calls`].
+    fn is_out_of_estimations(&self) -> bool {
+        self.config
+            .max_estimator_calls
+            .is_some_and(|max| self.report.estimator_calls >= max)
+    }
+
+    /// Checks if a hypothesis with `inliers` out of `num_checked` datapoints can be sampled from.
+    fn is_usable(&self, inliers: usize, num_checked: usize) -> bool {
+        match self.config.usable_hypothesis_threshold {
+            UsableHypothesisThreshold::EpsilonFloor => true,
+            UsableHypothesisThreshold::AbsoluteCount(count) => inliers >= count,
+            UsableHypothesisThreshold::FractionOfData(fraction) => {
+                inliers as f32 >= fraction * num_checked as f32
+            }
         }
     }
 
+    /// The number of hypotheses to estimate in a block whose best hypotheses so far are `hypotheses`,
+    /// which must be sorted, see [`ArrsacBuilder::min_estimations_per_block`].
+    fn block_estimations<Data, M: Model<Data>>(
+        &self,
+        data: impl Iterator<Item = Data> + Clone,
+        hypotheses: &[Hypothesis<M>],
+    ) -> usize {
+        let max = self.config.estimations_per_block;
+        let (Some(min), [first, second, ..]) = (self.config.min_estimations_per_block, hypotheses)
+        else {
+            return max;
+        };
+        let min = min.min(max);
+        let distance = jaccard_distance(
+            &self.inliers(data.clone(), &first.model),
+            &self.inliers(data, &second.model),
+        );
+        min + ((max - min) as f64 * distance + 0.5) as usize
+    }
+
+    /// The number of datapoints to sample for each hypothesis, see [`ArrsacBuilder::sample_size`].
+    fn samples_per_hypothesis(&self, minimum_samples: usize) -> usize {
+        self.config.sample_size.unwrap_or(minimum_samples)
+    }
+
     /// Generates as many hypotheses as one call to `Estimator::estimate()` returns from all data.
     fn generate_random_hypotheses<E, Data>(
         &mut self,
         estimator: &E,
         data: impl Iterator<Item = Data> + Clone,
+        is_local: Relation<'_, Data>,
+        strata: Strata<'_, Data>,
     ) -> E::ModelIter
     where
         E: Estimator<Data>,
     {
-        self.populate_samples(E::MIN_SAMPLES, data.clone().count());
-        estimator.estimate(
-            self.random_samples
-                .iter()
-                .map(|&ix| data.clone().nth(ix as usize).unwrap()),
-        )
+        self.full_samples += 1;
+        self.populate_samples(
+            self.samples_per_hypothesis(E::MIN_SAMPLES),
+            data.clone().count(),
+        );
+        if let Some((stratum, num_strata)) = strata {
+            self.stratify_samples(data.clone(), stratum, num_strata);
+        }
+        if let Some(is_local) = is_local {
+            self.localize_samples(data.clone(), None, is_local);
+        }
+        self.estimate_samples(estimator, data, None)
     }
 
-    /// Generates as many hypotheses as one call to `Estimator::estimate()` returns from a subset of the data.
-    fn generate_random_hypotheses_subset<E, Data>(
+    /// Generates the hypotheses of the seed sample at `ix` from `data` of `len` datapoints, see
+    /// [`ArrsacBuilder::seed_samples`].
+    fn seeded_hypotheses<E, Data>(
         &mut self,
         estimator: &E,
         data: impl Iterator<Item = Data> + Clone,
-        subset: &[usize],
+        len: usize,
+        ix: usize,
     ) -> E::ModelIter
     where
         E: Estimator<Data>,
     {
-        self.populate_samples(E::MIN_SAMPLES, subset.len());
-        estimator.estimate(
-            core::mem::take(&mut self.random_samples)
-                .iter()
-                .map(|&ix| data.clone().nth(subset[ix as usize]).unwrap()),
-        )
+        let sample = &self.config.seed_samples[ix];
+        assert_eq!(
+            sample.len(),
+            E::MIN_SAMPLES,
+            "seed sample {ix} must have exactly as many indices as a minimal sample"
+        );
+        self.random_samples.clear();
+        #[cfg(feature = "record-models")]
+        self.recorder.new_sample();
+        for &s in sample {
+            assert!((s as usize) < len, "seed sample index {s} is out of range");
+            self.random_samples.push(s);
+        }
+        self.estimate_samples(estimator, data, None)
     }
 
-    /// Algorithm 1 in "Randomized RANSAC with Sequential Probability Ratio Test".
-    ///
-    /// This tests if a model is accepted. Returns `Some(inliers)` if accepted or `None` if rejected.
+    /// Calls `Estimator::estimate()` with the datapoints in `self.random_samples`, which are indices
+    /// into `subset` if it is provided.
+    fn estimate_samples<E, Data>(
+        &mut self,
+        estimator: &E,
+        data: impl Iterator<Item = Data> + Clone,
+        subset: Option<&[usize]>,
+    ) -> E::ModelIter
+    where
+        E: Estimator<Data>,
+    {
+        self.report.estimator_calls += 1;
+        estimator.estimate(self.random_samples.as_slice().iter().map(move |&ix| {
+            let ix = subset.map_or(ix as usize, |subset| subset[ix as usize]);
+            data.clone().nth(ix).unwrap()
+        }))
+    }
+
+    /// Runs [`sprt`] with the configuration and state of this [`Arrsac`].
     ///
     /// `inlier_threshold` - The model residual error threshold between inliers and outliers
     /// `positive_likelihood_ratio` - `δ / ε`
     /// `negative_likelihood_ratio` - `(1 - δ) / (1 - ε)`
-    fn asprt<Data, M: Model<Data>>(
+    #[cfg(feature = "fuzzing")]
+    pub(crate) fn asprt<Data, M: Model<Data>>(
         &self,
-        data: impl Iterator<Item = Data>,
+        data: impl Iterator<Item = (usize, Data)>,
         model: &M,
         positive_likelihood_ratio: f32,
         negative_likelihood_ratio: f32,
         minimum_samples: usize,
-    ) -> Option<usize> {
-        let mut likelihood_ratio = 1.0;
-        let mut inliers = 0;
-        for data in data {
-            likelihood_ratio *= if model.residual(&data) < self.inlier_threshold {
-                inliers += 1;
-                positive_likelihood_ratio
-            } else {
-                negative_likelihood_ratio
-            };
+        inlier_indices: Option<&mut Vec<usize>>,
+    ) -> Option<(usize, f64, f64)> {
+        let params = Sprt {
+            scoring: self.scoring(),
+            positive_likelihood_ratio,
+            negative_likelihood_ratio,
+            likelihood_ratio_threshold: self.report.likelihood_ratio_threshold,
+            minimum_samples,
+        };
+        sprt::<Data, Data, M>(data, model, params, inlier_indices)
+    }
 
-            if likelihood_ratio > self.likelihood_ratio_threshold || likelihood_ratio.is_nan() {
-                return None;
-            }
+    /// Checks if a datapoint with `residual` is an inlier.
+    ///
+    /// Non-finite residuals, such as those from a degenerate model, are always outliers.
+    fn is_inlier(&self, residual: f64) -> bool {
+        self.scoring().is_inlier(residual)
+    }
+
+    /// Checks if a datapoint with `residual` is one of the returned inliers, see
+    /// [`ArrsacBuilder::report_threshold`].
+    fn is_reported_inlier(&self, residual: f64) -> bool {
+        match self.config.report_threshold {
+            Some(threshold) => self.is_within(residual, threshold),
+            None => self.is_inlier(residual),
+        }
+    }
+
+    /// Checks if `residual` is within `threshold`, see [`ArrsacBuilder::threshold_tolerance`].
+    fn is_within(&self, residual: f64, threshold: f64) -> bool {
+        InlierBound::new(threshold, self.config.threshold_tolerance).contains(residual)
+    }
+
+    /// How datapoints are scored in the current run.
+    fn scoring(&self) -> Scoring {
+        let inlier_threshold = self.run_inlier_threshold();
+        Scoring {
+            inlier_threshold,
+            bound: InlierBound::new(inlier_threshold, self.config.threshold_tolerance),
+            weight_fn: self.config.weight_fn,
+            recency_scale: 1.0,
         }
+    }
+
+    /// The inlier threshold of the current run, see [`ArrsacBuilder::adaptive_threshold_percentile`]
+    /// and [`ArrsacBuilder::calibrate_threshold`].
+    fn run_inlier_threshold(&self) -> f64 {
+        self.report
+            .adaptive_inlier_threshold
+            .or(self.report.calibrated_inlier_threshold)
+            .unwrap_or(self.config.inlier_threshold)
+    }
 
-        (inliers >= minimum_samples).then(|| inliers)
+    /// The inlier threshold calibrated from the inliers of `model` over the inlier window of `data`,
+    /// see [`ArrsacBuilder::calibrate_threshold`].
+    fn calibrated_threshold<Data, M: Model<Data>>(
+        &self,
+        data: impl Iterator<Item = Data>,
+        model: &M,
+        k: f64,
+    ) -> Option<f64> {
+        let mut median = P2Quantile::new(0.5);
+        for residual in data
+            .take(self.config.inlier_window)
+            .map(|data| model.residual(&data))
+            .filter(|&residual| self.is_inlier(residual))
+        {
+            median.push(residual);
+        }
+        // The median absolute deviation of Gaussian noise is this many standard deviations.
+        let sigma = 1.4826 * median.quantile()?;
+        (sigma > 0.0).then_some((k * sigma).min(self.config.inlier_threshold))
     }
 
-    /// Determines the number of inliers a model has.
+    /// Determines the number of inliers a model has, their weighted support and the sum of their
+    /// residuals. The indices of the inliers are pushed to `inlier_indices` if provided.
     fn count_inliers<Data, M: Model<Data>>(
         &self,
         data: impl Iterator<Item = Data>,
         model: &M,
-    ) -> usize {
-        data.filter(|data| model.residual(data) < self.inlier_threshold)
-            .count()
+        mut inlier_indices: Option<&mut Vec<usize>>,
+    ) -> (usize, f64, f64) {
+        let mut inliers = 0;
+        let mut support = 0.0;
+        let mut residual_sum = 0.0;
+        let scoring = self.scoring();
+        for (ix, data) in data.enumerate() {
+            let residual = model.residual(&data);
+            if scoring.is_inlier(residual) {
+                inliers += 1;
+                support += scoring.weight(residual);
+                residual_sum += residual;
+                if let Some(inlier_indices) = inlier_indices.as_deref_mut() {
+                    inlier_indices.push(ix);
+                }
+            }
+        }
+        (inliers, support, residual_sum)
+    }
+
+    /// An empty list of inlier indices if they are tracked, see [`ArrsacBuilder::incremental_inliers`].
+    fn tracked_inliers(&self) -> Option<Vec<usize>> {
+        self.config.incremental_inliers.then(Vec::new)
+    }
+
+    /// Takes the model and its inliers over all of `data` out of a hypothesis which was scored over
+    /// all of `data`, reusing the tracked inliers when possible.
+    fn take_model_inliers<Data, M: Model<Data>>(
+        &self,
+        hypothesis: Hypothesis<M>,
+        data: impl Iterator<Item = Data>,
+    ) -> (M, Vec<usize>) {
+        let mut inliers = Vec::new();
+        let model = self.take_model_inliers_into(hypothesis, data, &mut inliers);
+        (model, inliers)
+    }
+
+    /// Takes the model out of a hypothesis like [`Arrsac::take_model_inliers`], but pushes its
+    /// inliers to `inliers`.
+    fn take_model_inliers_into<Data, M: Model<Data>>(
+        &self,
+        hypothesis: Hypothesis<M>,
+        data: impl Iterator<Item = Data>,
+        inliers: &mut Vec<usize>,
+    ) -> M {
+        // The tracked inliers were found with the inlier threshold instead of the report threshold.
+        match hypothesis.inlier_indices {
+            // Keep the allocation of the tracked inliers unless the buffer has its own.
+            Some(tracked) if self.config.report_threshold.is_none() => {
+                if inliers.capacity() == 0 {
+                    *inliers = tracked;
+                } else {
+                    inliers.extend(tracked);
+                }
+            }
+            _ => inliers.extend(
+                data.take(self.config.inlier_window)
+                    .enumerate()
+                    .filter(|(_, data)| self.is_reported_inlier(hypothesis.model.residual(data)))
+                    .map(|(ix, _)| ix),
+            ),
+        }
+        hypothesis.model
+    }
+
+    /// Scores hypotheses from scratch over the inlier window of `data`.
+    fn score_hypotheses<Data, M: Model<Data>>(
+        &self,
+        data: impl Iterator<Item = Data> + Clone,
+        hypotheses: &mut [Hypothesis<M>],
+    ) {
+        for hypothesis in hypotheses.iter_mut() {
+            if let Some(inlier_indices) = &mut hypothesis.inlier_indices {
+                inlier_indices.clear();
+            }
+            (
+                hypothesis.inliers,
+                hypothesis.support,
+                hypothesis.residual_sum,
+            ) = self.count_inliers(
+                data.clone().take(self.config.inlier_window),
+                &hypothesis.model,
+                hypothesis.inlier_indices.as_mut(),
+            );
+        }
+    }
+
+    /// Weighs the support of `hypotheses` over the inlier window of `data` by how recent the blocks
+    /// starting at `block_starts` are, see [`ArrsacBuilder::recency_weight`]. Datapoints after the
+    /// last block count as part of it.
+    fn weigh_recency<Data, M: Model<Data>>(
+        &self,
+        data: impl Iterator<Item = Data> + Clone,
+        hypotheses: &mut [Hypothesis<M>],
+        block_starts: &[usize],
+    ) {
+        for hypothesis in hypotheses.iter_mut() {
+            // This weighs the blocks like `search_block` does.
+            let mut scoring = self.scoring();
+            let mut support = 0.0;
+            let mut block = 0;
+            for (ix, data) in data.clone().take(self.config.inlier_window).enumerate() {
+                // Everything before the start of a block is older than it.
+                while block_starts
+                    .get(block + 1)
+                    .is_some_and(|&start| start <= ix)
+                {
+                    let (scale, normalize) =
+                        recency_scale(scoring.recency_scale, self.config.recency_weight);
+                    scoring.recency_scale = scale;
+                    if normalize {
+                        support /= MAX_RECENCY_SCALE;
+                    }
+                    block += 1;
+                }
+                let residual = hypothesis.model.residual(&data);
+                if scoring.is_inlier(residual) {
+                    support += scoring.weight(residual);
+                }
+            }
+            hypothesis.support = support;
+        }
     }
 
     /// Gets indices of inliers for a model.
@@ -398,10 +3172,119 @@ where
         model: &M,
     ) -> Vec<usize> {
         data.enumerate()
-            .filter(|(_, data)| model.residual(data) < self.inlier_threshold)
+            .filter(|(_, data)| self.is_inlier(model.residual(data)))
+            .map(|(ix, _)| ix)
+            .collect()
+    }
+
+    /// Computes the residual of `model` for every datapoint in `data`, in the order of the input.
+    ///
+    /// This is useful to visualize how well a model returned by ARRSAC fits all of the data, or to
+    /// sweep over inlier thresholds with [`Arrsac::threshold_residuals`].
+    pub fn residuals<Data, M: Model<Data>>(
+        &self,
+        data: impl Iterator<Item = Data>,
+        model: &M,
+    ) -> Vec<f64> {
+        data.map(|data| model.residual(&data)).collect()
+    }
+
+    /// Finds the inliers of `model` in `data` with a different `inlier_threshold` than the one
+    /// of the run that found it, without running ARRSAC again.
+    ///
+    /// This applies [`ArrsacBuilder::threshold_tolerance`] like a run does. When tuning the
+    /// threshold interactively, the best model often stays the same while only its inliers change,
+    /// so a new threshold can be tried without generating any hypotheses. Run ARRSAC again once the
+    /// threshold has changed so much that a different model might fit better.
+    pub fn recompute_inliers<Data, M: Model<Data>>(
+        &self,
+        model: &M,
+        data: impl Iterator<Item = Data>,
+        inlier_threshold: f64,
+    ) -> Vec<usize> {
+        data.enumerate()
+            .filter(|(_, data)| self.is_within(model.residual(data), inlier_threshold))
             .map(|(ix, _)| ix)
             .collect()
     }
+
+    /// Finds the inliers among `residuals` with `inlier_threshold` like
+    /// [`Arrsac::recompute_inliers`], but from residuals computed once by [`Arrsac::residuals`].
+    ///
+    /// This is the fast path for sweeping over many thresholds: compute the residuals of the
+    /// model once and then call this for every threshold, so the model doesn't have to compute any
+    /// residuals again.
+    pub fn threshold_residuals(&self, residuals: &[f64], inlier_threshold: f64) -> Vec<usize> {
+        residuals
+            .iter()
+            .enumerate()
+            .filter(|&(_, &residual)| self.is_within(residual, inlier_threshold))
+            .map(|(ix, _)| ix)
+            .collect()
+    }
+
+    /// The standard deviation of the residuals of the inliers of `model` in `data`, computed in one
+    /// pass, which is `NaN` if there are no inliers.
+    ///
+    /// The inliers are the datapoints that would be returned as inliers of the model like in
+    /// [`Arrsac::validate`], and this is the [`ResidualStats::std_dev`] of their residuals.
+    pub fn inlier_residual_std<Data, M: Model<Data>>(
+        &self,
+        data: impl Iterator<Item = Data>,
+        model: &M,
+    ) -> f64 {
+        let residuals = data
+            .map(|data| model.residual(&data))
+            .filter(|&residual| self.is_reported_inlier(residual));
+        residual_stats(residuals).map_or(f64::NAN, |stats| stats.std_dev)
+    }
+
+    /// Measures how well `model` fits `data`, such as a held-out validation set for a model found
+    /// on other data.
+    ///
+    /// The inliers are the datapoints that would be returned as inliers of the model, so this uses
+    /// the inlier threshold of the most recent run, or the [`ArrsacBuilder::report_threshold`] if
+    /// there is one. The inlier window doesn't apply.
+    pub fn validate<Data, M: Model<Data>>(
+        &self,
+        model: &M,
+        data: impl Iterator<Item = Data>,
+    ) -> ValidationStats {
+        let mut datapoints = 0;
+        let residuals = data
+            .inspect(|_| datapoints += 1)
+            .map(|data| model.residual(&data))
+            .filter(|&residual| self.is_reported_inlier(residual));
+        let mut inliers = 0;
+        let inlier_residuals = residual_stats(residuals.inspect(|_| inliers += 1));
+        ValidationStats {
+            inliers,
+            datapoints,
+            inlier_ratio: if datapoints == 0 {
+                0.0
+            } else {
+                inliers as f64 / datapoints as f64
+            },
+            inlier_residuals,
+        }
+    }
+}
+
+impl<R> Arrsac<R>
+where
+    R: RngCore + SeedableRng,
+{
+    /// Replaces the state of the RNG with a new one created from `seed`, keeping the configuration.
+    ///
+    /// This allows reproducible sweeps over many datasets with one [`Arrsac`], such as using seed `i`
+    /// for dataset `i`, regardless of the order the datasets are processed in.
+    pub fn reseed(&mut self, seed: R::Seed) {
+        self.rng = R::from_seed(seed);
+        #[cfg(feature = "count-rng")]
+        {
+            self.rng_draws = 0;
+        }
+    }
 }
 
 impl<E, R, Data> Consensus<E, Data> for Arrsac<R>
@@ -422,67 +3305,30 @@ where
     where
         I: Iterator<Item = Data> + Clone,
     {
-        // Don't do anything if we don't have enough data.
-        if data.clone().count() < E::MIN_SAMPLES {
-            return None;
-        }
-        // Generate the initial set of hypotheses. This also gets us an estimate of delta.
-        let (mut hypotheses, delta) = self.initial_hypotheses(estimator, data.clone());
+        let hypotheses = self.survivors(estimator, data.clone(), SearchHooks::default());
+        self.select(hypotheses, data)
+    }
+}
 
-        // If there are no initial hypotheses then initialization failed, so exit early.
-        if hypotheses.is_empty() {
-            return None;
-        }
+/// Allows passing `&mut arrsac` wherever a [`Consensus`] is expected without giving up the [`Arrsac`].
+impl<E, R, Data> Consensus<E, Data> for &mut Arrsac<R>
+where
+    E: Estimator<Data>,
+    R: RngCore,
+{
+    type Inliers = Vec<usize>;
 
-        // Gradually increase how many datapoints we are evaluating until we evaluate them all.
-        // This starts at the first block that was not evaluated in initial_hypotheses.
-        'outer: for block in self.initialization_blocks.. {
-            let samples_up_to_beginning_of_block = block * self.block_size;
-            let samples_up_to_end_of_block = samples_up_to_beginning_of_block + self.block_size;
-            // Score hypotheses with samples.
-            for sample in samples_up_to_beginning_of_block..samples_up_to_end_of_block {
-                // Score the hypotheses with the new datapoint.
-                let new_datapoint = if let Some(datapoint) = data.clone().nth(sample) {
-                    datapoint
-                } else {
-                    // We reached the last datapoint, so break out of the outer loop.
-                    break 'outer;
-                };
-                for (hypothesis, inlier_count) in hypotheses.iter_mut() {
-                    if hypothesis.residual(&new_datapoint) < self.inlier_threshold {
-                        *inlier_count += 1;
-                    }
-                }
-            }
-            // Sort the hypotheses by their inliers to find the best.
-            hypotheses.sort_unstable_by_key(|&(_, inliers)| Reverse(inliers));
-            // Populate hypotheses with hypotheses that pass SPRT.
-            self.populate_hypotheses_sprt(
-                estimator,
-                &mut hypotheses,
-                delta,
-                data.clone(),
-                samples_up_to_end_of_block,
-                self.estimations_per_block,
-            );
-            // This will retain at least half of the hypotheses each time
-            // and gradually decrease as the number of samples we are evaluating increases.
-            // NOTE:
-            // The paper says to use a peculiar formula that just results in doing
-            // this basic right shift below, but as written it contained some apparent errors in
-            // where it was ran. This seems to be the correct location to do this.
-            hypotheses.sort_unstable_by_key(|&(_, inliers)| Reverse(inliers));
-            hypotheses.truncate(self.max_candidate_hypotheses >> block);
-            if hypotheses.len() <= 1 {
-                break 'outer;
-            }
-        }
-        hypotheses
-            .into_iter()
-            .max_by_key(|&(_, inliers)| inliers)
-            .map(|(model, _)| {
-                let inliers = self.inliers(data.clone(), &model);
-                (model, inliers)
-            })
+    fn model<I>(&mut self, estimator: &E, data: I) -> Option<E::Model>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        (**self).model(estimator, data)
+    }
+
+    fn model_inliers<I>(&mut self, estimator: &E, data: I) -> Option<(E::Model, Self::Inliers)>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        (**self).model_inliers(estimator, data)
     }
 }
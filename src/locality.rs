@@ -0,0 +1,63 @@
+use crate::{Arrsac, SearchHooks};
+use alloc::vec::Vec;
+use rand_core::RngCore;
+use sample_consensus::{Consensus, Estimator};
+
+/// An [`Arrsac`] that draws minimal samples from datapoints which are local to each other, created
+/// by [`Arrsac::locality_bias`].
+pub struct LocalityBias<'a, R, F> {
+    arrsac: &'a mut Arrsac<R>,
+    is_local: F,
+}
+
+impl<R> Arrsac<R>
+where
+    R: RngCore,
+{
+    /// Biases the minimal samples towards datapoints that are local to each other according to
+    /// `is_local`, such as being within some radius in space or time.
+    ///
+    /// After the first datapoint of a sample is drawn, the rest of the sample is drawn from the
+    /// datapoints that are local to it. This makes it more likely that every datapoint of a sample
+    /// belongs to the same structure in large scenes with many unrelated structures. The sample is
+    /// drawn from all of the data instead if too few datapoints are local to the first one.
+    ///
+    /// Finding the local datapoints takes a pass over the data for every sample.
+    pub fn locality_bias<F>(&mut self, is_local: F) -> LocalityBias<'_, R, F> {
+        LocalityBias {
+            arrsac: self,
+            is_local,
+        }
+    }
+}
+
+impl<E, R, F, Data> Consensus<E, Data> for LocalityBias<'_, R, F>
+where
+    E: Estimator<Data>,
+    R: RngCore,
+    F: Fn(&Data, &Data) -> bool,
+{
+    type Inliers = Vec<usize>;
+
+    fn model<I>(&mut self, estimator: &E, data: I) -> Option<E::Model>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        self.model_inliers(estimator, data).map(|(model, _)| model)
+    }
+
+    fn model_inliers<I>(&mut self, estimator: &E, data: I) -> Option<(E::Model, Self::Inliers)>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        let hypotheses = self.arrsac.survivors(
+            estimator,
+            data.clone(),
+            SearchHooks {
+                is_local: Some(&self.is_local),
+                ..SearchHooks::default()
+            },
+        );
+        self.arrsac.select(hypotheses, data)
+    }
+}
@@ -0,0 +1,95 @@
+use crate::Arrsac;
+use alloc::vec::Vec;
+use rand_core::RngCore;
+use sample_consensus::{Consensus, Estimator, Model};
+
+/// Computes the residual that a run uses for a datapoint from the residual of a model, such as for
+/// [`Arrsac::residual_scale`] and [`Arrsac::residual_prefilter`].
+pub(crate) trait MapResidual<Data, M> {
+    fn map_residual(&self, model: &M, data: &Data) -> f64;
+}
+
+impl<R> Arrsac<R>
+where
+    R: RngCore,
+{
+    /// Runs ARRSAC like [`Consensus::model_inliers`] on the models of `estimator` with their
+    /// residuals mapped by `map`.
+    pub(crate) fn mapped_model_inliers<E, G, Data, I>(
+        &mut self,
+        estimator: &E,
+        map: &G,
+        data: I,
+    ) -> Option<(E::Model, Vec<usize>)>
+    where
+        E: Estimator<Data>,
+        G: MapResidual<Data, E::Model>,
+        I: Iterator<Item = Data> + Clone,
+    {
+        let estimator = MappedEstimator { estimator, map };
+        self.model_inliers(&estimator, data)
+            .map(|(mapped, inliers)| (mapped.model, inliers))
+    }
+}
+
+/// Estimates models whose residuals are mapped by `map`.
+struct MappedEstimator<'a, E, G> {
+    estimator: &'a E,
+    map: &'a G,
+}
+
+/// A model whose residuals are mapped by `map`.
+struct Mapped<'a, M, G> {
+    model: M,
+    map: &'a G,
+}
+
+impl<M, G, Data> Model<Data> for Mapped<'_, M, G>
+where
+    G: MapResidual<Data, M>,
+{
+    fn residual(&self, data: &Data) -> f64 {
+        self.map.map_residual(&self.model, data)
+    }
+}
+
+/// The models of a [`MappedEstimator`].
+struct MappedModels<'a, I, G> {
+    models: I,
+    map: &'a G,
+}
+
+impl<'a, I, G> Iterator for MappedModels<'a, I, G>
+where
+    I: Iterator,
+{
+    type Item = Mapped<'a, I::Item, G>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let model = self.models.next()?;
+        Some(Mapped {
+            model,
+            map: self.map,
+        })
+    }
+}
+
+impl<'a, E, G, Data> Estimator<Data> for MappedEstimator<'a, E, G>
+where
+    E: Estimator<Data>,
+    G: MapResidual<Data, E::Model>,
+{
+    type Model = Mapped<'a, E::Model, G>;
+    type ModelIter = MappedModels<'a, <E::ModelIter as IntoIterator>::IntoIter, G>;
+    const MIN_SAMPLES: usize = E::MIN_SAMPLES;
+
+    fn estimate<I>(&self, data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        MappedModels {
+            models: self.estimator.estimate(data).into_iter(),
+            map: self.map,
+        }
+    }
+}
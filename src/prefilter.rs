@@ -0,0 +1,73 @@
+use crate::{mapped::MapResidual, Arrsac};
+use alloc::vec::Vec;
+use rand_core::RngCore;
+use sample_consensus::{Consensus, Estimator, Model};
+
+/// An [`Arrsac`] that checks every datapoint with a cheap test before computing its residual,
+/// created by [`Arrsac::residual_prefilter`].
+pub struct ResidualPrefilter<'a, R, F> {
+    arrsac: &'a mut Arrsac<R>,
+    prefilter: Prefilter<F>,
+}
+
+impl<R> Arrsac<R>
+where
+    R: RngCore,
+{
+    /// Checks every datapoint against a model with `prefilter` before computing its residual, such
+    /// as with a bounding box test, and treats it as an outlier without computing its residual if
+    /// the check fails.
+    ///
+    /// This is a performance hook for models with expensive residuals, such as reprojection with
+    /// lens distortion, which skips the residuals of obvious outliers. It applies wherever
+    /// residuals are used, including the SPRT and the inliers that are returned. A `prefilter` that
+    /// passes every datapoint is the same as not filtering.
+    pub fn residual_prefilter<F>(&mut self, prefilter: F) -> ResidualPrefilter<'_, R, F> {
+        ResidualPrefilter {
+            arrsac: self,
+            prefilter: Prefilter(prefilter),
+        }
+    }
+}
+
+impl<E, R, F, Data> Consensus<E, Data> for ResidualPrefilter<'_, R, F>
+where
+    E: Estimator<Data>,
+    R: RngCore,
+    F: Fn(&Data, &E::Model) -> bool,
+{
+    type Inliers = Vec<usize>;
+
+    fn model<I>(&mut self, estimator: &E, data: I) -> Option<E::Model>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        self.model_inliers(estimator, data).map(|(model, _)| model)
+    }
+
+    fn model_inliers<I>(&mut self, estimator: &E, data: I) -> Option<(E::Model, Self::Inliers)>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        self.arrsac
+            .mapped_model_inliers(estimator, &self.prefilter, data)
+    }
+}
+
+/// Only computes the residual of a model for datapoints that pass the prefilter.
+struct Prefilter<F>(F);
+
+impl<M, F, Data> MapResidual<Data, M> for Prefilter<F>
+where
+    M: Model<Data>,
+    F: Fn(&Data, &M) -> bool,
+{
+    fn map_residual(&self, model: &M, data: &Data) -> f64 {
+        // A non-finite residual is never an inlier.
+        if (self.0)(data, model) {
+            model.residual(data)
+        } else {
+            f64::INFINITY
+        }
+    }
+}
@@ -0,0 +1,10 @@
+//! Everything needed for typical use of ARRSAC, so that `use arrsac::prelude::*;` is enough.
+//!
+//! This includes the traits from `sample-consensus` that are needed to run ARRSAC and to implement
+//! models and estimators for it.
+
+pub use crate::{
+    Arrsac, ArrsacBuilder, ArrsacError, AsprtOrder, ModelResult, Report, TiePolicy,
+    UsableHypothesisThreshold, WeightFn,
+};
+pub use sample_consensus::{Consensus, Estimator, Model};
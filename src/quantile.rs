@@ -0,0 +1,108 @@
+/// Streaming estimate of a quantile without storing the observations.
+///
+/// This is the P² algorithm from "The P² Algorithm for Dynamic Calculation of Quantiles and
+/// Histograms Without Storing Observations" by Jain and Chlamtac. It keeps five markers whose
+/// heights approximate the minimum, the `p / 2`, `p` and `(1 + p) / 2` quantiles and the maximum.
+pub(crate) struct P2Quantile {
+    /// The quantile to estimate, in `[0, 1]`.
+    p: f64,
+    /// The number of observations so far.
+    count: usize,
+    /// The heights of the markers.
+    heights: [f64; 5],
+    /// The actual positions of the markers, starting at 1.
+    positions: [f64; 5],
+    /// The desired positions of the markers.
+    desired: [f64; 5],
+    /// How much the desired positions increase with each observation.
+    increments: [f64; 5],
+}
+
+impl P2Quantile {
+    pub(crate) fn new(p: f64) -> Self {
+        Self {
+            p,
+            count: 0,
+            heights: [0.0; 5],
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired: [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0],
+            increments: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0],
+        }
+    }
+
+    /// Adds an observation to the estimate.
+    pub(crate) fn push(&mut self, x: f64) {
+        // The first five observations are the initial marker heights.
+        if self.count < 5 {
+            self.heights[self.count] = x;
+            self.count += 1;
+            if self.count == 5 {
+                self.heights.sort_unstable_by(f64::total_cmp);
+            }
+            return;
+        }
+        self.count += 1;
+        // Find the cell the observation falls in, extending the extreme markers if needed.
+        let k = if x < self.heights[0] {
+            self.heights[0] = x;
+            0
+        } else if x >= self.heights[4] {
+            self.heights[4] = x;
+            3
+        } else {
+            (1..5).find(|&i| x < self.heights[i]).unwrap() - 1
+        };
+        for position in &mut self.positions[k + 1..] {
+            *position += 1.0;
+        }
+        for (desired, increment) in self.desired.iter_mut().zip(self.increments) {
+            *desired += increment;
+        }
+        // Move the middle markers towards their desired positions if they are off by at least one.
+        for i in 1..4 {
+            let d = self.desired[i] - self.positions[i];
+            let right = self.positions[i + 1] - self.positions[i];
+            let left = self.positions[i - 1] - self.positions[i];
+            if (d >= 1.0 && right > 1.0) || (d <= -1.0 && left < -1.0) {
+                let d = if d > 0.0 { 1.0 } else { -1.0 };
+                let parabolic = self.parabolic(i, d);
+                self.heights[i] =
+                    if self.heights[i - 1] < parabolic && parabolic < self.heights[i + 1] {
+                        parabolic
+                    } else {
+                        self.linear(i, d)
+                    };
+                self.positions[i] += d;
+            }
+        }
+    }
+
+    /// The estimated quantile, which is `None` if there were no observations.
+    pub(crate) fn quantile(&self) -> Option<f64> {
+        if self.count >= 5 {
+            return Some(self.heights[2]);
+        }
+        // Too few observations for the markers, so pick the closest observation instead.
+        let mut heights = self.heights;
+        let heights = &mut heights[..self.count];
+        heights.sort_unstable_by(f64::total_cmp);
+        let last = heights.len().checked_sub(1)?;
+        let ix = (self.p * last as f64 + 0.5) as usize;
+        Some(heights[ix.min(last)])
+    }
+
+    /// The piecewise-parabolic prediction of marker `i` moved by `d`.
+    fn parabolic(&self, i: usize, d: f64) -> f64 {
+        let (q, n) = (&self.heights, &self.positions);
+        q[i] + d / (n[i + 1] - n[i - 1])
+            * ((n[i] - n[i - 1] + d) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                + (n[i + 1] - n[i] - d) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]))
+    }
+
+    /// The linear prediction of marker `i` moved by `d`.
+    fn linear(&self, i: usize, d: f64) -> f64 {
+        let (q, n) = (&self.heights, &self.positions);
+        let j = if d > 0.0 { i + 1 } else { i - 1 };
+        q[i] + d * (q[j] - q[i]) / (n[j] - n[i])
+    }
+}
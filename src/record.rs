@@ -0,0 +1,130 @@
+use crate::Arrsac;
+use alloc::vec::Vec;
+use rand_core::RngCore;
+use sample_consensus::Estimator;
+
+/// A model generated during a run, recorded with [`Arrsac::record_models`].
+///
+/// The model itself isn't stored since [`Arrsac`] doesn't know the type of the models, but it can
+/// be estimated again from its sample with [`RecordedModel::estimate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedModel {
+    /// The generation of the model, see [`Event::HypothesisGenerated`](crate::Event::HypothesisGenerated).
+    pub generation: usize,
+    /// The indices of the datapoints the model was estimated from, in the order they were drawn.
+    pub samples: Vec<usize>,
+    /// The position of the model among the models the estimator returned for the sample.
+    pub position: usize,
+    /// The model was kept as a hypothesis instead of being rejected by the SPRT.
+    pub accepted: bool,
+}
+
+impl RecordedModel {
+    /// Estimates the model again from its sample of `data`, which must be the data of the run.
+    ///
+    /// This is only the recorded model if the estimator is deterministic.
+    pub fn estimate<E, Data>(
+        &self,
+        estimator: &E,
+        data: impl Iterator<Item = Data> + Clone,
+    ) -> Option<E::Model>
+    where
+        E: Estimator<Data>,
+    {
+        estimator
+            .estimate(self.samples.iter().map(|&ix| data.clone().nth(ix).unwrap()))
+            .into_iter()
+            .nth(self.position)
+    }
+}
+
+/// The models recorded during the current run.
+#[derive(Debug, Clone)]
+pub(crate) struct Recorder {
+    enabled: bool,
+    max_models: usize,
+    models: Vec<RecordedModel>,
+    /// The position of the next model estimated from the current sample.
+    position: usize,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_models: 4096,
+            models: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+impl Recorder {
+    pub(crate) fn clear(&mut self) {
+        self.models.clear();
+    }
+
+    /// Starts counting the positions of the models estimated from a new sample.
+    pub(crate) fn new_sample(&mut self) {
+        self.position = 0;
+    }
+}
+
+impl<R> Arrsac<R>
+where
+    R: RngCore,
+{
+    /// Records every model generated during a run along with the sample it was estimated from and
+    /// whether it was accepted, which can be retrieved with [`Arrsac::recorded_models`].
+    ///
+    /// This is meant for debugging a run offline, so it is heavyweight. Only the first
+    /// [`Arrsac::max_recorded_models`] models of a run are recorded.
+    #[must_use]
+    pub fn record_models(mut self, record_models: bool) -> Self {
+        self.recorder.enabled = record_models;
+        self
+    }
+
+    /// The maximum number of models to record during a run, see [`Arrsac::record_models`].
+    ///
+    /// Default: `4096`
+    #[must_use]
+    pub fn max_recorded_models(mut self, max_recorded_models: usize) -> Self {
+        self.recorder.max_models = max_recorded_models;
+        self
+    }
+
+    /// The models recorded during the most recent run in the order they were generated, see
+    /// [`Arrsac::record_models`].
+    pub fn recorded_models(&self) -> &[RecordedModel] {
+        &self.recorder.models
+    }
+
+    /// Records the next model estimated from `self.random_samples`, which are indices into `subset`
+    /// if it is provided.
+    pub(crate) fn record_model(
+        &mut self,
+        generation: usize,
+        subset: Option<&[usize]>,
+        accepted: bool,
+    ) {
+        let recorder = &mut self.recorder;
+        let position = recorder.position;
+        recorder.position += 1;
+        if !recorder.enabled || recorder.models.len() >= recorder.max_models {
+            return;
+        }
+        let samples = self
+            .random_samples
+            .as_slice()
+            .iter()
+            .map(|&ix| subset.map_or(ix as usize, |subset| subset[ix as usize]))
+            .collect();
+        recorder.models.push(RecordedModel {
+            generation,
+            samples,
+            position,
+            accepted,
+        });
+    }
+}
@@ -0,0 +1,86 @@
+use crate::Arrsac;
+use alloc::vec::Vec;
+use rand_core::RngCore;
+use sample_consensus::{Consensus, Estimator, Model};
+
+/// An [`Estimator`] that can refine a model given its inliers, which is often much cheaper than
+/// estimating a model from all of the inliers from scratch.
+///
+/// Use [`Arrsac::refined`] to refine the returned model with [`RefiningEstimator::refine`].
+/// Implementing the trait without overriding [`RefiningEstimator::refine`] never refines, so an
+/// estimator that can't refine anything can still be used wherever one is required.
+pub trait RefiningEstimator<Data>: Estimator<Data> {
+    /// Refines `model` given the datapoints that are its inliers, such as with a least squares fit
+    /// starting from `model`. This is `None` if the model couldn't be refined, which is what the
+    /// default implementation always returns.
+    fn refine<I>(&self, model: &Self::Model, inliers: I) -> Option<Self::Model>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        let _ = (model, inliers);
+        None
+    }
+}
+
+/// An [`Arrsac`] that refines the best model with a [`RefiningEstimator`], created by
+/// [`Arrsac::refined`].
+pub struct Refined<'a, R> {
+    arrsac: &'a mut Arrsac<R>,
+}
+
+impl<R> Arrsac<R>
+where
+    R: RngCore,
+{
+    /// Refines the best model with [`RefiningEstimator::refine`] given its inliers, whose inliers
+    /// are then found again for the refined model.
+    ///
+    /// The refined model is only returned if it has at least as many inliers as the best model, so
+    /// a failed refinement never makes the result worse. Finding the inliers of the refined model
+    /// takes another pass over the data.
+    pub fn refined(&mut self) -> Refined<'_, R> {
+        Refined { arrsac: self }
+    }
+}
+
+impl<E, R, Data> Consensus<E, Data> for Refined<'_, R>
+where
+    E: RefiningEstimator<Data>,
+    R: RngCore,
+{
+    type Inliers = Vec<usize>;
+
+    fn model<I>(&mut self, estimator: &E, data: I) -> Option<E::Model>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        self.model_inliers(estimator, data).map(|(model, _)| model)
+    }
+
+    fn model_inliers<I>(&mut self, estimator: &E, data: I) -> Option<(E::Model, Self::Inliers)>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        let (model, inliers) = self.arrsac.model_inliers(estimator, data.clone())?;
+        // The inliers are sorted, so they can be picked out in one pass over the data.
+        let inlier_data = data
+            .clone()
+            .enumerate()
+            .filter(|(ix, _)| inliers.binary_search(ix).is_ok())
+            .map(|(_, data)| data);
+        let Some(refined) = estimator.refine(&model, inlier_data) else {
+            return Some((model, inliers));
+        };
+        let refined_inliers: Vec<usize> = data
+            .take(self.arrsac.config.inlier_window)
+            .enumerate()
+            .filter(|(_, data)| self.arrsac.is_reported_inlier(refined.residual(data)))
+            .map(|(ix, _)| ix)
+            .collect();
+        if refined_inliers.len() >= inliers.len() {
+            Some((refined, refined_inliers))
+        } else {
+            Some((model, inliers))
+        }
+    }
+}
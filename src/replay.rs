@@ -0,0 +1,59 @@
+use rand_core::RngCore;
+
+/// An RNG that yields a fixed sequence of `u32` values in order, which replays the random numbers
+/// of a recorded run.
+///
+/// [`Arrsac`](crate::Arrsac) only ever draws `u32`s from its RNG, so recording the values that an
+/// RNG returns from [`RngCore::next_u32`] during a run, such as with a wrapper around it, and
+/// replaying them with `Arrsac::new(inlier_threshold, SliceRng::new(&values))` on the same data and
+/// configuration reproduces the run exactly. This doesn't depend on the RNG that was recorded, so a
+/// run can be reproduced from the values alone. If a later version of this crate consumes the RNG
+/// differently, the replay diverges or runs out of values, which itself shows where it changed.
+///
+/// Panics once all of the values were drawn.
+#[derive(Debug, Clone)]
+pub struct SliceRng<'a> {
+    values: &'a [u32],
+    position: usize,
+}
+
+impl<'a> SliceRng<'a> {
+    /// Creates an RNG that yields `values` in order.
+    pub fn new(values: &'a [u32]) -> Self {
+        Self {
+            values,
+            position: 0,
+        }
+    }
+
+    /// How many of the values haven't been drawn yet, which is `0` after a complete replay.
+    pub fn remaining(&self) -> usize {
+        self.values.len() - self.position
+    }
+}
+
+impl RngCore for SliceRng<'_> {
+    fn next_u32(&mut self) -> u32 {
+        let Some(&value) = self.values.get(self.position) else {
+            panic!(
+                "SliceRng ran out after {} values, so the replayed run consumed more random numbers than the recorded one",
+                self.values.len()
+            );
+        };
+        self.position += 1;
+        value
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_u32(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
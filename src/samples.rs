@@ -0,0 +1,139 @@
+use alloc::{collections::VecDeque, vec::Vec};
+
+/// The number of sample indices that are stored without allocating.
+///
+/// This covers the minimal samples of all common estimators, such as lines, planes, homographies
+/// and the eight point algorithm.
+const INLINE_SAMPLES: usize = 8;
+
+/// The sample indices drawn for one hypothesis.
+///
+/// Samples that fit in [`INLINE_SAMPLES`] are stored inline, which avoids a heap allocation
+/// entirely for small minimal samples. Larger samples fall back to a [`Vec`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Samples {
+    inline: [u32; INLINE_SAMPLES],
+    len: usize,
+    heap: Vec<u32>,
+}
+
+impl Samples {
+    /// Removes all samples, but keeps any allocation for future use.
+    pub(crate) fn clear(&mut self) {
+        self.len = 0;
+        self.heap.clear();
+    }
+
+    /// Adds a sample, moving all of them to the heap once they no longer fit inline.
+    pub(crate) fn push(&mut self, sample: u32) {
+        if self.len < INLINE_SAMPLES {
+            self.inline[self.len] = sample;
+        } else {
+            if self.len == INLINE_SAMPLES {
+                self.heap.extend_from_slice(&self.inline);
+            }
+            self.heap.push(sample);
+        }
+        self.len += 1;
+    }
+
+    /// All of the samples in the order they were added.
+    pub(crate) fn as_slice(&self) -> &[u32] {
+        if self.len <= INLINE_SAMPLES {
+            &self.inline[..self.len]
+        } else {
+            &self.heap
+        }
+    }
+
+    /// Checks if `sample` was already added.
+    pub(crate) fn contains(&self, sample: &u32) -> bool {
+        self.as_slice().contains(sample)
+    }
+
+    /// A hash of the set of datapoints that were sampled, which are indices into `subset` if it is
+    /// provided. It doesn't depend on the order of the samples.
+    pub(crate) fn signature(&self, subset: Option<&[usize]>) -> u64 {
+        self.as_slice()
+            .iter()
+            .map(|&ix| subset.map_or(ix as usize, |subset| subset[ix as usize]))
+            .fold(0, |signature, ix| signature.wrapping_add(mix(ix as u64)))
+    }
+}
+
+/// The SplitMix64 finalizer, which spreads the bits of the index over the whole hash.
+pub(crate) fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// The number of rejected samples remembered by [`RejectedSamples`].
+const REJECTED_SAMPLES: usize = 1024;
+
+/// The signatures of the most recently rejected samples, see
+/// [`crate::ArrsacBuilder::dedupe_samples_global`].
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct RejectedSamples {
+    /// From least to most recently used.
+    signatures: VecDeque<u64>,
+}
+
+impl RejectedSamples {
+    pub(crate) fn clear(&mut self) {
+        self.signatures.clear();
+    }
+
+    /// Checks if the sample with `signature` was rejected, which marks it as recently used.
+    pub(crate) fn contains(&mut self, signature: u64) -> bool {
+        match self.signatures.iter().position(|&s| s == signature) {
+            Some(position) => {
+                self.signatures.remove(position);
+                self.signatures.push_back(signature);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remembers that the sample with `signature` was rejected, forgetting the least recently used one if full.
+    pub(crate) fn insert(&mut self, signature: u64) {
+        if self.signatures.len() == REJECTED_SAMPLES {
+            self.signatures.pop_front();
+        }
+        self.signatures.push_back(signature);
+    }
+}
+
+/// A fixed sequence of sample indices that replaces the RNG, see [`crate::Arrsac::with_fixed_samples`].
+#[cfg(feature = "test-util")]
+#[derive(Debug, Clone)]
+pub(crate) struct FixedSamples {
+    samples: Vec<u32>,
+    next: usize,
+}
+
+#[cfg(feature = "test-util")]
+impl FixedSamples {
+    pub(crate) fn new(samples: Vec<u32>) -> Self {
+        assert!(
+            !samples.is_empty(),
+            "there must be at least one fixed sample"
+        );
+        Self { samples, next: 0 }
+    }
+
+    /// The next sample index, starting over at the beginning once all were used.
+    pub(crate) fn next_sample(&mut self) -> u32 {
+        let sample = self.samples[self.next];
+        self.next = (self.next + 1) % self.samples.len();
+        sample
+    }
+
+    /// Starts over at the beginning of the sequence.
+    pub(crate) fn rewind(&mut self) {
+        self.next = 0;
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{mapped::MapResidual, Arrsac};
+use alloc::vec::Vec;
+use rand_core::RngCore;
+use sample_consensus::{Consensus, Estimator, Model};
+
+/// An [`Arrsac`] that divides the residual of every datapoint by its scale, created by
+/// [`Arrsac::residual_scale`].
+pub struct ResidualScale<'a, R, F> {
+    arrsac: &'a mut Arrsac<R>,
+    scale: Scale<F>,
+}
+
+impl<R> Arrsac<R>
+where
+    R: RngCore,
+{
+    /// Divides the residual of every datapoint by `scale` of the datapoint before comparing it to
+    /// the inlier threshold, such as the standard deviation of its measurement.
+    ///
+    /// This makes the inlier threshold a threshold on the normalized residual, which is natural for
+    /// data with known per-datapoint variances. It applies wherever residuals are used, including
+    /// the SPRT, the weights and the [`Report`](crate::Report). A scale of `1.0` for every
+    /// datapoint is the same as not scaling.
+    pub fn residual_scale<F>(&mut self, scale: F) -> ResidualScale<'_, R, F> {
+        ResidualScale {
+            arrsac: self,
+            scale: Scale(scale),
+        }
+    }
+}
+
+impl<E, R, F, Data> Consensus<E, Data> for ResidualScale<'_, R, F>
+where
+    E: Estimator<Data>,
+    R: RngCore,
+    F: Fn(&Data) -> f64,
+{
+    type Inliers = Vec<usize>;
+
+    fn model<I>(&mut self, estimator: &E, data: I) -> Option<E::Model>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        self.model_inliers(estimator, data).map(|(model, _)| model)
+    }
+
+    fn model_inliers<I>(&mut self, estimator: &E, data: I) -> Option<(E::Model, Self::Inliers)>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        self.arrsac
+            .mapped_model_inliers(estimator, &self.scale, data)
+    }
+}
+
+/// Divides the residual of a model by the scale of the datapoint.
+struct Scale<F>(F);
+
+impl<M, F, Data> MapResidual<Data, M> for Scale<F>
+where
+    M: Model<Data>,
+    F: Fn(&Data) -> f64,
+{
+    fn map_residual(&self, model: &M, data: &Data) -> f64 {
+        model.residual(data) / (self.0)(data)
+    }
+}
@@ -0,0 +1,100 @@
+use crate::Arrsac;
+use alloc::vec::Vec;
+use rand_core::RngCore;
+use sample_consensus::{Consensus, Estimator, Model};
+
+/// The line `y = slope * x + intercept`, whose residual is the vertical distance from it.
+struct Line {
+    slope: f64,
+    intercept: f64,
+}
+
+impl Model<[f64; 2]> for Line {
+    fn residual(&self, &[x, y]: &[f64; 2]) -> f64 {
+        (y - (self.slope * x + self.intercept)).abs()
+    }
+}
+
+/// Estimates the line through two points.
+struct LineEstimator;
+
+impl Estimator<[f64; 2]> for LineEstimator {
+    type Model = Line;
+    type ModelIter = Option<Line>;
+    const MIN_SAMPLES: usize = 2;
+
+    fn estimate<I>(&self, mut data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = [f64; 2]> + Clone,
+    {
+        let ([x0, y0], [x1, y1]) = (data.next()?, data.next()?);
+        if x0 == x1 {
+            return None;
+        }
+        let slope = (y1 - y0) / (x1 - x0);
+        Some(Line {
+            slope,
+            intercept: y0 - slope * x0,
+        })
+    }
+}
+
+/// SplitMix64, which is enough to draw the samples without depending on an RNG crate.
+struct SplitMix64(u64);
+
+impl RngCore for SplitMix64 {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl Arrsac<()> {
+    /// Runs ARRSAC with a built-in line estimator on a fixed synthetic dataset of inliers and
+    /// outliers, and checks that it recovers the line, such as for a smoke test at startup.
+    ///
+    /// This catches a build that broke ARRSAC as a whole, such as a feature that changes how
+    /// residuals are compared. It only uses the default configuration, so it says nothing about
+    /// the configuration, RNG or estimator of an application.
+    pub fn self_test() -> bool {
+        // A quarter of the points are outliers spread far from the line `y = 2x + 1`, and the
+        // inliers are offset from it by at most `0.1`.
+        let mut outliers = SplitMix64(0);
+        let points: Vec<[f64; 2]> = (0..1000)
+            .map(|ix| {
+                let x = ix as f64 / 50.0;
+                let y = if ix % 4 == 3 {
+                    (outliers.next_u32() as f64 / u32::MAX as f64 - 0.5) * 100.0
+                } else {
+                    2.0 * x + 1.0 + ((ix * 7) % 11) as f64 / 50.0 - 0.1
+                };
+                [x, y]
+            })
+            .collect();
+        let mut arrsac = Arrsac::new(0.5, SplitMix64(1));
+        let Some((line, inliers)) = arrsac.model_inliers(&LineEstimator, points.iter().copied())
+        else {
+            return false;
+        };
+        // The model is estimated from only two inliers, so it is slightly off from the line.
+        (line.slope - 2.0).abs() < 0.05
+            && (line.intercept - 1.0).abs() < 0.5
+            && inliers.len() >= 700
+    }
+}
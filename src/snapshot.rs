@@ -0,0 +1,169 @@
+use crate::{masked, Arrsac, Hypothesis, RejectedSamples, Report, SearchHooks, SearchState};
+use alloc::vec::Vec;
+use rand_core::RngCore;
+use sample_consensus::Estimator;
+
+/// The state of a run between two blocks, which can be serialized to resume the run later.
+///
+/// This is created by [`Arrsac::snapshot`] and consumed by [`Arrsac::resume`]. It contains the
+/// surviving hypotheses, so it only implements `Serialize` and `Deserialize` if the model `M` does.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct ArrsacSnapshot<M> {
+    state: SearchState<M>,
+    /// The datapoints that are searched on, see [`Arrsac::search_mask`].
+    keep: Option<Vec<bool>>,
+    full_samples: usize,
+    rejected_samples: RejectedSamples,
+    /// This also has the likelihood ratio threshold of the run.
+    report: Report,
+}
+
+impl<M> ArrsacSnapshot<M> {
+    /// The index of the next block to evaluate.
+    pub fn block(&self) -> usize {
+        self.state.block
+    }
+
+    /// The number of datapoints the hypotheses have been scored on.
+    pub fn datapoints(&self) -> usize {
+        self.state.num_scored
+    }
+
+    /// The number of inliers of every surviving hypothesis, from best to worst.
+    pub fn inliers(&self) -> Vec<usize> {
+        self.state.hypotheses.iter().map(|h| h.inliers).collect()
+    }
+
+    /// The inlier ratio of the best surviving hypothesis.
+    pub fn epsilon(&self) -> f32 {
+        self.state
+            .hypotheses
+            .first()
+            .map(|h| h.inliers as f32 / self.state.num_scored as f32)
+            .unwrap_or_default()
+    }
+
+    /// The estimated inlier ratio of a bad model.
+    pub fn delta(&self) -> f32 {
+        self.state.delta
+    }
+
+    /// There are no more blocks to evaluate, so resuming only selects the best model.
+    pub fn is_done(&self) -> bool {
+        self.state.done
+    }
+}
+
+impl<R> Arrsac<R>
+where
+    R: RngCore,
+{
+    /// Starts a run on `data` and stops it after evaluating at most `blocks` blocks past the
+    /// initialization blocks, returning its state so it can be continued with [`Arrsac::resume`].
+    ///
+    /// This is `None` if the initialization failed, in which case the run would not find a model.
+    /// Resuming must use the same `data`, estimator and configuration for the run to be the same as
+    /// if it had not been interrupted, except for the RNG state.
+    ///
+    /// Snapshots are only taken of a plain run, so they don't apply
+    /// [`ArrsacBuilder::calibrate_threshold`](crate::ArrsacBuilder::calibrate_threshold), which takes
+    /// a run of its own, nor the hooks of adapters such as [`Arrsac::dedupe_data`] or
+    /// [`Arrsac::locality_bias`].
+    #[must_use]
+    pub fn snapshot<E, Data, I>(
+        &mut self,
+        estimator: &E,
+        data: I,
+        blocks: usize,
+    ) -> Option<ArrsacSnapshot<E::Model>>
+    where
+        E: Estimator<Data>,
+        I: Iterator<Item = Data> + Clone,
+    {
+        self.reset_state();
+        let keep = self.search_mask(data.clone(), None);
+        let state = match &keep {
+            Some(keep) => self.start_snapshot(estimator, masked(data, keep), blocks),
+            None => self.start_snapshot(estimator, data, blocks),
+        }?;
+        Some(ArrsacSnapshot {
+            state,
+            keep,
+            full_samples: self.full_samples,
+            rejected_samples: self.rejected_samples.clone(),
+            report: self.report.clone(),
+        })
+    }
+
+    /// Continues a run from a `snapshot` taken on the same `data` and returns the best model and
+    /// its inliers, just like [`Consensus::model_inliers`](sample_consensus::Consensus::model_inliers).
+    #[must_use]
+    pub fn resume<E, Data, I>(
+        &mut self,
+        snapshot: ArrsacSnapshot<E::Model>,
+        estimator: &E,
+        data: I,
+    ) -> Option<(E::Model, Vec<usize>)>
+    where
+        E: Estimator<Data>,
+        I: Iterator<Item = Data> + Clone,
+    {
+        self.reset_state();
+        let ArrsacSnapshot {
+            state,
+            keep,
+            full_samples,
+            rejected_samples,
+            report,
+        } = snapshot;
+        self.full_samples = full_samples;
+        self.rejected_samples = rejected_samples;
+        self.report = report;
+        let hypotheses = match &keep {
+            Some(keep) => {
+                let (mut hypotheses, block_starts) =
+                    self.finish_snapshot(estimator, masked(data.clone(), keep), state);
+                self.rescore_masked(data.clone(), &mut hypotheses, &block_starts, keep);
+                hypotheses
+            }
+            None => self.finish_snapshot(estimator, data.clone(), state).0,
+        };
+        self.select(hypotheses, data)
+    }
+
+    /// Starts a search on `data` and evaluates at most `blocks` blocks of it.
+    fn start_snapshot<E, Data>(
+        &mut self,
+        estimator: &E,
+        data: impl Iterator<Item = Data> + Clone,
+        blocks: usize,
+    ) -> Option<SearchState<E::Model>>
+    where
+        E: Estimator<Data>,
+    {
+        let mut state = self.start_search(estimator, data.clone(), SearchHooks::default())?;
+        self.advance_search(estimator, data, &mut state, blocks, SearchHooks::default());
+        Some(state)
+    }
+
+    /// Evaluates the remaining blocks of a search on `data` from `state`, like
+    /// [`Arrsac::finish_search`].
+    fn finish_snapshot<E, Data>(
+        &mut self,
+        estimator: &E,
+        data: impl Iterator<Item = Data> + Clone,
+        mut state: SearchState<E::Model>,
+    ) -> (Vec<Hypothesis<E::Model>>, Vec<usize>)
+    where
+        E: Estimator<Data>,
+    {
+        self.advance_search(
+            estimator,
+            data.clone(),
+            &mut state,
+            usize::MAX,
+            SearchHooks::default(),
+        );
+        self.finish_search(data, state)
+    }
+}
@@ -0,0 +1,71 @@
+use crate::{Arrsac, SearchHooks};
+use alloc::vec::Vec;
+use rand_core::RngCore;
+use sample_consensus::{Consensus, Estimator};
+
+/// An [`Arrsac`] that draws the minimal samples of the initial hypotheses from distinct strata,
+/// created by [`Arrsac::stratified_init`].
+pub struct StratifiedInit<'a, R, F> {
+    arrsac: &'a mut Arrsac<R>,
+    stratum: F,
+    num_strata: usize,
+}
+
+impl<R> Arrsac<R>
+where
+    R: RngCore,
+{
+    /// Draws the minimal samples of the initial hypotheses with at most one datapoint from each
+    /// stratum, where `stratum` assigns every datapoint to one of `num_strata` strata, such as the
+    /// cells of a grid or the source images of multi-view data.
+    ///
+    /// This gives more geometrically diverse initial hypotheses than uniform sampling. A sample
+    /// first draws distinct strata uniformly and then a datapoint uniformly from each of them.
+    /// Datapoints whose stratum is not less than `num_strata` are never drawn this way. The sample
+    /// is drawn uniformly from all of the data instead if fewer strata have datapoints in them than
+    /// there are datapoints in a sample. The hypotheses of later blocks are sampled as usual.
+    ///
+    /// Assigning the datapoints to strata takes a pass over the data for every initial sample.
+    pub fn stratified_init<F>(
+        &mut self,
+        stratum: F,
+        num_strata: usize,
+    ) -> StratifiedInit<'_, R, F> {
+        StratifiedInit {
+            arrsac: self,
+            stratum,
+            num_strata,
+        }
+    }
+}
+
+impl<E, R, F, Data> Consensus<E, Data> for StratifiedInit<'_, R, F>
+where
+    E: Estimator<Data>,
+    R: RngCore,
+    F: Fn(&Data) -> usize,
+{
+    type Inliers = Vec<usize>;
+
+    fn model<I>(&mut self, estimator: &E, data: I) -> Option<E::Model>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        self.model_inliers(estimator, data).map(|(model, _)| model)
+    }
+
+    fn model_inliers<I>(&mut self, estimator: &E, data: I) -> Option<(E::Model, Self::Inliers)>
+    where
+        I: Iterator<Item = Data> + Clone,
+    {
+        let hypotheses = self.arrsac.survivors(
+            estimator,
+            data.clone(),
+            SearchHooks {
+                strata: Some((&self.stratum, self.num_strata)),
+                ..SearchHooks::default()
+            },
+        );
+        self.arrsac.select(hypotheses, data)
+    }
+}
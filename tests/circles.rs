@@ -0,0 +1,40 @@
+mod common;
+
+use arrsac::Arrsac;
+use common::circle::{
+    circle_points, circumcircle, distance, outliers, random_circle, CircleEstimator,
+};
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use sample_consensus::Consensus;
+
+#[test]
+fn circumcircle_through_points() {
+    let circle = circumcircle([0.0, 10.0], [10.0, 0.0], [0.0, -10.0]).unwrap();
+    assert!(distance(&circle.center, &[0.0, 0.0]) < 1e-9);
+    assert!((circle.radius - 10.0).abs() < 1e-9);
+    // Collinear and repeated points have no circle through them.
+    assert!(circumcircle([0.0, 0.0], [1.0, 1.0], [2.0, 2.0]).is_none());
+    assert!(circumcircle([1.0, 2.0], [1.0, 2.0], [3.0, 4.0]).is_none());
+}
+
+#[test]
+fn circles() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(2.0, rng.clone());
+    for _ in 0..100 {
+        let circle = random_circle(&mut rng);
+        let mut points = circle_points(&mut rng, &circle, 1.0, 300);
+        points.extend(outliers(&mut rng, 200));
+        points.shuffle(&mut rng);
+
+        let (model, inliers) = arrsac
+            .model_inliers(&CircleEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        // The points are up to 1 away from the circle and the threshold is 2.
+        assert!(distance(&model.center, &circle.center) < 2.0);
+        assert!((model.radius - circle.radius).abs() < 2.0);
+        // Nearly every point on the circle is an inlier.
+        assert!(inliers.len() >= 290);
+    }
+}
@@ -0,0 +1,94 @@
+//! A circle estimator, which is shared by the tests and `examples/circle.rs`.
+
+use rand::distributions::{Distribution, Uniform};
+use rand::Rng;
+use sample_consensus::{Estimator, Model};
+
+/// A point in the plane.
+pub type Point = [f64; 2];
+
+#[derive(Debug, Clone, Copy)]
+pub struct Circle {
+    pub center: Point,
+    pub radius: f64,
+}
+
+impl Model<Point> for Circle {
+    /// The distance of the point from the circle.
+    fn residual(&self, point: &Point) -> f64 {
+        (distance(&self.center, point) - self.radius).abs()
+    }
+}
+
+/// Estimates the circle through three points.
+pub struct CircleEstimator;
+
+impl Estimator<Point> for CircleEstimator {
+    type Model = Circle;
+    type ModelIter = Option<Circle>;
+    const MIN_SAMPLES: usize = 3;
+
+    fn estimate<I>(&self, mut data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = Point> + Clone,
+    {
+        let (a, b, c) = (data.next()?, data.next()?, data.next()?);
+        circumcircle(a, b, c)
+    }
+}
+
+/// The circle through `a`, `b` and `c`, which is `None` if they are (nearly) collinear.
+pub fn circumcircle(a: Point, b: Point, c: Point) -> Option<Circle> {
+    // Work relative to `a` to keep the precision for circles far from the origin.
+    let (bx, by) = (b[0] - a[0], b[1] - a[1]);
+    let (cx, cy) = (c[0] - a[0], c[1] - a[1]);
+    let b_sq = bx * bx + by * by;
+    let c_sq = cx * cx + cy * cy;
+    let d = 2.0 * (bx * cy - by * cx);
+    // The points are collinear if the sides span no area compared to their lengths, which
+    // includes repeated points.
+    if d * d <= 1e-18 * b_sq * c_sq {
+        return None;
+    }
+    let ux = (cy * b_sq - by * c_sq) / d;
+    let uy = (bx * c_sq - cx * b_sq) / d;
+    Some(Circle {
+        center: [a[0] + ux, a[1] + uy],
+        radius: (ux * ux + uy * uy).sqrt(),
+    })
+}
+
+pub fn distance(a: &Point, b: &Point) -> f64 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+}
+
+/// Generates a random circle that lies within `[-100, 100]` on both axes.
+pub fn random_circle(rng: &mut impl Rng) -> Circle {
+    let radius = rng.gen_range(10.0..50.0);
+    let center = [rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0)];
+    Circle { center, radius }
+}
+
+/// Generates `num` points around `circle` that are no more than `noise` away from it.
+pub fn circle_points(rng: &mut impl Rng, circle: &Circle, noise: f64, num: usize) -> Vec<Point> {
+    let angles = Uniform::new(0.0, 2.0 * std::f64::consts::PI);
+    let offsets = Uniform::new_inclusive(-noise, noise);
+    (0..num)
+        .map(|_| {
+            let angle: f64 = angles.sample(rng);
+            let radius = circle.radius + offsets.sample(rng);
+            [
+                circle.center[0] + radius * angle.cos(),
+                circle.center[1] + radius * angle.sin(),
+            ]
+        })
+        .collect()
+}
+
+/// Generates `num` outliers uniformly within `[-100, 100]` on both axes.
+pub fn outliers(rng: &mut impl Rng, num: usize) -> Vec<Point> {
+    let coordinates = Uniform::new(-100.0, 100.0);
+    (0..num)
+        .map(|_| [coordinates.sample(rng), coordinates.sample(rng)])
+        .collect()
+}
@@ -0,0 +1,119 @@
+#![allow(dead_code)]
+
+pub mod circle;
+
+use rand::distributions::{Distribution, Uniform};
+use rand::Rng;
+use sample_consensus::{Estimator, Model};
+
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vector2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl Vector2<f64> {
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+    pub fn dot(&self, other: &Self) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+    pub fn norm(&self) -> f64 {
+        (self.x * self.x + self.y * self.y).sqrt()
+    }
+    pub fn normalize(&self) -> Self {
+        let v_norm = self.norm();
+        Self {
+            x: self.x / v_norm,
+            y: self.y / v_norm,
+        }
+    }
+}
+
+impl core::ops::Mul<Vector2<f64>> for f64 {
+    type Output = Vector2<f64>;
+    fn mul(self, rhs: Vector2<f64>) -> Self::Output {
+        Vector2 {
+            x: self * rhs.x,
+            y: self * rhs.y,
+        }
+    }
+}
+
+impl core::ops::Add for Vector2<f64> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+        }
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Line {
+    pub norm: Vector2<f64>,
+    pub c: f64,
+}
+
+impl Model<Vector2<f64>> for Line {
+    fn residual(&self, point: &Vector2<f64>) -> f64 {
+        (self.norm.dot(point) + self.c).abs()
+    }
+}
+
+pub struct LineEstimator;
+
+impl Estimator<Vector2<f64>> for LineEstimator {
+    type Model = Line;
+    type ModelIter = std::iter::Once<Line>;
+    const MIN_SAMPLES: usize = 2;
+
+    fn estimate<I>(&self, mut data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = Vector2<f64>> + Clone,
+    {
+        let a = data.next().unwrap();
+        let b = data.next().unwrap();
+        let norm = Vector2::new(a.y - b.y, b.x - a.x).normalize();
+        let c = -norm.dot(&b);
+        std::iter::once(Line { norm, c })
+    }
+}
+
+/// Generates the normal and offset of a random line.
+pub fn random_line(rng: &mut impl Rng) -> (Vector2<f64>, f64) {
+    // Generate <a, b> and normalize.
+    let norm = Vector2::new(rng.gen_range(-10.0..10.0), rng.gen_range(-10.0..10.0)).normalize();
+    // Generate random c.
+    let c = rng.gen_range(-10.0..10.0);
+    (norm, c)
+}
+
+/// Generates `num` points no more than `5.0` away from the line described by `norm` and `c`.
+pub fn line_points(
+    rng: &mut impl Rng,
+    norm: Vector2<f64>,
+    c: f64,
+    num: usize,
+) -> Vec<Vector2<f64>> {
+    // Get parallel ray.
+    let ray = Vector2::new(norm.y, -norm.x);
+    // The points should be no more than 5.0 away from the line and be evenly distributed away from the line.
+    let residuals = Uniform::new(-5.0, 5.0);
+    // The points must be generated along the line, but the distance should be bounded to make it more difficult.
+    let distances = Uniform::new(-50.0, 50.0);
+    // Generate the points.
+    (0..num)
+        .map(|_| {
+            let residual: f64 = residuals.sample(rng);
+            let distance: f64 = distances.sample(rng);
+            let along = distance * ray;
+            let against = (residual - c) * norm;
+            along + against
+        })
+        .collect()
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "count-rng")]
+
+mod common;
+
+use arrsac::Arrsac;
+use common::{line_points, random_line, LineEstimator};
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256PlusPlus;
+use sample_consensus::Consensus;
+
+#[test]
+fn rng_draws_are_deterministic() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 1000);
+
+    let mut draws = vec![];
+    for _ in 0..2 {
+        let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(1));
+        assert_eq!(arrsac.rng_draws(), 0);
+        arrsac
+            .model(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        let first = arrsac.rng_draws();
+        // Every minimal sample takes at least one draw per datapoint.
+        assert!(first >= 2 * arrsac.report().hypotheses_generated as u64);
+        arrsac
+            .model(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(arrsac.rng_draws() > first);
+        draws.push((first, arrsac.rng_draws()));
+
+        // Reseeding starts counting over.
+        arrsac.reseed([0; 32]);
+        assert_eq!(arrsac.rng_draws(), 0);
+    }
+    assert_eq!(draws[0], draws[1]);
+}
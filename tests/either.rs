@@ -0,0 +1,105 @@
+mod common;
+
+use arrsac::{Arrsac, ModelEither};
+use common::{random_line, LineEstimator, Vector2};
+use rand::{distributions::Uniform, Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use sample_consensus::{Estimator, Model};
+
+#[derive(Debug)]
+struct Circle {
+    center: Vector2<f64>,
+    radius: f64,
+}
+
+impl Model<Vector2<f64>> for Circle {
+    fn residual(&self, point: &Vector2<f64>) -> f64 {
+        let offset = Vector2::new(point.x - self.center.x, point.y - self.center.y);
+        (offset.norm() - self.radius).abs()
+    }
+}
+
+/// Estimates the circle through three points.
+struct CircleEstimator;
+
+impl Estimator<Vector2<f64>> for CircleEstimator {
+    type Model = Circle;
+    type ModelIter = Option<Circle>;
+    const MIN_SAMPLES: usize = 3;
+
+    fn estimate<I>(&self, mut data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = Vector2<f64>> + Clone,
+    {
+        let (a, b, c) = (data.next()?, data.next()?, data.next()?);
+        let d = 2.0 * (a.x * (b.y - c.y) + b.x * (c.y - a.y) + c.x * (a.y - b.y));
+        if d.abs() < 1e-9 {
+            return None;
+        }
+        let (a2, b2, c2) = (a.dot(&a), b.dot(&b), c.dot(&c));
+        let center = Vector2::new(
+            (a2 * (b.y - c.y) + b2 * (c.y - a.y) + c2 * (a.y - b.y)) / d,
+            (a2 * (c.x - b.x) + b2 * (a.x - c.x) + c2 * (b.x - a.x)) / d,
+        );
+        let radius = Circle {
+            center,
+            radius: 0.0,
+        }
+        .residual(&a);
+        Some(Circle { center, radius })
+    }
+}
+
+fn circle_points(
+    rng: &mut impl Rng,
+    center: Vector2<f64>,
+    radius: f64,
+    num: usize,
+) -> Vec<Vector2<f64>> {
+    let angle = Uniform::new(0.0, 2.0 * std::f64::consts::PI);
+    let noise = Uniform::new(-0.5, 0.5);
+    (0..num)
+        .map(|_| {
+            let angle = rng.sample(angle);
+            let radius = radius + rng.sample(noise);
+            Vector2::new(
+                center.x + radius * angle.cos(),
+                center.y + radius * angle.sin(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn model_inliers_multi() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(1.0, rng.clone());
+    for _ in 0..20 {
+        let (norm, c) = random_line(&mut rng);
+        // Points exactly on the line, which a circle can fit at most as well.
+        let direction = Vector2::new(-norm.y, norm.x);
+        let points: Vec<Vector2<f64>> = (0..500)
+            .map(|_| (-c) * norm + rng.gen_range(-100.0..100.0) * direction)
+            .collect();
+        let (model, inliers) = arrsac
+            .model_inliers_multi(&LineEstimator, &CircleEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        let ModelEither::Left(line) = &model else {
+            panic!("expected a line, got {model:?}");
+        };
+        assert!(line.norm.dot(&norm).abs() > 0.99);
+        assert_eq!(inliers.len(), 500);
+
+        let center = Vector2::new(rng.gen_range(-100.0..100.0), rng.gen_range(-100.0..100.0));
+        let radius = rng.gen_range(20.0..100.0);
+        let points = circle_points(&mut rng, center, radius, 500);
+        let (model, inliers) = arrsac
+            .model_inliers_multi(&LineEstimator, &CircleEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        let ModelEither::Right(circle) = &model else {
+            panic!("expected a circle, got {model:?}");
+        };
+        assert!((circle.radius - radius).abs() < 2.0);
+        assert!(inliers.len() > 400);
+    }
+}
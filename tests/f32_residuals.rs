@@ -0,0 +1,40 @@
+#![cfg(feature = "f32-residuals")]
+
+mod common;
+
+use arrsac::Arrsac;
+use common::{line_points, random_line, LineEstimator};
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256PlusPlus;
+use sample_consensus::{Consensus, Model};
+
+/// A model whose residual is the datapoint itself.
+struct Identity;
+
+impl Model<f64> for Identity {
+    fn residual(&self, data: &f64) -> f64 {
+        *data
+    }
+}
+
+#[test]
+fn f32_residuals_round_before_comparing() {
+    let arrsac = Arrsac::new(1.0, Xoshiro256PlusPlus::seed_from_u64(0));
+    // The second residual rounds to the threshold, and the third is too large for `f32`.
+    let stats = arrsac.validate(&Identity, [0.5, 1.0 - 1e-12, 1e300].into_iter());
+    assert_eq!(stats.inliers, 1);
+}
+
+#[test]
+fn f32_residuals_lines() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    for _ in 0..100 {
+        let (norm, c) = random_line(&mut rng);
+        let points = line_points(&mut rng, norm, c, 500);
+        let model = arrsac
+            .model(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+    }
+}
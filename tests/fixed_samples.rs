@@ -0,0 +1,39 @@
+#![cfg(feature = "test-util")]
+
+mod common;
+
+use arrsac::Arrsac;
+use common::{LineEstimator, Vector2};
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256PlusPlus;
+use sample_consensus::Consensus;
+
+/// Points on the x axis followed by points on a parallel line far away.
+fn two_lines() -> Vec<Vector2<f64>> {
+    let near = (0..300).map(|x| Vector2::new(x as f64, 0.0));
+    let far = (0..700).map(|x| Vector2::new(x as f64, 100.0));
+    near.chain(far).collect()
+}
+
+#[test]
+fn fixed_samples_choose_the_model() {
+    let points = two_lines();
+    let rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    // Only ever fit the line through the first two points, even though it has less support.
+    let mut arrsac = Arrsac::with_fixed_samples(1.0, rng, [0, 1]).full_sampling_only(true);
+    for _ in 0..2 {
+        let (model, inliers) = arrsac
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.y.abs() > 0.999);
+        assert_eq!(inliers, (0..300).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+#[should_panic]
+fn fixed_samples_out_of_range() {
+    let points = two_lines();
+    let rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    Arrsac::with_fixed_samples(1.0, rng, [0, 1000]).model(&LineEstimator, points.iter().copied());
+}
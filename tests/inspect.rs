@@ -0,0 +1,391 @@
+mod common;
+
+use arrsac::{Arrsac, Event, TerminationContext};
+use common::{line_points, random_line, Line, LineEstimator, Vector2};
+use core::ops::ControlFlow;
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256PlusPlus;
+use sample_consensus::{Consensus, Estimator, Model};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn inspect_observes_run() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 1000);
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = events.clone();
+    let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(1)).inspect(move |event| {
+        recorded.lock().unwrap().push(event);
+        ControlFlow::Continue(())
+    });
+    let inspected = arrsac.model_inliers(&LineEstimator, points.iter().copied());
+    assert!(!arrsac.report().stopped_early);
+
+    // Observing the run does not change it.
+    let reference = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(1))
+        .model_inliers(&LineEstimator, points.iter().copied());
+    assert_eq!(
+        inspected.map(|(_, inliers)| inliers),
+        reference.map(|(_, inliers)| inliers)
+    );
+
+    let events = events.lock().unwrap();
+    let generated = events
+        .iter()
+        .filter(|event| matches!(event, Event::HypothesisGenerated { .. }))
+        .count();
+    let tested = events
+        .iter()
+        .filter(|event| {
+            matches!(
+                event,
+                Event::HypothesisAccepted { .. } | Event::HypothesisRejected { .. }
+            )
+        })
+        .count();
+    // Every hypothesis after the initial ones goes through the SPRT.
+    assert_eq!(generated, 256 + tested);
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, Event::BlockCompleted { .. })));
+}
+
+#[test]
+fn inspect_break_stops_early() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 1000);
+
+    let generated = Arc::new(Mutex::new(0));
+    let counter = generated.clone();
+    let mut arrsac =
+        Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(1)).inspect(move |event| match event {
+            Event::HypothesisGenerated { generation: 63 } => ControlFlow::Break(()),
+            Event::HypothesisGenerated { .. } => {
+                *counter.lock().unwrap() += 1;
+                ControlFlow::Continue(())
+            }
+            _ => ControlFlow::Continue(()),
+        });
+    // The best model out of the first few is still returned.
+    let model = arrsac
+        .model(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    assert!(model.norm.dot(&norm).abs() > 0.99);
+    assert!(arrsac.report().stopped_early);
+    assert_eq!(*generated.lock().unwrap(), 63);
+
+    // The next run starts over.
+    arrsac
+        .model(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    assert!(arrsac.report().stopped_early);
+    assert_eq!(*generated.lock().unwrap(), 126);
+}
+
+#[test]
+fn estimated_hypothesis_evaluations() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 1000);
+    for (max_candidate_hypotheses, calibrate) in [(64, false), (1 << 20, false), (1 << 20, true)] {
+        let tested = Arc::new(Mutex::new(0));
+        let counter = tested.clone();
+        let mut arrsac =
+            Arrsac::new(3.0, rng.clone()).max_candidate_hypotheses(max_candidate_hypotheses);
+        if calibrate {
+            arrsac = arrsac.calibrate_threshold(3.0);
+        }
+        let mut arrsac = arrsac.inspect(move |event| {
+            if let Event::HypothesisAccepted { .. } | Event::HypothesisRejected { .. } = event {
+                *counter.lock().unwrap() += 1;
+            }
+            ControlFlow::Continue(())
+        });
+        let estimate = arrsac.estimated_hypothesis_evaluations(points.len());
+        let _ = arrsac.model(&LineEstimator, points.iter().copied());
+        let tested = *tested.lock().unwrap();
+        assert!(tested <= estimate, "{tested} > {estimate}");
+        if max_candidate_hypotheses == 1 << 20 {
+            // Every block up to the last full one keeps enough hypotheses to continue, in both
+            // searches of a calibrated run.
+            let searches = if calibrate { 2 } else { 1 };
+            assert_eq!(estimate, searches * (256 + 11 * 64));
+            assert_eq!(tested, estimate);
+        }
+    }
+}
+
+#[test]
+fn patience_stops_early() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 10000);
+
+    let blocks = |arrsac: Arrsac<Xoshiro256PlusPlus>| {
+        let completed = Arc::new(Mutex::new(0));
+        let counter = completed.clone();
+        let mut arrsac = arrsac
+            .max_candidate_hypotheses(1 << 20)
+            .inspect(move |event| {
+                if let Event::BlockCompleted { .. } = event {
+                    *counter.lock().unwrap() += 1;
+                }
+                ControlFlow::Continue(())
+            });
+        let model = arrsac
+            .model(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+        let completed = *completed.lock().unwrap();
+        completed
+    };
+    let patient = blocks(Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(1)));
+    let impatient = blocks(Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(1)).patience(2));
+    // The best hypothesis of the clean data is found early, so the search ends long before the data
+    // runs out.
+    assert!(impatient >= 2);
+    assert!(2 * impatient < patient, "{patient} <= 2 * {impatient}");
+}
+
+#[test]
+fn initial_eval_size() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 1000);
+
+    let blocks = Arc::new(Mutex::new(Vec::new()));
+    let recorded = blocks.clone();
+    let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(1))
+        .initial_eval_size(32)
+        .inspect(move |event| {
+            if let Event::BlockCompleted { datapoints, .. } = event {
+                recorded.lock().unwrap().push(datapoints);
+            }
+            ControlFlow::Continue(())
+        });
+    let model = arrsac
+        .model(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    assert!(model.norm.dot(&norm).abs() > 0.99);
+    // The blocks continue right after the initial evaluation.
+    assert_eq!(blocks.lock().unwrap()[..2], [32 + 64, 32 + 128]);
+}
+
+#[test]
+fn likelihood_ratios() {
+    let (positive, negative) = Arrsac::likelihood_ratios(0.5, 0.1);
+    assert!((positive - 0.2).abs() < 1e-6);
+    assert!((negative - 1.8).abs() < 1e-6);
+    // An epsilon of one would make the outlier ratio infinite.
+    let (positive, negative) = Arrsac::likelihood_ratios(1.0, 0.05);
+    assert!((positive - 0.05).abs() < 1e-6);
+    assert!(negative.is_finite() && negative > 1e12);
+    assert_eq!(Arrsac::required_block_size(1.0, 0.05, 1e3), 1);
+    // Zero epsilon and delta don't divide zero by zero.
+    let (positive, negative) = Arrsac::likelihood_ratios(0.0, 0.0);
+    assert_eq!((positive, negative), (1.0, 1.0));
+}
+
+#[test]
+fn required_block_size() {
+    // 1.9^10 is about 613 and 1.9^11 is about 1165.
+    assert_eq!(Arrsac::required_block_size(0.5, 0.05, 1e3), 11);
+    // (0.95 / 0.9)^n exceeds 1000 once n > ln(1000) / ln(1.0556), which is about 127.8.
+    assert_eq!(Arrsac::required_block_size(0.1, 0.05, 1e3), 128);
+    // Any datapoint exceeds a threshold below one.
+    assert_eq!(Arrsac::required_block_size(0.5, 0.05, 0.5), 0);
+    // Outlier models are never rejected unless epsilon is above delta.
+    assert_eq!(Arrsac::required_block_size(0.05, 0.05, 1e3), usize::MAX);
+    assert_eq!(Arrsac::required_block_size(0.05, 0.1, 1e3), usize::MAX);
+    assert_eq!(Arrsac::required_block_size(f32::NAN, 0.1, 1e3), usize::MAX);
+}
+
+#[test]
+fn max_blocks() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 10000);
+
+    let blocks = Arc::new(Mutex::new(Vec::new()));
+    let recorded = blocks.clone();
+    let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(1))
+        .max_candidate_hypotheses(1 << 20)
+        .max_blocks(3)
+        .inspect(move |event| {
+            if let Event::BlockCompleted { datapoints, .. } = event {
+                recorded.lock().unwrap().push(datapoints);
+            }
+            ControlFlow::Continue(())
+        });
+    let model = arrsac
+        .model(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    assert!(model.norm.dot(&norm).abs() > 0.99);
+    // The search stops after three blocks although most of the data is left.
+    assert_eq!(*blocks.lock().unwrap(), [256 + 64, 256 + 128, 256 + 192]);
+    assert_eq!(
+        arrsac.estimated_hypothesis_evaluations(points.len()),
+        256 + 3 * 64
+    );
+}
+
+#[test]
+fn max_blocks_zero() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 10000);
+
+    let blocks = Arc::new(Mutex::new(0));
+    let recorded = blocks.clone();
+    let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(1))
+        .max_blocks(0)
+        .inspect(move |event| {
+            if let Event::BlockCompleted { .. } = event {
+                *recorded.lock().unwrap() += 1;
+            }
+            ControlFlow::Continue(())
+        });
+    let model = arrsac
+        .model(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    assert!(model.norm.dot(&norm).abs() > 0.99);
+    // Only the initial hypotheses are tested and no block is evaluated after them.
+    assert_eq!(*blocks.lock().unwrap(), 0);
+    assert_eq!(arrsac.estimated_hypothesis_evaluations(points.len()), 256);
+}
+
+#[test]
+fn min_estimations_per_block() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 10000);
+
+    let mut generated = vec![];
+    for min in [None, Some(0)] {
+        let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(1)).max_blocks(8);
+        if let Some(min) = min {
+            arrsac = arrsac.min_estimations_per_block(min);
+        }
+        let model = arrsac
+            .model(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+        generated.push(arrsac.report().hypotheses_generated);
+    }
+    // The best hypotheses of a single line mostly agree, so fewer hypotheses are generated.
+    assert!(generated[1] < generated[0], "{generated:?}");
+}
+
+#[test]
+fn terminate_when() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 10000);
+
+    let contexts = Arc::new(Mutex::new(Vec::new()));
+    let recorded = contexts.clone();
+    let blocks = Arc::new(Mutex::new(0));
+    let completed = blocks.clone();
+    let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(1))
+        .max_candidate_hypotheses(1 << 20)
+        .inspect(move |event| {
+            if let Event::BlockCompleted { .. } = event {
+                *completed.lock().unwrap() += 1;
+            }
+            ControlFlow::Continue(())
+        })
+        .terminate_when(move |context: &TerminationContext| {
+            recorded.lock().unwrap().push(*context);
+            context.datapoints >= 512
+        });
+    let model = arrsac
+        .model(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    assert!(model.norm.dot(&norm).abs() > 0.99);
+    // The criterion is called after every block until it stops the search.
+    let contexts = contexts.lock().unwrap();
+    assert_eq!(*blocks.lock().unwrap(), contexts.len());
+    assert_eq!(
+        contexts
+            .iter()
+            .map(|context| context.datapoints)
+            .collect::<Vec<_>>(),
+        [320, 384, 448, 512]
+    );
+    for context in contexts.iter() {
+        assert!(context.best_inliers > 0);
+        assert!(context.survivors > 1);
+        assert!(context.epsilon > context.delta);
+        assert!(context.hypotheses_generated > 256);
+    }
+    assert!(!arrsac.report().stopped_early);
+}
+
+/// A line that takes a lot of memory.
+struct PaddedLine {
+    line: Line,
+    _padding: [u8; 1000],
+}
+
+impl Model<Vector2<f64>> for PaddedLine {
+    fn residual(&self, point: &Vector2<f64>) -> f64 {
+        self.line.residual(point)
+    }
+}
+
+struct PaddedLineEstimator;
+
+impl Estimator<Vector2<f64>> for PaddedLineEstimator {
+    type Model = PaddedLine;
+    type ModelIter = std::iter::Once<PaddedLine>;
+    const MIN_SAMPLES: usize = 2;
+
+    fn estimate<I>(&self, data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = Vector2<f64>> + Clone,
+    {
+        let line = LineEstimator.estimate(data).next().unwrap();
+        std::iter::once(PaddedLine {
+            line,
+            _padding: [0; 1000],
+        })
+    }
+}
+
+#[test]
+fn max_survivors_bytes() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 2000);
+
+    // A padded line with its bookkeeping takes a bit more than 1000 bytes, so 3 fit into 4096.
+    for (budget, max_survivors) in [(None, usize::MAX), (Some(4096), 3), (Some(0), 1)] {
+        let survivors = Arc::new(Mutex::new(Vec::new()));
+        let recorded = survivors.clone();
+        let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(1))
+            .max_candidate_hypotheses(1 << 20)
+            .inspect(move |event| {
+                if let Event::BlockCompleted { hypotheses, .. } = event {
+                    recorded.lock().unwrap().push(hypotheses);
+                }
+                ControlFlow::Continue(())
+            });
+        if let Some(budget) = budget {
+            arrsac = arrsac.max_survivors_bytes(budget);
+        }
+        let model = arrsac
+            .model(&PaddedLineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.line.norm.dot(&norm).abs() > 0.99);
+        let most = survivors.lock().unwrap().iter().copied().max().unwrap();
+        if budget.is_some() {
+            assert_eq!(most, max_survivors);
+        } else {
+            // Without a budget, many more hypotheses survive than fit into 4096 bytes.
+            assert!(most > 3);
+        }
+    }
+}
@@ -1,124 +1,1492 @@
-use arrsac::Arrsac;
-use rand::distributions::Uniform;
-use rand::{distributions::Distribution, Rng, SeedableRng};
+mod common;
+
+use arrsac::{
+    Arrsac, AsprtOrder, Executor, IndexedEstimator, RefiningEstimator, Sequential, Task,
+    UsableHypothesisThreshold, WeightFn,
+};
+use common::{line_points, random_line, Line, LineEstimator, Vector2};
+use rand::{seq::SliceRandom, Rng, SeedableRng};
 use rand_xoshiro::Xoshiro256PlusPlus;
 use sample_consensus::{Consensus, Estimator, Model};
+use std::cell::Cell;
+
+#[test]
+fn lines() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    // The max candidate hypotheses had to be increased dramatically to ensure all 1000 cases find a
+    // good-fitting line.
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
 
-#[derive(Debug, Clone, Copy)]
-struct Vector2<T> {
-    x: T,
-    y: T,
+    for _ in 0..2000 {
+        let (norm, c) = random_line(&mut rng);
+        // Generate random number of points between 50 and 1000.
+        let num = rng.gen_range(50..1000);
+        let points = line_points(&mut rng, norm, c, num);
+
+        let model = arrsac
+            .model(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        // Check the slope using the cosine distance.
+        assert!(
+            model.norm.dot(&norm).abs() > 0.99,
+            "slope out of expected range"
+        );
+    }
 }
 
-impl Vector2<f64> {
-    fn new(x: f64, y: f64) -> Self {
-        Self { x, y }
+#[test]
+fn lines_soft_inliers() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    for weight_fn in [WeightFn::Tukey, WeightFn::TriCube] {
+        let mut arrsac = Arrsac::new(3.0, rng.clone()).soft_inliers(weight_fn);
+        for _ in 0..200 {
+            let (norm, c) = random_line(&mut rng);
+            let num = rng.gen_range(50..1000);
+            let points = line_points(&mut rng, norm, c, num);
+
+            let (model, inliers) = arrsac
+                .model_inliers(&LineEstimator, points.iter().copied())
+                .expect("unable to estimate a model");
+            assert!(
+                model.norm.dot(&norm).abs() > 0.99,
+                "slope out of expected range"
+            );
+            // The returned inliers still use the hard threshold.
+            assert!(inliers.iter().all(|&ix| model.residual(&points[ix]) < 3.0));
+        }
+    }
+}
+
+#[test]
+fn lines_full_sampling_only() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone()).full_sampling_only(true);
+    for _ in 0..200 {
+        let (norm, c) = random_line(&mut rng);
+        let num = rng.gen_range(50..1000);
+        let points = line_points(&mut rng, norm, c, num);
+
+        let model = arrsac
+            .model(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(
+            model.norm.dot(&norm).abs() > 0.99,
+            "slope out of expected range"
+        );
     }
-    fn dot(&self, other: &Self) -> f64 {
-        self.x * other.x + self.y * other.y
+}
+
+#[test]
+fn lines_usable_hypothesis_threshold() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    for threshold in [
+        UsableHypothesisThreshold::AbsoluteCount(40),
+        UsableHypothesisThreshold::FractionOfData(0.8),
+    ] {
+        let mut arrsac = Arrsac::new(3.0, rng.clone()).usable_hypothesis_threshold(threshold);
+        for _ in 0..200 {
+            let (norm, c) = random_line(&mut rng);
+            let num = rng.gen_range(50..1000);
+            let points = line_points(&mut rng, norm, c, num);
+
+            let model = arrsac
+                .model(&LineEstimator, points.iter().copied())
+                .expect("unable to estimate a model");
+            assert!(
+                model.norm.dot(&norm).abs() > 0.99,
+                "slope out of expected range"
+            );
+        }
     }
-    fn norm(&self) -> f64 {
-        (self.x * self.x + self.y * self.y).sqrt()
+}
+
+/// A generic pipeline that takes its consensus algorithm by value.
+fn fit_line<C: Consensus<LineEstimator, Vector2<f64>>>(
+    mut consensus: C,
+    points: &[Vector2<f64>],
+) -> Option<Line> {
+    consensus.model(&LineEstimator, points.iter().copied())
+}
+
+#[test]
+fn lines_by_mutable_reference() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    for _ in 0..10 {
+        let (norm, c) = random_line(&mut rng);
+        let points = line_points(&mut rng, norm, c, 500);
+        let model = fit_line(&mut arrsac, &points).expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
     }
-    fn normalize(&self) -> Self {
-        let v_norm = self.norm();
-        Self {
-            x: self.x / v_norm,
-            y: self.y / v_norm,
+    // The instance is still usable directly afterwards.
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 500);
+    assert!(arrsac
+        .model(&LineEstimator, points.iter().copied())
+        .is_some());
+}
+
+#[test]
+fn lines_geometric_blocks() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone())
+        .block_size(16)
+        .geometric_blocks(2.0);
+    for _ in 0..200 {
+        let (norm, c) = random_line(&mut rng);
+        let num = rng.gen_range(50..5000);
+        let points = line_points(&mut rng, norm, c, num);
+
+        let model = arrsac
+            .model(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(
+            model.norm.dot(&norm).abs() > 0.99,
+            "slope out of expected range"
+        );
+    }
+}
+
+#[test]
+fn lines_score_sample_fraction() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone()).score_sample_fraction(0.25);
+    for _ in 0..100 {
+        let (norm, c) = random_line(&mut rng);
+        let num = rng.gen_range(1000..5000);
+        let points = line_points(&mut rng, norm, c, num);
+
+        let (model, inliers) = arrsac
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(
+            model.norm.dot(&norm).abs() > 0.99,
+            "slope out of expected range"
+        );
+        // The inliers are computed over all of the data.
+        assert!(inliers.iter().any(|&ix| ix >= num / 2));
+    }
+}
+
+#[test]
+fn lines_block_eval_stride() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let datasets: Vec<_> =
+        (0..20)
+            .map(|_| {
+                let (norm, c) = random_line(&mut rng);
+                let mut points = line_points(&mut rng, norm, c, 4000);
+                points.extend((0..1000).map(|_| {
+                    Vector2::new(rng.gen_range(-100.0..100.0), rng.gen_range(-100.0..100.0))
+                }));
+                points.shuffle(&mut rng);
+                (norm, points)
+            })
+            .collect();
+    let residuals = Cell::new(0);
+    let estimator = CountedLineEstimator {
+        residuals: &residuals,
+    };
+    let mut costs = vec![];
+    for stride in [1, 4] {
+        let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(0))
+            .max_candidate_hypotheses(1 << 16)
+            .block_eval_stride(stride);
+        for (norm, points) in &datasets {
+            let (model, inliers) = arrsac
+                .model_inliers(&estimator, points.iter().copied())
+                .expect("unable to estimate a model");
+            assert!(
+                model.line.norm.dot(norm).abs() > 0.99,
+                "slope out of expected range with stride {stride}"
+            );
+            // The inliers are computed over all of the data.
+            assert!(inliers.len() > 2000, "too few inliers with stride {stride}");
+        }
+        costs.push(residuals.replace(0));
+    }
+    // Most of the cost is in the many blocks with a lot of candidates, which the stride makes cheaper.
+    assert!(costs[1] * 3 < costs[0] * 2, "{costs:?}");
+}
+
+#[test]
+fn lines_builder() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let builder = Arrsac::builder(3.0)
+        .block_size(32)
+        .max_candidate_hypotheses(32);
+    for seed in 0..20 {
+        // Every instance is stamped out from the same configuration.
+        let mut arrsac = builder
+            .clone()
+            .build(Xoshiro256PlusPlus::seed_from_u64(seed));
+        let (norm, c) = random_line(&mut rng);
+        let points = line_points(&mut rng, norm, c, 500);
+        let model = arrsac
+            .model(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+    }
+    // Building from the default configuration is the same as `Arrsac::new`.
+    let points = line_points(&mut rng, Vector2::new(1.0, 0.0), 0.0, 500);
+    let a = Arrsac::builder(3.0)
+        .build(Xoshiro256PlusPlus::seed_from_u64(7))
+        .model_inliers(&LineEstimator, points.iter().copied())
+        .map(|(_, inliers)| inliers);
+    let b = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(7))
+        .model_inliers(&LineEstimator, points.iter().copied())
+        .map(|(_, inliers)| inliers);
+    assert_eq!(a, b);
+}
+
+#[test]
+fn lines_incremental_inliers() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    for seed in 0..20 {
+        let (norm, c) = random_line(&mut rng);
+        let num = rng.gen_range(50..1000);
+        let points = line_points(&mut rng, norm, c, num);
+        let (model, inliers) = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(seed))
+            .incremental_inliers(true)
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        // The tracked inliers are the same as those found by a full scan.
+        let expected: Vec<usize> = points
+            .iter()
+            .enumerate()
+            .filter(|(_, point)| model.residual(point) < 3.0)
+            .map(|(ix, _)| ix)
+            .collect();
+        assert_eq!(inliers, expected);
+        // Tracking the inliers does not change the result.
+        let reference = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(seed))
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .map(|(_, inliers)| inliers);
+        assert_eq!(reference, Some(inliers));
+    }
+}
+
+/// Fits lines from oversampled sets, checking that it always gets `sample_size` datapoints.
+struct OversampledLineEstimator {
+    sample_size: usize,
+}
+
+impl Estimator<Vector2<f64>> for OversampledLineEstimator {
+    type Model = Line;
+    type ModelIter = std::iter::Once<Line>;
+    const MIN_SAMPLES: usize = 2;
+
+    fn estimate<I>(&self, data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = Vector2<f64>> + Clone,
+    {
+        assert_eq!(data.clone().count(), self.sample_size);
+        LineEstimator.estimate(data)
+    }
+}
+
+#[test]
+fn lines_sample_size() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    // Samples that don't fit inline must spill over to the heap.
+    for sample_size in (0..20).map(|ix| if ix % 2 == 0 { 4 } else { 12 }) {
+        let (norm, c) = random_line(&mut rng);
+        let points = line_points(&mut rng, norm, c, 500);
+        let model = Arrsac::new(3.0, rng.clone())
+            .sample_size(sample_size)
+            .model(
+                &OversampledLineEstimator { sample_size },
+                points.iter().copied(),
+            )
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+    }
+}
+
+#[test]
+#[should_panic]
+fn lines_sample_size_below_minimum() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 500);
+    Arrsac::new(3.0, rng)
+        .sample_size(1)
+        .model(&LineEstimator, points.iter().copied());
+}
+
+#[test]
+fn lines_skip_initial_phase() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone())
+        .skip_initial_phase(true)
+        .initial_estimates(0.5, 0.05);
+    for _ in 0..20 {
+        let (norm, c) = random_line(&mut rng);
+        let points = line_points(&mut rng, norm, c, 1000);
+        let model = arrsac
+            .model(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+        // No initial hypotheses were generated to estimate delta from.
+        assert_eq!(arrsac.report().delta_estimations, 0);
+    }
+}
+
+#[test]
+fn lines_asprt_worst_first() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone())
+        .asprt_order(AsprtOrder::WorstFirst)
+        .incremental_inliers(true);
+    for _ in 0..20 {
+        let (norm, c) = random_line(&mut rng);
+        let num = rng.gen_range(50..1000);
+        let points = line_points(&mut rng, norm, c, num);
+        let (model, inliers) = arrsac
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+        // The tracked inliers are still in the order of the input.
+        let expected: Vec<usize> = points
+            .iter()
+            .enumerate()
+            .filter(|(_, point)| model.residual(point) < 3.0)
+            .map(|(ix, _)| ix)
+            .collect();
+        assert_eq!(inliers, expected);
+    }
+}
+
+#[test]
+fn lines_residuals() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 500);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    let (model, inliers) = arrsac
+        .model_inliers(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    let residuals = arrsac.residuals(points.iter().copied(), &model);
+    assert_eq!(residuals.len(), points.len());
+    let below: Vec<usize> = (0..points.len())
+        .filter(|&ix| residuals[ix] < 3.0)
+        .collect();
+    assert_eq!(below, inliers);
+}
+
+#[test]
+fn lines_inlier_window() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    for incremental_inliers in [false, true] {
+        let (norm, c) = random_line(&mut rng);
+        let points = line_points(&mut rng, norm, c, 1000);
+        let (model, inliers) = Arrsac::new(3.0, rng.clone())
+            .inlier_window(300)
+            .incremental_inliers(incremental_inliers)
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+        // Only the inliers within the window are returned.
+        let expected: Vec<usize> = (0..300)
+            .filter(|&ix| model.residual(&points[ix]) < 3.0)
+            .collect();
+        assert_eq!(inliers, expected);
+    }
+}
+
+#[test]
+fn lines_models() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    for _ in 0..20 {
+        let lines = [random_line(&mut rng), random_line(&mut rng)];
+        // Skip nearly parallel lines, whose inliers overlap too much to tell them apart.
+        if lines[0].0.dot(&lines[1].0).abs() > 0.9 {
+            continue;
+        }
+        let mut points = line_points(&mut rng, lines[0].0, lines[0].1, 300);
+        points.extend(line_points(&mut rng, lines[1].0, lines[1].1, 300));
+
+        // The threshold covers the noise, so no band of leftover points is mistaken for a line.
+        let seed = rng.gen();
+        let models = Arrsac::new(5.0, Xoshiro256PlusPlus::seed_from_u64(seed)).models(
+            &LineEstimator,
+            points.iter().copied(),
+            2,
+        );
+        assert_eq!(models.len(), 2);
+        // Every line is found once and the inliers are disjoint.
+        for (norm, _) in lines {
+            assert!(models
+                .iter()
+                .any(|(model, _)| model.norm.dot(&norm).abs() > 0.99));
         }
+        assert!(models[0].1.iter().all(|ix| !models[1].1.contains(ix)));
     }
 }
 
-impl core::ops::Mul<Vector2<f64>> for f64 {
-    type Output = Vector2<f64>;
-    fn mul(self, rhs: Vector2<f64>) -> Self::Output {
-        Vector2 {
-            x: self * rhs.x,
-            y: self * rhs.y,
+/// Counts the samples with datapoints further apart than `radius`.
+struct SpreadLineEstimator {
+    radius: f64,
+    spread_samples: Cell<usize>,
+}
+
+impl Estimator<Vector2<f64>> for SpreadLineEstimator {
+    type Model = Line;
+    type ModelIter = std::iter::Once<Line>;
+    const MIN_SAMPLES: usize = 2;
+
+    fn estimate<I>(&self, data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = Vector2<f64>> + Clone,
+    {
+        let mut points = data.clone();
+        let (a, b) = (points.next().unwrap(), points.next().unwrap());
+        if (a + -1.0 * b).norm() > self.radius {
+            self.spread_samples.set(self.spread_samples.get() + 1);
         }
+        LineEstimator.estimate(data)
     }
 }
 
-impl core::ops::Add for Vector2<f64> {
-    type Output = Self;
-    fn add(self, rhs: Self) -> Self::Output {
-        Self {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
+#[test]
+fn lines_locality_bias() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    for _ in 0..20 {
+        // Two lines that are far apart from each other.
+        let (norm, c) = random_line(&mut rng);
+        let mut points = line_points(&mut rng, norm, c, 300);
+        let shift = Vector2::new(1000.0, 0.0);
+        let (other_norm, other_c) = random_line(&mut rng);
+        points.extend(
+            line_points(&mut rng, other_norm, other_c, 200)
+                .into_iter()
+                .map(|point| point + shift),
+        );
+
+        let estimator = SpreadLineEstimator {
+            radius: 200.0,
+            spread_samples: Cell::new(0),
+        };
+        let model = arrsac
+            .locality_bias(|a: &Vector2<f64>, b: &Vector2<f64>| (*a + -1.0 * *b).norm() < 200.0)
+            .model(&estimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+        assert_eq!(estimator.spread_samples.get(), 0);
+
+        // Sampling falls back to all of the data when no datapoint is local.
+        let model = arrsac
+            .locality_bias(|_: &Vector2<f64>, _: &Vector2<f64>| false)
+            .model(&estimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+        assert!(estimator.spread_samples.get() > 0);
+    }
+}
+
+/// Counts the samples with identical datapoints.
+struct DuplicateLineEstimator {
+    duplicate_samples: Cell<usize>,
+}
+
+impl Estimator<Vector2<f64>> for DuplicateLineEstimator {
+    type Model = Line;
+    type ModelIter = std::iter::Once<Line>;
+    const MIN_SAMPLES: usize = 2;
+
+    fn estimate<I>(&self, data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = Vector2<f64>> + Clone,
+    {
+        let mut points = data.clone();
+        let (a, b) = (points.next().unwrap(), points.next().unwrap());
+        if a.x == b.x && a.y == b.y {
+            self.duplicate_samples.set(self.duplicate_samples.get() + 1);
         }
+        LineEstimator.estimate(data)
     }
 }
 
-#[derive(Debug)]
-struct Line {
-    norm: Vector2<f64>,
-    c: f64,
+#[test]
+fn lines_dedupe_data() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    let is_duplicate = |a: &Vector2<f64>, b: &Vector2<f64>| a.x == b.x && a.y == b.y;
+    for _ in 0..20 {
+        let (norm, c) = random_line(&mut rng);
+        // Every point is repeated a few times, like on a coarse sensor grid.
+        let mut points: Vec<Vector2<f64>> = line_points(&mut rng, norm, c, 100)
+            .into_iter()
+            .flat_map(|point| [point; 4])
+            .collect();
+        points.extend(
+            line_points(&mut rng, norm, 30.0 + c, 10)
+                .into_iter()
+                .flat_map(|point| [point; 30]),
+        );
+
+        let estimator = DuplicateLineEstimator {
+            duplicate_samples: Cell::new(0),
+        };
+        let (model, inliers) = arrsac
+            .dedupe_data(is_duplicate)
+            .model_inliers(&estimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+        assert_eq!(estimator.duplicate_samples.get(), 0);
+        // The inliers include every duplicate of the points on the line.
+        assert!(inliers.len() > 4 * 40 && inliers.len() % 4 == 0);
+        assert!(inliers.iter().all(|&ix| ix < 400));
+
+        // Without deduplication, samples of identical datapoints are drawn.
+        let _ = arrsac.model(&estimator, points.iter().copied());
+        assert!(estimator.duplicate_samples.get() > 0);
+    }
 }
 
-impl Model<Vector2<f64>> for Line {
-    fn residual(&self, point: &Vector2<f64>) -> f64 {
-        (self.norm.dot(point) + self.c).abs()
+#[test]
+fn lines_adaptive_threshold() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    // The configured threshold is several times looser than the noise of the inliers.
+    let mut arrsac = Arrsac::new(15.0, rng.clone()).adaptive_threshold_percentile(0.4);
+    for _ in 0..50 {
+        let (norm, c) = random_line(&mut rng);
+        // Points no more than 5.0 away from the line among uniformly scattered outliers.
+        let mut points = line_points(&mut rng, norm, c, 600);
+        points.extend(
+            (0..400)
+                .map(|_| Vector2::new(rng.gen_range(-100.0..100.0), rng.gen_range(-100.0..100.0))),
+        );
+        points.shuffle(&mut rng);
+
+        let (model, inliers) = arrsac
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+        let threshold = arrsac
+            .report()
+            .adaptive_inlier_threshold
+            .expect("no adaptive threshold");
+        assert!(threshold > 1.0 && threshold < 10.0, "threshold {threshold}");
+        assert!(inliers
+            .iter()
+            .all(|&ix| model.residual(&points[ix]) < threshold));
+    }
+}
+
+#[test]
+fn lines_calibrate_threshold() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    // The configured threshold is only a loose upper bound on the noise of the inliers. The noise
+    // is uniform, which is bounded by about 1.5 of its estimated standard deviations.
+    let mut arrsac = Arrsac::new(10.0, rng.clone()).calibrate_threshold(1.5);
+    for _ in 0..50 {
+        let (norm, c) = random_line(&mut rng);
+        // Points no more than 5.0 away from the line among uniformly scattered outliers.
+        let mut points = line_points(&mut rng, norm, c, 600);
+        points.extend(
+            (0..400)
+                .map(|_| Vector2::new(rng.gen_range(-100.0..100.0), rng.gen_range(-100.0..100.0))),
+        );
+        points.shuffle(&mut rng);
+
+        let (model, inliers) = arrsac
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+        let threshold = arrsac
+            .report()
+            .calibrated_inlier_threshold
+            .expect("no calibrated threshold");
+        assert!(
+            threshold > 4.0 && threshold <= 10.0,
+            "threshold {threshold}"
+        );
+        assert!(inliers
+            .iter()
+            .all(|&ix| model.residual(&points[ix]) < threshold));
+        // Nearly all of the points on the line are still inliers.
+        assert!(inliers.len() > 550, "{} inliers", inliers.len());
+    }
+}
+
+#[test]
+fn lines_extreme_blocks() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 200);
+    // The initialization blocks cover more datapoints than `usize` can count.
+    let model = Arrsac::new(3.0, rng.clone())
+        .block_size(usize::MAX / 2)
+        .model(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    assert!(model.norm.dot(&norm).abs() > 0.99);
+    // There are more blocks than bits in `usize`, so the retained hypotheses can't be a shift.
+    let model = Arrsac::new(3.0, rng.clone())
+        .block_size(1)
+        .max_candidate_hypotheses(usize::MAX)
+        .estimations_per_block(4)
+        .model(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    assert!(model.norm.dot(&norm).abs() > 0.99);
+    // Fewer candidates than initialization blocks still keep the best hypothesis.
+    assert!(Arrsac::new(3.0, rng)
+        .max_candidate_hypotheses(1)
+        .model(&LineEstimator, points.iter().copied())
+        .is_some());
+}
+
+#[test]
+fn lines_with_len() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 500);
+    let expected = Arrsac::new(3.0, rng.clone())
+        .model_inliers(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+
+    // A channel receiver can't be cloned.
+    let (sender, receiver) = std::sync::mpsc::channel();
+    for &point in &points {
+        sender.send(point).unwrap();
+    }
+    drop(sender);
+    let (model, inliers) = Arrsac::new(3.0, rng)
+        .model_inliers_with_len(&LineEstimator, receiver, points.len())
+        .expect("unable to estimate a model");
+    assert_eq!(model.c, expected.0.c);
+    assert_eq!(inliers, expected.1);
+}
+
+#[test]
+fn lines_report_threshold() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    for incremental_inliers in [false, true] {
+        for _ in 0..50 {
+            let (norm, c) = random_line(&mut rng);
+            let points = line_points(&mut rng, norm, c, 500);
+            let seed = rng.gen();
+            let (model, inliers) = Arrsac::new(2.0, Xoshiro256PlusPlus::seed_from_u64(seed))
+                .incremental_inliers(incremental_inliers)
+                .model_inliers(&LineEstimator, points.iter().copied())
+                .expect("unable to estimate a model");
+            let (reported_model, reported_inliers) =
+                Arrsac::new(2.0, Xoshiro256PlusPlus::seed_from_u64(seed))
+                    .incremental_inliers(incremental_inliers)
+                    .report_threshold(4.0)
+                    .model_inliers(&LineEstimator, points.iter().copied())
+                    .expect("unable to estimate a model");
+            // The same model is selected, but it has more inliers.
+            assert_eq!(model.c, reported_model.c);
+            assert!(inliers.iter().all(|ix| reported_inliers.contains(ix)));
+            assert!(reported_inliers.len() > inliers.len());
+            assert!(reported_inliers
+                .iter()
+                .all(|&ix| model.residual(&points[ix]) < 4.0));
+        }
     }
 }
 
-struct LineEstimator;
+/// Checks that the indices it is given are those of the sampled datapoints.
+struct IndexedLineEstimator<'a> {
+    points: &'a [Vector2<f64>],
+    indexed_samples: Cell<usize>,
+}
 
-impl Estimator<Vector2<f64>> for LineEstimator {
+impl Estimator<Vector2<f64>> for IndexedLineEstimator<'_> {
     type Model = Line;
     type ModelIter = std::iter::Once<Line>;
     const MIN_SAMPLES: usize = 2;
 
-    fn estimate<I>(&self, mut data: I) -> Self::ModelIter
+    fn estimate<I>(&self, data: I) -> Self::ModelIter
     where
         I: Iterator<Item = Vector2<f64>> + Clone,
     {
-        let a = data.next().unwrap();
-        let b = data.next().unwrap();
-        let norm = Vector2::new(a.y - b.y, b.x - a.x).normalize();
-        let c = -norm.dot(&b);
-        std::iter::once(Line { norm, c })
+        LineEstimator.estimate(data)
+    }
+}
+
+impl IndexedEstimator<Vector2<f64>> for IndexedLineEstimator<'_> {
+    fn estimate_indexed<I>(&self, data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = (usize, Vector2<f64>)> + Clone,
+    {
+        for (ix, point) in data.clone() {
+            assert_eq!(self.points[ix].x, point.x);
+            assert_eq!(self.points[ix].y, point.y);
+        }
+        self.indexed_samples.set(self.indexed_samples.get() + 1);
+        self.estimate(data.map(|(_, point)| point))
     }
 }
 
 #[test]
-fn lines() {
+fn lines_indexed_samples() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    for seed in 0..50 {
+        let (norm, c) = random_line(&mut rng);
+        let points = line_points(&mut rng, norm, c, 1000);
+        let estimator = IndexedLineEstimator {
+            points: &points,
+            indexed_samples: Cell::new(0),
+        };
+
+        let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(seed));
+        let (model, inliers) = arrsac
+            .indexed_samples()
+            .model_inliers(&estimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+        assert!(estimator.indexed_samples.get() > 0);
+
+        // Passing the indices does not change the run.
+        let reference = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(seed))
+            .model_inliers(&LineEstimator, points.iter().copied());
+        assert_eq!(reference.map(|(_, inliers)| inliers), Some(inliers));
+    }
+}
+
+#[test]
+fn lines_inliers_into() {
     let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
-    // The max candidate hypotheses had to be increased dramatically to ensure all 1000 cases find a
-    // good-fitting line.
     let mut arrsac = Arrsac::new(3.0, rng.clone());
+    let mut reference = Arrsac::new(3.0, rng.clone());
+    let mut inliers = Vec::new();
+    for _ in 0..50 {
+        let (norm, c) = random_line(&mut rng);
+        let num = rng.gen_range(50..1000);
+        let points = line_points(&mut rng, norm, c, num);
 
-    for _ in 0..2000 {
-        // Generate <a, b> and normalize.
-        let norm = Vector2::new(rng.gen_range(-10.0..10.0), rng.gen_range(-10.0..10.0)).normalize();
-        // Get parallel ray.
-        let ray = Vector2::new(norm.y, -norm.x);
-        // Generate random c.
-        let c = rng.gen_range(-10.0..10.0);
+        let model = arrsac
+            .model_inliers_into(&LineEstimator, points.iter().copied(), &mut inliers)
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+        // The buffer only holds the inliers of this run.
+        let (_, expected) = reference
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .unwrap();
+        assert_eq!(inliers, expected);
+    }
 
-        // Generate random number of points between 50 and 1000.
+    // The buffer is cleared even if no model is found.
+    assert!(arrsac
+        .model_inliers_into(&LineEstimator, core::iter::empty(), &mut inliers)
+        .is_none());
+    assert!(inliers.is_empty());
+}
+
+/// Always estimates the x axis.
+struct AxisEstimator;
+
+impl Estimator<Vector2<f64>> for AxisEstimator {
+    type Model = Line;
+    type ModelIter = std::iter::Once<Line>;
+    const MIN_SAMPLES: usize = 2;
+
+    fn estimate<I>(&self, _: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = Vector2<f64>> + Clone,
+    {
+        std::iter::once(Line {
+            norm: Vector2::new(0.0, 1.0),
+            c: 0.0,
+        })
+    }
+}
+
+#[test]
+fn lines_threshold_tolerance() {
+    // Points on the x axis and points with residuals exactly at the inlier threshold.
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut points: Vec<Vector2<f64>> = (0..100)
+        .map(|_| Vector2::new(rng.gen_range(-100.0..100.0), 0.0))
+        .collect();
+    points.extend((0..20).map(|ix| Vector2::new(ix as f64, if ix % 2 == 0 { 3.0 } else { -3.0 })));
+
+    for seed in 0..10 {
+        let (_, inliers) = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(seed))
+            .model_inliers(&AxisEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert_eq!(inliers, (0..100).collect::<Vec<_>>());
+
+        let (_, inliers) = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(seed))
+            .threshold_tolerance(1e-9)
+            .model_inliers(&AxisEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert_eq!(inliers, (0..120).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn lines_inliers_ranges() {
+    // Contiguous runs of points on the x axis separated by points far from it.
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut on_axis = |num: usize, y: f64| -> Vec<Vector2<f64>> {
+        (0..num)
+            .map(|_| Vector2::new(rng.gen_range(-100.0..100.0), y))
+            .collect()
+    };
+    let mut points = on_axis(300, 0.0);
+    points.extend(on_axis(100, 50.0));
+    points.extend(on_axis(1, 0.0));
+    points.extend(on_axis(1, 50.0));
+    points.extend(on_axis(200, 0.0));
+
+    let (_, ranges) = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(0))
+        .model_inliers_ranges(&AxisEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    assert_eq!(ranges, [0..300, 400..401, 402..602]);
+}
+
+#[test]
+fn lines_with_capacity() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    for hint in [0, 10, 1_000_000] {
+        let mut arrsac = Arrsac::with_capacity(3.0, rng.clone(), hint);
+        let mut reference = Arrsac::new(3.0, rng.clone());
+        for _ in 0..20 {
+            let (norm, c) = random_line(&mut rng);
+            let num = rng.gen_range(50..1000);
+            let points = line_points(&mut rng, norm, c, num);
+
+            // The hint doesn't change the results, whether it is too large or too small.
+            let (model, inliers) = arrsac
+                .model_inliers(&LineEstimator, points.iter().copied())
+                .expect("unable to estimate a model");
+            assert!(model.norm.dot(&norm).abs() > 0.99);
+            let (expected_model, expected) = reference
+                .model_inliers(&LineEstimator, points.iter().copied())
+                .unwrap();
+            assert_eq!(model.c, expected_model.c);
+            assert_eq!(inliers, expected);
+        }
+    }
+}
+
+#[test]
+fn lines_full_scan_small_data() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    // An aggressive SPRT with few hypotheses, so rejecting a good hypothesis matters.
+    let mut sprt = Arrsac::new(3.0, rng.clone())
+        .initialization_hypotheses(8)
+        .likelihood_ratio_threshold(5.0);
+    let mut full_scan = Arrsac::new(3.0, rng.clone())
+        .initialization_hypotheses(8)
+        .likelihood_ratio_threshold(5.0)
+        .full_scan_small_data(true);
+    let (mut sprt_inliers, mut full_scan_inliers) = (0, 0);
+    for _ in 0..200 {
+        let (norm, c) = random_line(&mut rng);
+        // Half of the points are far from the line.
+        let mut points = line_points(&mut rng, norm, c, 30);
+        points.extend(
+            (0..30)
+                .map(|_| Vector2::new(rng.gen_range(-100.0..100.0), rng.gen_range(-100.0..100.0))),
+        );
+        points.shuffle(&mut rng);
+
+        let (_, inliers) = sprt
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        sprt_inliers += inliers.len();
+        let (_, inliers) = full_scan
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        // Every hypothesis was scored exactly, so nothing is left for the blocks.
+        assert_eq!(full_scan.report().found_in_block, Some(0));
+        full_scan_inliers += inliers.len();
+    }
+    assert!(full_scan_inliers > sprt_inliers);
+}
+
+#[test]
+fn lines_inliers_iter() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    let mut reference = Arrsac::new(3.0, rng.clone());
+    for _ in 0..50 {
+        let (norm, c) = random_line(&mut rng);
         let num = rng.gen_range(50..1000);
-        // The points should be no more than 5.0 away from the line and be evenly distributed away from the line.
-        let residuals = Uniform::new(-5.0, 5.0);
-        // The points must be generated along the line, but the distance should be bounded to make it more difficult.
-        let distances = Uniform::new(-50.0, 50.0);
-        // Generate the points.
-        let points: Vec<Vector2<f64>> = (0..num)
-            .map(|_| {
-                let residual: f64 = residuals.sample(&mut rng);
-                let distance: f64 = distances.sample(&mut rng);
-                let along = distance * ray;
-                let against = (residual - c) * norm;
-                along + against
-            })
+        let points = line_points(&mut rng, norm, c, num);
+
+        let inliers = arrsac
+            .model_inliers_iter(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(inliers.model().norm.dot(&norm).abs() > 0.99);
+        let (_, expected) = reference
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .unwrap();
+        assert_eq!(inliers.collect::<Vec<_>>(), expected);
+    }
+}
+
+#[test]
+fn lines_fewer_than_a_block() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    for _ in 0..100 {
+        let (norm, c) = random_line(&mut rng);
+        // Fewer points than a single block, so only the initial phase runs.
+        let num = rng.gen_range(10..64);
+        let mut points = line_points(&mut rng, norm, c, num);
+        points.extend(
+            (0..num / 4)
+                .map(|_| Vector2::new(rng.gen_range(-100.0..100.0), rng.gen_range(-100.0..100.0))),
+        );
+        points.shuffle(&mut rng);
+
+        let (model, inliers) = arrsac
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+        // The inliers are found over all of the data.
+        let expected: Vec<usize> = (0..points.len())
+            .filter(|&ix| model.residual(&points[ix]) < 3.0)
             .collect();
+        assert_eq!(inliers, expected);
+        assert!(2 * inliers.len() >= num);
+    }
+}
+
+#[test]
+fn lines_residual_scale() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    for seed in 0..20 {
+        let (norm, c) = random_line(&mut rng);
+        let points = line_points(&mut rng, norm, c, 1000);
+
+        // A scale of one for every point is the same as not scaling.
+        let scaled = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(seed))
+            .residual_scale(|_: &Vector2<f64>| 1.0)
+            .model_inliers(&LineEstimator, points.iter().copied());
+        let reference = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(seed))
+            .model_inliers(&LineEstimator, points.iter().copied());
+        assert_eq!(
+            scaled.map(|(_, inliers)| inliers),
+            reference.map(|(_, inliers)| inliers)
+        );
+
+        // Halving the residuals is the same as doubling the threshold.
+        let (model, inliers) = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(seed))
+            .residual_scale(|_: &Vector2<f64>| 2.0)
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+        let reference = Arrsac::new(6.0, Xoshiro256PlusPlus::seed_from_u64(seed))
+            .model_inliers(&LineEstimator, points.iter().copied());
+        assert_eq!(reference.map(|(_, inliers)| inliers), Some(inliers));
+    }
+}
+
+#[test]
+fn lines_residual_prefilter() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    for seed in 0..20 {
+        let (norm, c) = random_line(&mut rng);
+        let points = line_points(&mut rng, norm, c, 1000);
+
+        // A prefilter that passes every point is the same as not filtering.
+        let prefiltered = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(seed))
+            .residual_prefilter(|_: &Vector2<f64>, _: &Line| true)
+            .model_inliers(&LineEstimator, points.iter().copied());
+        let reference = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(seed))
+            .model_inliers(&LineEstimator, points.iter().copied());
+        let (model, inliers) = prefiltered.expect("unable to estimate a model");
+        let (expected, expected_inliers) = reference.unwrap();
+        assert_eq!(model.c, expected.c);
+        assert_eq!(inliers, expected_inliers);
+
+        // Points that fail the prefilter are never inliers.
+        let (_, inliers) = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(seed))
+            .residual_prefilter(|point: &Vector2<f64>, _: &Line| point.x > 0.0)
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(inliers.iter().all(|&ix| points[ix].x > 0.0));
+    }
+}
+
+#[test]
+fn lines_dedupe_hypotheses() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(1.0, rng.clone());
+    let is_same_model = |a: &Line, b: &Line| {
+        // The normal of a line can point either way.
+        let sign = a.norm.dot(&b.norm).signum();
+        a.norm.dot(&b.norm).abs() > 1.0 - 1e-9 && (a.c - sign * b.c).abs() < 1e-6
+    };
+    for _ in 0..20 {
+        let (norm, c) = random_line(&mut rng);
+        // Every sample of points exactly on the line produces the same line.
+        let direction = Vector2::new(-norm.y, norm.x);
+        let mut points: Vec<Vector2<f64>> = (0..600)
+            .map(|_| (-c) * norm + rng.gen_range(-100.0..100.0) * direction)
+            .collect();
+        points.extend(
+            (0..400)
+                .map(|_| Vector2::new(rng.gen_range(-100.0..100.0), rng.gen_range(-100.0..100.0))),
+        );
+        points.shuffle(&mut rng);
+
+        let (model, inliers) = arrsac
+            .dedupe_hypotheses(is_same_model)
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+        assert!(inliers.len() >= 600);
+        assert!(arrsac.report().duplicate_hypotheses > 0);
 
+        let _ = arrsac.model(&LineEstimator, points.iter().copied());
+        assert_eq!(arrsac.report().duplicate_hypotheses, 0);
+    }
+}
+
+#[test]
+fn lines_no_candidate_hypotheses() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    // No candidate hypotheses still retains the best one instead of finding nothing.
+    let mut arrsac = Arrsac::new(3.0, rng.clone()).max_candidate_hypotheses(0);
+    for _ in 0..20 {
+        let (norm, c) = random_line(&mut rng);
+        let points = line_points(&mut rng, norm, c, 1000);
+        let (model, _) = arrsac
+            .try_model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+    }
+}
+
+/// Records for every sample if its datapoints are on the same side of the y axis.
+struct SideLineEstimator {
+    same_side: std::cell::RefCell<Vec<bool>>,
+}
+
+impl Estimator<Vector2<f64>> for SideLineEstimator {
+    type Model = Line;
+    type ModelIter = std::iter::Once<Line>;
+    const MIN_SAMPLES: usize = 2;
+
+    fn estimate<I>(&self, data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = Vector2<f64>> + Clone,
+    {
+        let mut points = data.clone();
+        let (a, b) = (points.next().unwrap(), points.next().unwrap());
+        self.same_side.borrow_mut().push((a.x > 0.0) == (b.x > 0.0));
+        LineEstimator.estimate(data)
+    }
+}
+
+#[test]
+fn lines_stratified_init() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    let side = |point: &Vector2<f64>| usize::from(point.x > 0.0);
+    for _ in 0..20 {
+        let (norm, c) = random_line(&mut rng);
+        let mut points = line_points(&mut rng, norm, c, 500);
+        // Make sure both sides have datapoints.
+        points.push(Vector2::new(-1.0, 0.0));
+        points.push(Vector2::new(1.0, 0.0));
+
+        let estimator = SideLineEstimator {
+            same_side: Default::default(),
+        };
         let model = arrsac
-            .model(&LineEstimator, points.iter().copied())
+            .stratified_init(side, 2)
+            .model(&estimator, points.iter().copied())
             .expect("unable to estimate a model");
-        // Check the slope using the cosine distance.
-        assert!(
-            model.norm.dot(&norm).abs() > 0.99,
-            "slope out of expected range"
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+        // Only the initial samples are stratified.
+        let same_side = estimator.same_side.take();
+        assert!(same_side.len() > 256);
+        assert!(same_side[..256].iter().all(|&same_side| !same_side));
+
+        // Sampling falls back to all of the data when there are too few strata.
+        let model = arrsac
+            .stratified_init(|_: &Vector2<f64>| 0, 2)
+            .model(&estimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+        assert!(estimator.same_side.take()[..256]
+            .iter()
+            .any(|&same_side| same_side));
+    }
+}
+
+/// An iterator whose every clone yields one datapoint more than the one it was cloned from, by
+/// starting over from the first point.
+struct Growing {
+    points: std::rc::Rc<Vec<Vector2<f64>>>,
+    len: usize,
+    position: usize,
+}
+
+impl Clone for Growing {
+    fn clone(&self) -> Self {
+        Self {
+            points: self.points.clone(),
+            len: self.len + 1,
+            position: self.position,
+        }
+    }
+}
+
+impl Iterator for Growing {
+    type Item = Vector2<f64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position == self.len {
+            return None;
+        }
+        let point = self.points[self.position % self.points.len()];
+        self.position += 1;
+        Some(point)
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "every clone of the data to yield the same datapoints")]
+fn lines_non_idempotent_data() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    let (norm, c) = random_line(&mut rng);
+    let points = Growing {
+        points: line_points(&mut rng, norm, c, 1000).into(),
+        len: 1000,
+        position: 0,
+    };
+    arrsac.model(&LineEstimator, points);
+}
+
+/// Refines a line with a total least squares fit to its inliers.
+impl RefiningEstimator<Vector2<f64>> for LineEstimator {
+    fn refine<I>(&self, _: &Line, inliers: I) -> Option<Line>
+    where
+        I: Iterator<Item = Vector2<f64>> + Clone,
+    {
+        let n = inliers.clone().count() as f64;
+        let sum = inliers
+            .clone()
+            .fold(Vector2::new(0.0, 0.0), |sum, p| sum + p);
+        let mean = (1.0 / n) * sum;
+        let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+        for p in inliers {
+            let d = p + -1.0 * mean;
+            sxx += d.x * d.x;
+            sxy += d.x * d.y;
+            syy += d.y * d.y;
+        }
+        // The line runs along the principal axis of the inliers.
+        let angle = 0.5 * (2.0 * sxy).atan2(sxx - syy);
+        let norm = Vector2::new(-angle.sin(), angle.cos());
+        Some(Line {
+            norm,
+            c: -norm.dot(&mean),
+        })
+    }
+}
+
+/// Never refines.
+impl RefiningEstimator<Vector2<f64>> for AxisEstimator {}
+
+#[test]
+fn lines_refined() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    let mut reference = Arrsac::new(3.0, rng.clone());
+    let mut improved = 0;
+    for _ in 0..50 {
+        let (norm, c) = random_line(&mut rng);
+        let points = line_points(&mut rng, norm, c, 500);
+
+        let (refined, refined_inliers) = arrsac
+            .refined()
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        let (_, inliers) = reference
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .unwrap();
+        assert!(refined.norm.dot(&norm).abs() > 0.99);
+        // A refined model that loses inliers is discarded.
+        assert!(refined_inliers.len() >= inliers.len());
+        if refined_inliers.len() > inliers.len() {
+            improved += 1;
+        }
+    }
+    assert!(improved > 0);
+
+    // The default implementation leaves the model as it is.
+    let points: Vec<Vector2<f64>> = (0..100)
+        .map(|_| Vector2::new(rng.gen_range(-100.0..100.0), 0.0))
+        .collect();
+    let (model, inliers) = arrsac
+        .refined()
+        .model_inliers(&AxisEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    assert_eq!((model.norm.x, model.norm.y, model.c), (0.0, 1.0, 0.0));
+    assert_eq!(inliers, (0..100).collect::<Vec<_>>());
+}
+
+/// Runs every task on its own thread.
+struct Threads;
+
+impl Executor for Threads {
+    fn execute(&self, tasks: &mut [Task<'_>]) {
+        std::thread::scope(|scope| {
+            for task in tasks {
+                scope.spawn(task);
+            }
+        });
+    }
+}
+
+#[test]
+fn lines_parallel_scoring() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    let mut sequential = Arrsac::new(3.0, rng.clone());
+    let mut threads = Arrsac::new(3.0, rng.clone());
+    for _ in 0..20 {
+        let (norm, c) = random_line(&mut rng);
+        let points = line_points(&mut rng, norm, c, 500);
+
+        let (model, inliers) = arrsac
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        // Scoring in parallel finds the same model with the same inliers.
+        let results = [
+            sequential
+                .parallel_scoring(Sequential)
+                .model_inliers(&LineEstimator, points.iter().copied()),
+            threads
+                .parallel_scoring(Threads)
+                .model_inliers(&LineEstimator, points.iter().copied()),
+        ];
+        for (parallel, parallel_inliers) in results.into_iter().map(Option::unwrap) {
+            assert_eq!(parallel.c, model.c);
+            assert_eq!(parallel.norm.dot(&model.norm), model.norm.dot(&model.norm));
+            assert_eq!(parallel_inliers, inliers);
+        }
+    }
+}
+
+#[test]
+fn lines_model_only() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    let mut reference = Arrsac::new(3.0, rng.clone());
+    for _ in 0..50 {
+        let (norm, c) = random_line(&mut rng);
+        let points = line_points(&mut rng, norm, c, 500);
+
+        let model = arrsac
+            .model_only(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(arrsac.report().inlier_residuals.is_none());
+        let expected = reference
+            .model(&LineEstimator, points.iter().copied())
+            .unwrap();
+        assert_eq!(model.c, expected.c);
+        assert_eq!(
+            (model.norm.x, model.norm.y),
+            (expected.norm.x, expected.norm.y)
+        );
+        assert_eq!(
+            arrsac.report().found_in_block,
+            reference.report().found_in_block
         );
     }
 }
+
+/// A line that counts how often its residual is computed.
+struct CountedLine<'a> {
+    line: Line,
+    residuals: &'a Cell<usize>,
+}
+
+impl Model<Vector2<f64>> for CountedLine<'_> {
+    fn residual(&self, point: &Vector2<f64>) -> f64 {
+        self.residuals.set(self.residuals.get() + 1);
+        self.line.residual(point)
+    }
+}
+
+struct CountedLineEstimator<'a> {
+    residuals: &'a Cell<usize>,
+}
+
+impl<'a> Estimator<Vector2<f64>> for CountedLineEstimator<'a> {
+    type Model = CountedLine<'a>;
+    type ModelIter = Option<CountedLine<'a>>;
+    const MIN_SAMPLES: usize = 2;
+
+    fn estimate<I>(&self, data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = Vector2<f64>> + Clone,
+    {
+        let line = LineEstimator.estimate(data).next()?;
+        Some(CountedLine {
+            line,
+            residuals: self.residuals,
+        })
+    }
+}
+
+#[test]
+fn lines_single_final_pass() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 1000);
+    let residuals = Cell::new(0);
+    let estimator = CountedLineEstimator {
+        residuals: &residuals,
+    };
+    let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(0));
+    let _ = arrsac
+        .model_inliers(&estimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    let with_inliers = residuals.replace(0);
+    assert!(arrsac.report().inlier_residuals.is_some());
+    let model = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(0))
+        .model_only(&estimator, points.iter().copied());
+    assert!(model.is_some());
+    // The inliers and the spread of their residuals are found in one pass over the data.
+    assert_eq!(with_inliers - residuals.get(), points.len());
+}
+
+thread_local! {
+    /// How often the residual of a [`CachedLine`] is computed on this thread.
+    static CACHED_RESIDUALS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// A line that can be cached with `residual_cache`, which counts how often its residual is computed.
+#[derive(Debug, Clone, PartialEq)]
+struct CachedLine(f64, f64, f64);
+
+impl Eq for CachedLine {}
+
+impl std::hash::Hash for CachedLine {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        for value in [self.0, self.1, self.2] {
+            value.to_bits().hash(state);
+        }
+    }
+}
+
+impl Model<Vector2<f64>> for CachedLine {
+    fn residual(&self, point: &Vector2<f64>) -> f64 {
+        CACHED_RESIDUALS.with(|residuals| residuals.set(residuals.get() + 1));
+        let line = Line {
+            norm: Vector2::new(self.0, self.1),
+            c: self.2,
+        };
+        line.residual(point)
+    }
+}
+
+struct CachedLineEstimator;
+
+impl Estimator<Vector2<f64>> for CachedLineEstimator {
+    type Model = CachedLine;
+    type ModelIter = Option<CachedLine>;
+    const MIN_SAMPLES: usize = 2;
+
+    fn estimate<I>(&self, data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = Vector2<f64>> + Clone,
+    {
+        let line = LineEstimator.estimate(data).next()?;
+        Some(CachedLine(line.norm.x, line.norm.y, line.c))
+    }
+}
+
+/// Takes the number of residuals computed on this thread so far.
+fn cached_residuals() -> usize {
+    CACHED_RESIDUALS.with(|residuals| residuals.replace(0))
+}
+
+#[test]
+fn lines_residual_cache() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    for _ in 0..10 {
+        let lines = [random_line(&mut rng), random_line(&mut rng)];
+        if lines[0].0.dot(&lines[1].0).abs() > 0.9 {
+            continue;
+        }
+        let mut points = line_points(&mut rng, lines[0].0, lines[0].1, 300);
+        points.extend(line_points(&mut rng, lines[1].0, lines[1].1, 300));
+
+        let seed = rng.gen();
+        let expected = Arrsac::new(5.0, Xoshiro256PlusPlus::seed_from_u64(seed)).models(
+            &LineEstimator,
+            points.iter().copied(),
+            2,
+        );
+        let mut arrsac =
+            Arrsac::new(5.0, Xoshiro256PlusPlus::seed_from_u64(seed)).residual_cache(true);
+        let models = arrsac.cached_models(&CachedLineEstimator, points.iter().copied(), 2);
+        assert_eq!(models.len(), expected.len());
+        for ((model, inliers), (expected_model, expected_inliers)) in models.iter().zip(&expected) {
+            assert_eq!(model.2, expected_model.c);
+            assert_eq!(inliers, expected_inliers);
+        }
+
+        // Sweeping over thresholds reuses the residuals that were cached while extracting the
+        // models, apart from the few that check that the data is the same.
+        for (model, _) in &models {
+            for threshold in [1.0, 3.0, 5.0, 8.0] {
+                let expected = arrsac.recompute_inliers(model, points.iter().copied(), threshold);
+                cached_residuals();
+                let inliers = arrsac.cached_inliers(model, points.iter().copied(), threshold);
+                assert!(cached_residuals() <= 32);
+                assert_eq!(inliers, expected);
+            }
+        }
+        assert_eq!(arrsac.data_epoch(), 0);
+
+        // Models that aren't equal don't share residuals, even though they are close.
+        let (model, _) = &models[0];
+        let moved = CachedLine(model.0, model.1, model.2 + 1e-9);
+        let inliers = arrsac.cached_inliers(&moved, points.iter().copied(), 5.0);
+        assert_eq!(cached_residuals(), points.len());
+        assert_eq!(
+            inliers,
+            arrsac.recompute_inliers(&moved, points.iter().copied(), 5.0)
+        );
+        cached_residuals();
+
+        // A new data epoch drops the cache.
+        arrsac.bump_data_epoch();
+        assert_eq!(arrsac.data_epoch(), 1);
+        let _ = arrsac.cached_inliers(model, points.iter().copied(), 5.0);
+        assert_eq!(cached_residuals(), points.len());
+
+        // Changed data starts a new data epoch by itself, whether its length changed or not.
+        let fewer = &points[..points.len() - 1];
+        let inliers = arrsac.cached_inliers(model, fewer.iter().copied(), 5.0);
+        assert_eq!(arrsac.data_epoch(), 2);
+        assert_eq!(
+            inliers,
+            arrsac.recompute_inliers(model, fewer.iter().copied(), 5.0)
+        );
+        let shifted: Vec<_> = points
+            .iter()
+            .map(|p| Vector2::new(p.x, p.y + 1.0))
+            .collect();
+        let inliers = arrsac.cached_inliers(model, shifted.iter().copied(), 5.0);
+        assert_eq!(arrsac.data_epoch(), 3);
+        assert_eq!(
+            inliers,
+            arrsac.recompute_inliers(model, shifted.iter().copied(), 5.0)
+        );
+        cached_residuals();
+
+        // Nothing is cached without the flag.
+        let mut arrsac = Arrsac::new(5.0, Xoshiro256PlusPlus::seed_from_u64(seed));
+        let _ = arrsac.cached_inliers(model, points.iter().copied(), 5.0);
+        let _ = arrsac.cached_inliers(model, points.iter().copied(), 5.0);
+        assert_eq!(cached_residuals(), 2 * points.len());
+    }
+}
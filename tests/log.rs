@@ -0,0 +1,46 @@
+#![cfg(feature = "log")]
+
+mod common;
+
+use arrsac::Arrsac;
+use common::{line_points, random_line, LineEstimator};
+use log::{LevelFilter, Log, Metadata, Record};
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256PlusPlus;
+use sample_consensus::Consensus;
+use std::sync::Mutex;
+
+/// Collects every logged message.
+struct Messages(Mutex<Vec<String>>);
+
+impl Log for Messages {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.0.lock().unwrap().push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+static MESSAGES: Messages = Messages(Mutex::new(Vec::new()));
+
+#[test]
+fn log_run() {
+    log::set_logger(&MESSAGES).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 1000);
+    Arrsac::new(3.0, rng)
+        .model(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+
+    let messages = MESSAGES.0.lock().unwrap();
+    assert!(messages[0].starts_with("initial phase: epsilon "));
+    assert!(messages[1].starts_with("block 4: epsilon "));
+    assert!(messages.last().unwrap().starts_with("selected hypothesis "));
+}
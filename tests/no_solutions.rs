@@ -1,9 +1,9 @@
-use arrsac::Arrsac;
+use arrsac::{Arrsac, ArrsacError};
 use rand::SeedableRng;
 use rand_xoshiro::Xoshiro256PlusPlus;
 use sample_consensus::{Consensus, Estimator, Model};
 
-pub struct Unsolvable(f64);
+pub struct Unsolvable;
 
 impl Model<i32> for Unsolvable {
     fn residual(&self, _data: &i32) -> f64 {
@@ -35,4 +35,44 @@ pub fn no_valid_hypothesys() {
     let estimator = UnsolvableEstimator {};
     let result = arrsac.model(&estimator, 1..999);
     assert!(result.is_none());
+    assert!(arrsac.report().no_models_generated);
+    assert_eq!(arrsac.report().delta_estimations, 0);
+}
+
+/// The `try_` methods report why no model was found instead of returning `None` or panicking.
+#[test]
+pub fn try_model_inliers_errors() {
+    let rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng);
+    let estimator = UnsolvableEstimator {};
+    assert_eq!(
+        arrsac.try_model_inliers(&estimator, 1..999).err(),
+        Some(ArrsacError::NoModelGenerated)
+    );
+    assert_eq!(
+        arrsac.try_model_inliers(&estimator, 0..0).err(),
+        Some(ArrsacError::EmptyData)
+    );
+    assert_eq!(
+        arrsac
+            .try_fit(&estimator, 0..3)
+            .err()
+            .map(|error| error.to_string()),
+        Some(ArrsacError::NotEnoughSamples.to_string())
+    );
+    let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(0)).initial_eval_size(3);
+    assert_eq!(
+        arrsac.try_model_inliers(&estimator, 1..999).err(),
+        Some(ArrsacError::InvalidConfig)
+    );
+    let mut arrsac = arrsac.initial_eval_size(64).initialization_hypotheses(0);
+    assert_eq!(
+        arrsac.try_model_inliers(&estimator, 1..999).err(),
+        Some(ArrsacError::InvalidConfig)
+    );
+    let mut arrsac = arrsac.initialization_hypotheses(256).sample_size(2);
+    assert_eq!(
+        arrsac.try_model_inliers(&estimator, 1..999).err(),
+        Some(ArrsacError::InvalidConfig)
+    );
 }
@@ -0,0 +1,45 @@
+use arrsac::Arrsac;
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256PlusPlus;
+use sample_consensus::{Consensus, Estimator, Model};
+
+/// A model that produces non-finite residuals for three quarters of the data.
+pub struct Degenerate;
+
+impl Model<i32> for Degenerate {
+    fn residual(&self, data: &i32) -> f64 {
+        match data % 4 {
+            0 => f64::NAN,
+            1 => f64::NEG_INFINITY,
+            2 => f64::INFINITY,
+            _ => 0.0,
+        }
+    }
+}
+
+pub struct DegenerateEstimator;
+
+impl Estimator<i32> for DegenerateEstimator {
+    type Model = Degenerate;
+    type ModelIter = Option<Degenerate>;
+    const MIN_SAMPLES: usize = 2;
+
+    fn estimate<I>(&self, _data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = i32> + Clone,
+    {
+        Some(Degenerate)
+    }
+}
+
+/// Datapoints with a non-finite residual are never inliers.
+#[test]
+fn non_finite_residuals_are_outliers() {
+    let rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng);
+    let (_, inliers) = arrsac
+        .model_inliers(&DegenerateEstimator, 0..1000)
+        .expect("unable to estimate a model");
+    let expected: Vec<usize> = (3..1000).step_by(4).collect();
+    assert_eq!(inliers, expected);
+}
@@ -0,0 +1,180 @@
+use arrsac::Arrsac;
+use rand::distributions::Uniform;
+use rand::{distributions::Distribution, seq::SliceRandom, Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use sample_consensus::{Consensus, Estimator, Model};
+
+#[derive(Debug, Clone, Copy)]
+struct Vector3 {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+impl Vector3 {
+    fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+    fn dot(&self, other: &Self) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+    fn cross(&self, other: &Self) -> Self {
+        Self {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+    fn norm(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+    fn scale(&self, s: f64) -> Self {
+        Self::new(self.x * s, self.y * s, self.z * s)
+    }
+    fn sub(&self, other: &Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+    fn add(&self, other: &Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+/// The plane `norm · p + d = 0` with a unit `norm`.
+#[derive(Debug)]
+struct Plane {
+    norm: Vector3,
+    d: f64,
+}
+
+impl Model<Vector3> for Plane {
+    fn residual(&self, point: &Vector3) -> f64 {
+        (self.norm.dot(point) + self.d).abs()
+    }
+}
+
+struct PlaneEstimator;
+
+impl Estimator<Vector3> for PlaneEstimator {
+    type Model = Plane;
+    type ModelIter = Option<Plane>;
+    const MIN_SAMPLES: usize = 3;
+
+    fn estimate<I>(&self, mut data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = Vector3> + Clone,
+    {
+        let a = data.next().unwrap();
+        let b = data.next().unwrap();
+        let c = data.next().unwrap();
+        let norm = b.sub(&a).cross(&c.sub(&a));
+        let len = norm.norm();
+        // Collinear samples don't define a plane.
+        if len < 1e-9 {
+            return None;
+        }
+        let norm = norm.scale(1.0 / len);
+        Some(Plane {
+            d: -norm.dot(&a),
+            norm,
+        })
+    }
+}
+
+/// Generates `num` points with the given `outlier_fraction` around a random plane.
+///
+/// Returns the plane normal, the points, and whether each point was generated as an inlier.
+fn noisy_plane(
+    rng: &mut impl Rng,
+    num: usize,
+    outlier_fraction: f64,
+    noise: f64,
+) -> (Vector3, Vec<Vector3>, Vec<bool>) {
+    let unit = Uniform::new(-1.0, 1.0);
+    let norm = loop {
+        let v = Vector3::new(unit.sample(rng), unit.sample(rng), unit.sample(rng));
+        if v.norm() > 0.1 {
+            break v.scale(1.0 / v.norm());
+        }
+    };
+    // Two directions spanning the plane.
+    let helper = if norm.x.abs() < 0.9 {
+        Vector3::new(1.0, 0.0, 0.0)
+    } else {
+        Vector3::new(0.0, 1.0, 0.0)
+    };
+    let u = norm.cross(&helper);
+    let u = u.scale(1.0 / u.norm());
+    let v = norm.cross(&u);
+    let d = rng.gen_range(-10.0..10.0);
+
+    let extent = Uniform::new(-50.0, 50.0);
+    let noise = Uniform::new(-noise, noise);
+    let mut points = Vec::with_capacity(num);
+    let mut truth = Vec::with_capacity(num);
+    for _ in 0..num {
+        if rng.gen_bool(outlier_fraction) {
+            points.push(Vector3::new(
+                extent.sample(rng),
+                extent.sample(rng),
+                extent.sample(rng),
+            ));
+            truth.push(false);
+        } else {
+            let on_plane = u
+                .scale(extent.sample(rng))
+                .add(&v.scale(extent.sample(rng)))
+                .add(&norm.scale(-d + noise.sample(rng)));
+            points.push(on_plane);
+            truth.push(true);
+        }
+    }
+    // Shuffle the points and ground truth together.
+    let mut order: Vec<usize> = (0..num).collect();
+    order.shuffle(rng);
+    let points = order.iter().map(|&ix| points[ix]).collect();
+    let truth = order.iter().map(|&ix| truth[ix]).collect();
+    (norm, points, truth)
+}
+
+fn check_planes(outlier_fraction: f64) {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(1.0, rng.clone());
+    for _ in 0..50 {
+        let num = rng.gen_range(500..2000);
+        let (norm, points, truth) = noisy_plane(&mut rng, num, outlier_fraction, 0.5);
+
+        let (model, inliers) = arrsac
+            .model_inliers(&PlaneEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(
+            model.norm.dot(&norm).abs() > 0.99,
+            "plane normal out of expected range"
+        );
+        let true_inliers = truth.iter().filter(|&&inlier| inlier).count();
+        let recovered = inliers.iter().filter(|&&ix| truth[ix]).count();
+        assert!(
+            recovered as f64 > 0.9 * true_inliers as f64,
+            "recovered {recovered} out of {true_inliers} inliers"
+        );
+        // Random outliers rarely land close to the plane.
+        assert!(
+            (inliers.len() - recovered) as f64 <= 0.05 * num as f64,
+            "too many outliers were accepted"
+        );
+    }
+}
+
+#[test]
+fn planes_without_outliers() {
+    check_planes(0.0);
+}
+
+#[test]
+fn planes_with_outliers() {
+    check_planes(0.3);
+}
+
+#[test]
+fn planes_with_many_outliers() {
+    check_planes(0.6);
+}
@@ -0,0 +1,18 @@
+mod common;
+
+use arrsac::prelude::*;
+use common::{line_points, random_line, LineEstimator};
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+#[test]
+fn prelude_is_enough() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 500);
+    let mut arrsac = Arrsac::new(3.0, rng);
+    let (model, inliers) = arrsac
+        .model_inliers(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    assert!(inliers.iter().all(|&ix| model.residual(&points[ix]) < 3.0));
+}
@@ -0,0 +1,125 @@
+#![cfg(feature = "record-models")]
+
+mod common;
+
+use arrsac::{Arrsac, Event};
+use common::{line_points, random_line, LineEstimator, Vector2};
+use core::ops::ControlFlow;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use sample_consensus::{Consensus, Model};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn record_models() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 1000);
+
+    let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(1)).record_models(true);
+    let (model, inliers) = arrsac
+        .model_inliers(&LineEstimator, points.iter().copied())
+        .unwrap();
+
+    let recorded = arrsac.recorded_models();
+    assert_eq!(recorded.len(), arrsac.report().hypotheses_generated);
+    assert!(recorded.iter().any(|record| record.accepted));
+    assert!(recorded.iter().any(|record| !record.accepted));
+    assert!(recorded
+        .windows(2)
+        .all(|records| records[0].generation < records[1].generation));
+
+    // The recorded samples estimate the models again, including the returned one.
+    assert!(recorded.iter().any(|record| {
+        let line = record
+            .estimate(&LineEstimator, points.iter().copied())
+            .unwrap();
+        points
+            .iter()
+            .all(|point| (model.residual(point) - line.residual(point)).abs() < 1e-9)
+    }));
+    assert!(!inliers.is_empty());
+
+    // Recording does not change the run.
+    let reference = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(1))
+        .model_inliers(&LineEstimator, points.iter().copied());
+    assert_eq!(reference.map(|(_, inliers)| inliers), Some(inliers));
+}
+
+#[test]
+fn max_recorded_models() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 1000);
+
+    let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(1))
+        .record_models(true)
+        .max_recorded_models(10);
+    arrsac.model_inliers(&LineEstimator, points.iter().copied());
+    assert_eq!(arrsac.recorded_models().len(), 10);
+
+    // Only the latest run is kept.
+    arrsac.model_inliers(&LineEstimator, points.iter().copied());
+    assert_eq!(arrsac.recorded_models().len(), 10);
+
+    let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(1));
+    arrsac.model_inliers(&LineEstimator, points.iter().copied());
+    assert!(arrsac.recorded_models().is_empty());
+}
+
+#[test]
+fn accepted_inliers_are_counted_fully() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let mut points = line_points(&mut rng, norm, c, 600);
+    points.extend(
+        (0..400).map(|_| Vector2::new(rng.gen_range(-100.0..100.0), rng.gen_range(-100.0..100.0))),
+    );
+    points.shuffle(&mut rng);
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = events.clone();
+    let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(1))
+        .record_models(true)
+        .inspect(move |event| {
+            recorded.lock().unwrap().push(event);
+            ControlFlow::Continue(())
+        });
+    arrsac
+        .model(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+
+    // The hypotheses accepted by the SPRT carry their inliers over all of the datapoints checked so
+    // far into the next block, not a count truncated by the SPRT. The first 512 hypotheses are the
+    // initial ones, which are checked on the 4 initial blocks, and the rest up to the first
+    // completed block are also checked on the next block.
+    let events = events.lock().unwrap();
+    let accepted: Vec<(usize, usize)> = events
+        .iter()
+        .take_while(|event| !matches!(event, Event::BlockCompleted { .. }))
+        .filter_map(|event| match *event {
+            Event::HypothesisAccepted {
+                generation,
+                inliers,
+            } => Some((generation, inliers)),
+            _ => None,
+        })
+        .collect();
+    assert!(!accepted.is_empty());
+    for (generation, inliers) in accepted {
+        let record = arrsac
+            .recorded_models()
+            .iter()
+            .find(|record| record.generation == generation)
+            .unwrap();
+        let line = record
+            .estimate(&LineEstimator, points.iter().copied())
+            .unwrap();
+        let checked = if generation < 512 { 256 } else { 320 };
+        let expected = points[..checked]
+            .iter()
+            .filter(|point| line.residual(point) < 3.0)
+            .count();
+        assert_eq!(inliers, expected);
+    }
+}
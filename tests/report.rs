@@ -0,0 +1,462 @@
+mod common;
+
+use arrsac::{Arrsac, WeightFn};
+use common::{line_points, random_line, LineEstimator, Vector2};
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use sample_consensus::{Consensus, Estimator, Model};
+use std::cell::Cell;
+
+#[test]
+fn delta_estimations() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone()).initialization_hypotheses(32);
+    assert_eq!(arrsac.report().delta_estimations, 0);
+
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 500);
+    arrsac
+        .model(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    // The line estimator always produces exactly one model per sample.
+    assert_eq!(arrsac.report().delta_estimations, 32);
+}
+
+#[test]
+fn models_generated() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 500);
+    arrsac
+        .model(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    assert!(!arrsac.report().no_models_generated);
+}
+
+#[test]
+fn auto_likelihood_threshold() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut fixed = Arrsac::new(3.0, rng.clone());
+    let mut auto = Arrsac::new(3.0, rng.clone()).auto_likelihood_threshold(1e-3);
+    for _ in 0..50 {
+        let (norm, c) = random_line(&mut rng);
+        let points = line_points(&mut rng, norm, c, 500);
+        fixed
+            .model(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert_eq!(fixed.report().likelihood_ratio_threshold, 1e3);
+
+        let model = auto
+            .model(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&norm).abs() > 0.99);
+        // Wald's bound guarantees the threshold never needs to exceed the reciprocal probability.
+        let threshold = auto.report().likelihood_ratio_threshold;
+        assert!(threshold > 1.0 && threshold <= 1.001e3, "{threshold}");
+    }
+}
+
+#[test]
+fn suspicious_threshold() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 500);
+    for (threshold, suspicious) in [(3.0, false), (1e6, true), (1e-9, true)] {
+        let mut arrsac = Arrsac::new(threshold, rng.clone());
+        arrsac.model(&LineEstimator, points.iter().copied());
+        assert_eq!(
+            arrsac.report().suspicious_threshold,
+            suspicious,
+            "threshold {threshold}"
+        );
+    }
+}
+
+#[test]
+fn success_probability() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    assert_eq!(arrsac.success_probability(), None);
+
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 500);
+    let (_, inliers) = arrsac
+        .model_inliers(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    let report = arrsac.report();
+    assert_eq!(report.sample_size, 2);
+    assert!(report.hypotheses_generated >= 256);
+    // The model may only have been scored on a prefix of the data.
+    let epsilon = report.epsilon.expect("no inlier ratio recorded");
+    assert!((epsilon - inliers.len() as f32 / 500.0).abs() < 0.05);
+    // With that many hypotheses on clean data, an all-inlier sample was certainly drawn.
+    assert!(arrsac.success_probability().unwrap() > 0.999);
+}
+
+#[test]
+fn inlier_residuals() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 500);
+    let (model, inliers) = arrsac
+        .model_inliers(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    let residuals: Vec<f64> = inliers
+        .iter()
+        .map(|&ix| model.residual(&points[ix]))
+        .collect();
+    let stats = arrsac
+        .report()
+        .inlier_residuals
+        .expect("no residuals recorded");
+    assert_eq!(
+        stats.min,
+        residuals.iter().copied().fold(f64::INFINITY, f64::min)
+    );
+    assert_eq!(
+        stats.max,
+        residuals.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+    );
+    let mean = residuals.iter().sum::<f64>() / residuals.len() as f64;
+    assert!((stats.mean - mean).abs() < 1e-9);
+    assert!(stats.max < 3.0);
+    let variance =
+        residuals.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / residuals.len() as f64;
+    assert!((stats.std_dev - variance.sqrt()).abs() < 1e-9);
+}
+
+#[test]
+fn inlier_residual_std() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(6.0, rng.clone());
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 2000);
+    // The points are uniformly within `5.0` of the true line, so the distances from it are uniform
+    // in `[0, 5]` with a standard deviation of `5 / sqrt(12)`.
+    let expected = 5.0 / 12f64.sqrt();
+    let std = arrsac.inlier_residual_std(points.iter().copied(), &common::Line { norm, c });
+    assert!((std - expected).abs() < 0.05, "{std} != {expected}");
+
+    let model = arrsac
+        .model(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    let std = arrsac.inlier_residual_std(points.iter().copied(), &model);
+    let stats = arrsac
+        .report()
+        .inlier_residuals
+        .expect("no residuals recorded");
+    assert!((std - stats.std_dev).abs() < 1e-9);
+    assert!((std - expected).abs() < 0.3, "{std} != {expected}");
+    assert!(arrsac
+        .inlier_residual_std(points[..0].iter().copied(), &model)
+        .is_nan());
+}
+
+#[test]
+fn fit() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 500);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    let result = arrsac
+        .fit(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    // The result dereferences to the model.
+    assert!(result.norm.dot(&norm).abs() > 0.99);
+    assert_eq!(&result.report, arrsac.report());
+
+    let (model, inliers) = Arrsac::new(3.0, rng)
+        .model_inliers(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    assert_eq!(result.inliers, inliers);
+    assert_eq!(result.c, model.c);
+}
+
+/// Fits either all even datapoints or none at all.
+struct Parity(bool);
+
+impl Model<i32> for Parity {
+    fn residual(&self, data: &i32) -> f64 {
+        if self.0 && data % 2 == 0 {
+            0.0
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+/// Alternates between a good and a useless model without needing any samples, so the worst model
+/// has no inliers at all.
+struct ParityEstimator(std::cell::Cell<bool>);
+
+impl Estimator<i32> for ParityEstimator {
+    type Model = Parity;
+    type ModelIter = Option<Parity>;
+    const MIN_SAMPLES: usize = 0;
+
+    fn estimate<I>(&self, _data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = i32> + Clone,
+    {
+        self.0.set(!self.0.get());
+        Some(Parity(self.0.get()))
+    }
+}
+
+#[test]
+fn min_delta() {
+    let rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(1.0, rng).min_delta(0.01);
+    let (model, inliers) = arrsac
+        .model_inliers(&ParityEstimator(Default::default()), 0..1000)
+        .expect("unable to estimate a model");
+    assert!(model.0);
+    assert_eq!(inliers.len(), 500);
+    assert_eq!(arrsac.report().delta, 0.01);
+}
+
+#[test]
+fn found_in_block() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    for _ in 0..20 {
+        let (norm, c) = random_line(&mut rng);
+        let points = line_points(&mut rng, norm, c, 1000);
+        arrsac
+            .model(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        let block = arrsac.report().found_in_block.expect("no block recorded");
+        // The initial phase covers the first four blocks.
+        assert!(block == 0 || (4..1000 / 64).contains(&block));
+    }
+    arrsac.model(&LineEstimator, core::iter::empty());
+    assert_eq!(arrsac.report().found_in_block, None);
+}
+
+#[test]
+fn possibly_unshuffled() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone()).assert_shuffled(true);
+    for _ in 0..20 {
+        let (norm, c) = random_line(&mut rng);
+        // The outliers were appended after the points on the line.
+        let mut points = line_points(&mut rng, norm, c, 500);
+        points.extend(
+            (0..500).map(|_| Vector2::new(rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0))),
+        );
+        let _ = arrsac.model(&LineEstimator, points.iter().copied());
+        assert!(arrsac.report().possibly_unshuffled);
+
+        points.shuffle(&mut rng);
+        let _ = arrsac.model(&LineEstimator, points.iter().copied());
+        assert!(!arrsac.report().possibly_unshuffled);
+    }
+}
+
+/// Fits every tenth datapoint tightly, every even datapoint loosely or nothing at all.
+#[derive(Clone, Copy)]
+enum Fit {
+    Tight,
+    Loose,
+    Nothing,
+}
+
+impl Model<i32> for Fit {
+    fn residual(&self, data: &i32) -> f64 {
+        match self {
+            Fit::Tight if data % 10 == 0 => 0.0,
+            Fit::Loose if data % 2 == 0 => 0.99,
+            _ => f64::INFINITY,
+        }
+    }
+}
+
+/// Cycles through the fits without needing any samples.
+struct FitEstimator(Cell<usize>);
+
+impl Estimator<i32> for FitEstimator {
+    type Model = Fit;
+    type ModelIter = Option<Fit>;
+    const MIN_SAMPLES: usize = 0;
+
+    fn estimate<I>(&self, _data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = i32> + Clone,
+    {
+        self.0.set(self.0.get() + 1);
+        Some([Fit::Tight, Fit::Loose, Fit::Nothing][self.0.get() % 3])
+    }
+}
+
+#[test]
+fn block_epsilon_uses_most_inliers() {
+    let rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    // The tight fit has the most support, but the loose fit has the most inliers.
+    let mut arrsac = Arrsac::new(1.0, rng)
+        .soft_inliers(WeightFn::Tukey)
+        .max_candidate_hypotheses(usize::MAX);
+    let (model, inliers) = arrsac
+        .model_inliers(&FitEstimator(Cell::new(0)), 0..1000)
+        .expect("unable to estimate a model");
+    assert!(matches!(model, Fit::Tight));
+    assert_eq!(inliers.len(), 100);
+    assert_eq!(arrsac.report().block_epsilon, 0.5);
+}
+
+#[test]
+fn validate() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 1000);
+    let held_out = line_points(&mut rng, norm, c, 500);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    let (model, inliers) = arrsac
+        .model_inliers(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+
+    // The training data validates to the returned inliers.
+    let stats = arrsac.validate(&model, points.iter().copied());
+    assert_eq!(stats.inliers, inliers.len());
+    assert_eq!(stats.datapoints, 1000);
+    assert_eq!(stats.inlier_residuals, arrsac.report().inlier_residuals);
+
+    // Points of the same line fit about as well as the training data.
+    let stats = arrsac.validate(&model, held_out.iter().copied());
+    assert_eq!(stats.datapoints, 500);
+    assert!((stats.inlier_ratio - inliers.len() as f64 / 1000.0).abs() < 0.1);
+    assert!(stats.inlier_residuals.unwrap().max < 3.0);
+
+    // Points of another line don't.
+    let (other_norm, other_c) = random_line(&mut rng);
+    let other = line_points(&mut rng, other_norm, other_c + 50.0, 500);
+    assert!(arrsac.validate(&model, other.iter().copied()).inlier_ratio < 0.2);
+
+    let stats = arrsac.validate(&model, core::iter::empty());
+    assert_eq!((stats.inliers, stats.inlier_ratio), (0, 0.0));
+    assert_eq!(stats.inlier_residuals, None);
+}
+
+#[test]
+fn recompute_inliers() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 1000);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    let (model, inliers) = arrsac
+        .model_inliers(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+
+    // The threshold of the run gives back its inliers.
+    assert_eq!(
+        arrsac.recompute_inliers(&model, points.iter().copied(), 3.0),
+        inliers
+    );
+    let residuals = arrsac.residuals(points.iter().copied(), &model);
+    let mut previous = vec![];
+    for threshold in [1.0, 2.0, 3.0, 4.0, 6.0] {
+        let swept = arrsac.threshold_residuals(&residuals, threshold);
+        assert_eq!(
+            swept,
+            arrsac.recompute_inliers(&model, points.iter().copied(), threshold)
+        );
+        assert!(swept.iter().all(|&ix| residuals[ix] < threshold));
+        // A larger threshold only adds inliers.
+        assert!(previous.iter().all(|ix| swept.contains(ix)));
+        assert!(swept.len() >= previous.len());
+        previous = swept;
+    }
+    // All points are within 5 of the line, but the model isn't exactly the line.
+    assert!(previous.len() > 990);
+}
+
+/// Estimates the same line twice from every sample and counts how often it was called.
+struct TwiceLineEstimator(Cell<usize>);
+
+impl Estimator<Vector2<f64>> for TwiceLineEstimator {
+    type Model = common::Line;
+    type ModelIter = Vec<common::Line>;
+    const MIN_SAMPLES: usize = 2;
+
+    fn estimate<I>(&self, data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = Vector2<f64>> + Clone,
+    {
+        self.0.set(self.0.get() + 1);
+        LineEstimator
+            .estimate(data.clone())
+            .chain(LineEstimator.estimate(data))
+            .collect()
+    }
+}
+
+#[test]
+fn max_estimator_calls() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 1000);
+    let estimator = TwiceLineEstimator(Cell::new(0));
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+    arrsac
+        .model(&estimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    assert_eq!(arrsac.report().estimator_calls, estimator.0.get());
+    assert!(estimator.0.get() > 40);
+
+    let estimator = TwiceLineEstimator(Cell::new(0));
+    let mut arrsac = Arrsac::new(3.0, rng).max_estimator_calls(40);
+    let model = arrsac
+        .model(&estimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    assert_eq!(estimator.0.get(), 40);
+    assert_eq!(arrsac.report().estimator_calls, 40);
+    // Every call produces two hypotheses.
+    assert_eq!(arrsac.report().hypotheses_generated, 80);
+    assert!(model.norm.dot(&norm).abs() > 0.99);
+}
+
+/// Estimates the constant through the first datapoint of a sample.
+struct FirstEstimator;
+
+impl Estimator<f64> for FirstEstimator {
+    type Model = Constant;
+    type ModelIter = Option<Constant>;
+    const MIN_SAMPLES: usize = 1;
+
+    fn estimate<I>(&self, mut data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = f64> + Clone,
+    {
+        data.next().map(Constant)
+    }
+}
+
+struct Constant(f64);
+
+impl Model<f64> for Constant {
+    fn residual(&self, data: &f64) -> f64 {
+        (data - self.0).abs()
+    }
+}
+
+#[test]
+fn success_probability_small() {
+    // A tenth of the data has the same value and the rest are far apart, so with samples of eight
+    // datapoints an all-inlier sample has a probability of about `1e-8`.
+    let data: Vec<f64> = (0..1000)
+        .map(|ix| if ix % 10 == 0 { 0.0 } else { ix as f64 * 10.0 })
+        .collect();
+    let mut arrsac = Arrsac::new(1.0, Xoshiro256PlusPlus::seed_from_u64(0)).sample_size(8);
+    arrsac
+        .model(&FirstEstimator, data.iter().copied())
+        .expect("unable to estimate a model");
+    let report = arrsac.report();
+    let expected = report.hypotheses_generated as f64 * f64::from(report.epsilon.unwrap()).powi(8);
+    let probability = f64::from(arrsac.success_probability().unwrap());
+    assert!(probability > 0.0);
+    assert!(
+        (probability / expected - 1.0).abs() < 0.01,
+        "{probability} != {expected}"
+    );
+}
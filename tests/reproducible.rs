@@ -0,0 +1,94 @@
+mod common;
+
+use arrsac::{Arrsac, SliceRng};
+use common::{line_points, random_line, LineEstimator, Vector2};
+use rand::{seq::SliceRandom, Rng, RngCore, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use sample_consensus::Consensus;
+
+/// The exact result of a run is pinned, so that a change in the result on any target fails this.
+#[test]
+fn reproducible_across_targets() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let mut points = line_points(&mut rng, norm, c, 1000);
+    points.extend(
+        (0..500).map(|_| Vector2::new(rng.gen_range(-100.0..100.0), rng.gen_range(-100.0..100.0))),
+    );
+    points.shuffle(&mut rng);
+
+    let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(1));
+    let (model, inliers) = arrsac
+        .model_inliers(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    assert_eq!(model.norm.x.to_bits(), 0x3fea3214cc39c2b7);
+    assert_eq!(model.norm.y.to_bits(), 0x3fe2610886920e04);
+    assert_eq!(model.c.to_bits(), 0x401015993701452e);
+    assert_eq!(inliers.len(), 630);
+    assert_eq!(inliers.iter().sum::<usize>(), 472551);
+    assert_eq!(arrsac.report().hypotheses_generated, 704);
+}
+
+/// Records the values drawn from the RNG it wraps.
+struct RecordingRng<R> {
+    rng: R,
+    values: Vec<u32>,
+}
+
+impl<R: RngCore> RngCore for RecordingRng<R> {
+    fn next_u32(&mut self) -> u32 {
+        let value = self.rng.next_u32();
+        self.values.push(value);
+        value
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        unimplemented!("ARRSAC only draws u32s")
+    }
+
+    fn fill_bytes(&mut self, _dest: &mut [u8]) {
+        unimplemented!("ARRSAC only draws u32s")
+    }
+
+    fn try_fill_bytes(&mut self, _dest: &mut [u8]) -> Result<(), rand::Error> {
+        unimplemented!("ARRSAC only draws u32s")
+    }
+}
+
+#[test]
+fn replay_recorded_rng() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let mut points = line_points(&mut rng, norm, c, 1000);
+    points.extend(
+        (0..500).map(|_| Vector2::new(rng.gen_range(-100.0..100.0), rng.gen_range(-100.0..100.0))),
+    );
+    points.shuffle(&mut rng);
+
+    let mut recording = RecordingRng {
+        rng: Xoshiro256PlusPlus::seed_from_u64(1),
+        values: vec![],
+    };
+    let (model, inliers) = Arrsac::new(3.0, &mut recording)
+        .model_inliers(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    assert!(!recording.values.is_empty());
+
+    let mut replay = SliceRng::new(&recording.values);
+    let (replayed, replayed_inliers) = Arrsac::new(3.0, &mut replay)
+        .model_inliers(&LineEstimator, points.iter().copied())
+        .expect("unable to estimate a model");
+    assert_eq!(replayed.norm.x.to_bits(), model.norm.x.to_bits());
+    assert_eq!(replayed.norm.y.to_bits(), model.norm.y.to_bits());
+    assert_eq!(replayed.c.to_bits(), model.c.to_bits());
+    assert_eq!(replayed_inliers, inliers);
+    assert_eq!(replay.remaining(), 0);
+}
+
+#[test]
+#[should_panic]
+fn replay_runs_out() {
+    let mut rng = SliceRng::new(&[1, 2]);
+    assert_eq!(rng.next_u64(), 2 << 32 | 1);
+    rng.next_u32();
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use arrsac::Arrsac;
+use common::{line_points, random_line, LineEstimator};
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256PlusPlus;
+use sample_consensus::Consensus;
+
+/// Back-to-back runs on unrelated datasets must not influence each other.
+#[test]
+fn back_to_back_runs_are_independent() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(3.0, rng.clone());
+
+    let (norm_a, c_a) = random_line(&mut rng);
+    let points_a = line_points(&mut rng, norm_a, c_a, 1000);
+    let (norm_b, c_b) = random_line(&mut rng);
+    let points_b = line_points(&mut rng, norm_b, c_b, 100);
+
+    let (model_a, inliers_a) = arrsac
+        .model_inliers(&LineEstimator, points_a.iter().copied())
+        .expect("unable to estimate a model");
+    assert!(model_a.norm.dot(&norm_a).abs() > 0.99);
+    assert!(inliers_a.iter().all(|&ix| ix < points_a.len()));
+
+    let (model_b, inliers_b) = arrsac
+        .model_inliers(&LineEstimator, points_b.iter().copied())
+        .expect("unable to estimate a model");
+    assert!(model_b.norm.dot(&norm_b).abs() > 0.99);
+    assert!(inliers_b.iter().all(|&ix| ix < points_b.len()));
+
+    // Resetting manually between runs is always allowed.
+    arrsac.reset_state();
+    let model_b = arrsac
+        .model(&LineEstimator, points_b.iter().copied())
+        .expect("unable to estimate a model");
+    assert!(model_b.norm.dot(&norm_b).abs() > 0.99);
+}
+
+/// Reseeding before every dataset makes each run depend only on its own seed.
+#[test]
+fn reseed_sweeps_are_reproducible() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let datasets: Vec<_> = (0..10)
+        .map(|_| {
+            let (norm, c) = random_line(&mut rng);
+            line_points(&mut rng, norm, c, 500)
+        })
+        .collect();
+    let sweep = |order: &[usize]| {
+        let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(99));
+        let mut results = vec![None; datasets.len()];
+        for &ix in order {
+            arrsac.reseed([ix as u8 + 1; 32]);
+            results[ix] = arrsac
+                .model_inliers(&LineEstimator, datasets[ix].iter().copied())
+                .map(|(_, inliers)| inliers);
+        }
+        results
+    };
+    let forward: Vec<usize> = (0..10).collect();
+    let backward: Vec<usize> = (0..10).rev().collect();
+    assert_eq!(sweep(&forward), sweep(&forward));
+    assert_eq!(sweep(&forward), sweep(&backward));
+}
@@ -0,0 +1,507 @@
+mod common;
+
+use arrsac::{Arrsac, ArrsacError, TiePolicy};
+use common::{line_points, random_line, LineEstimator, Vector2};
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use sample_consensus::{Consensus, Estimator, Model};
+use std::cell::Cell;
+
+/// With two competing structures, the one with the most inliers over all of the data must win.
+#[test]
+fn selects_dominant_structure() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(5.0, rng.clone());
+    for _ in 0..50 {
+        let (norm_a, c_a) = random_line(&mut rng);
+        let (norm_b, c_b) = random_line(&mut rng);
+        // Skip nearly parallel lines, as they can share too many inliers.
+        if norm_a.dot(&norm_b).abs() > 0.9 {
+            continue;
+        }
+        let mut points = line_points(&mut rng, norm_a, c_a, 600);
+        points.extend(line_points(&mut rng, norm_b, c_b, 300));
+        points.shuffle(&mut rng);
+
+        let (model, inliers) = arrsac
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(
+            model.norm.dot(&norm_a).abs() > 0.99,
+            "the dominant line must be selected"
+        );
+        assert!(inliers.len() >= 600);
+    }
+}
+
+#[test]
+fn model_candidates() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let mut arrsac = Arrsac::new(5.0, rng.clone()).block_size(16);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 1000);
+
+    let candidates = arrsac.model_candidates(&LineEstimator, points.iter().copied());
+    assert!(!candidates.is_empty());
+    assert!(candidates
+        .windows(2)
+        .all(|pair| pair[0].1.len() >= pair[1].1.len()));
+    let (best, inliers) = &candidates[0];
+    assert!(best.norm.dot(&norm).abs() > 0.99);
+    assert!(inliers.len() > 900);
+}
+
+/// A model which considers every data point an inlier.
+struct Tagged(usize);
+
+impl Model<usize> for Tagged {
+    fn residual(&self, _data: &usize) -> f64 {
+        0.0
+    }
+}
+
+/// Tags each model with the order in which it was estimated.
+#[derive(Default)]
+struct TaggingEstimator {
+    estimated: Cell<usize>,
+}
+
+impl Estimator<usize> for TaggingEstimator {
+    type Model = Tagged;
+    type ModelIter = Option<Tagged>;
+    const MIN_SAMPLES: usize = 2;
+
+    fn estimate<I>(&self, _data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = usize> + Clone,
+    {
+        let tag = self.estimated.get();
+        self.estimated.set(tag + 1);
+        Some(Tagged(tag))
+    }
+}
+
+/// All models are tied, so the first one generated must always be returned.
+#[test]
+fn ties_prefer_earliest_model() {
+    for seed in 0..10 {
+        let mut arrsac = Arrsac::new(1.0, Xoshiro256PlusPlus::seed_from_u64(seed));
+        let estimator = TaggingEstimator::default();
+        let model = arrsac
+            .model(&estimator, 0..1000)
+            .expect("unable to estimate a model");
+        assert_eq!(model.0, 0);
+        let candidates = arrsac.model_candidates(&estimator, 0..1000);
+        assert!(candidates
+            .windows(2)
+            .all(|pair| pair[0].0 .0 < pair[1].0 .0));
+    }
+}
+
+/// Only the first two models fit every data point, the others only fit the even ones.
+struct HalfTagged(usize);
+
+impl Model<usize> for HalfTagged {
+    // `is_multiple_of` needs a newer Rust than the crate does.
+    #[allow(clippy::manual_is_multiple_of)]
+    fn residual(&self, data: &usize) -> f64 {
+        if self.0 < 2 || data % 2 == 0 {
+            0.0
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+/// Tags each model with the order in which it was estimated.
+#[derive(Default)]
+struct HalfTaggingEstimator {
+    estimated: Cell<usize>,
+}
+
+impl Estimator<usize> for HalfTaggingEstimator {
+    type Model = HalfTagged;
+    type ModelIter = Option<HalfTagged>;
+    const MIN_SAMPLES: usize = 2;
+
+    fn estimate<I>(&self, _data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = usize> + Clone,
+    {
+        let tag = self.estimated.get();
+        self.estimated.set(tag + 1);
+        Some(HalfTagged(tag))
+    }
+}
+
+/// The hypotheses at the cutoff of the truncation are tied, so the earliest ones must survive.
+#[test]
+fn truncation_ties_prefer_earliest_model() {
+    for seed in 0..10 {
+        // The data is smaller than the initialization, so the truncation of the initial phase
+        // decides which hypotheses survive.
+        let mut arrsac = Arrsac::new(1.0, Xoshiro256PlusPlus::seed_from_u64(seed))
+            .initialization_blocks(1)
+            .max_candidate_hypotheses(4);
+        let candidates = arrsac.model_candidates(&HalfTaggingEstimator::default(), 0..50);
+        let tags: Vec<usize> = candidates.iter().map(|(model, _)| model.0).collect();
+        assert_eq!(tags, [0, 1, 2, 3]);
+    }
+}
+
+#[test]
+fn nth_best_model() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 1000);
+    let arrsac = || Arrsac::new(5.0, Xoshiro256PlusPlus::seed_from_u64(1)).block_size(16);
+
+    let candidates = arrsac().model_candidates(&LineEstimator, points.iter().copied());
+    let best = arrsac()
+        .model_inliers(&LineEstimator, points.iter().copied())
+        .map(|(_, inliers)| inliers);
+    for (k, (_, inliers)) in candidates.iter().enumerate() {
+        let nth = arrsac()
+            .nth_best_model(&LineEstimator, points.iter().copied(), k)
+            .map(|(_, inliers)| inliers);
+        assert_eq!(nth.as_ref(), Some(inliers));
+    }
+    assert_eq!(
+        arrsac()
+            .nth_best_model(&LineEstimator, points.iter().copied(), 0)
+            .map(|(_, inliers)| inliers),
+        best
+    );
+    assert!(arrsac()
+        .nth_best_model(&LineEstimator, points.iter().copied(), candidates.len())
+        .is_none());
+}
+
+/// A model that only fits the two datapoints it was estimated from.
+struct Pair(i32, i32);
+
+impl Model<i32> for Pair {
+    fn residual(&self, &data: &i32) -> f64 {
+        if data == self.0 || data == self.1 {
+            0.0
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+/// Estimates pairs while recording every one of them.
+struct PairEstimator {
+    pairs: std::cell::RefCell<Vec<(i32, i32)>>,
+}
+
+impl Estimator<i32> for PairEstimator {
+    type Model = Pair;
+    type ModelIter = Option<Pair>;
+    const MIN_SAMPLES: usize = 2;
+
+    fn estimate<I>(&self, mut data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = i32> + Clone,
+    {
+        let (a, b) = (data.next()?, data.next()?);
+        self.pairs.borrow_mut().push((a.min(b), a.max(b)));
+        Some(Pair(a, b))
+    }
+}
+
+/// When the best model is no better than a minimal sample, the next samples must be drawn from all
+/// of the data instead of only reproducing it.
+#[test]
+fn sub_minimal_best_is_not_sampled_from() {
+    let rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let estimator = PairEstimator {
+        pairs: Default::default(),
+    };
+    let mut arrsac = Arrsac::new(1.0, rng).initialization_hypotheses(32);
+    let _ = arrsac.model(&estimator, 0..1000);
+    let pairs = estimator.pairs.into_inner();
+    let (initial, later) = pairs.split_at(32);
+    assert!(!later.is_empty());
+    assert!(later.iter().any(|pair| !initial.contains(pair)));
+}
+
+#[test]
+fn seed_samples() {
+    let rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let estimator = PairEstimator {
+        pairs: Default::default(),
+    };
+    let mut arrsac = Arrsac::new(1.0, rng)
+        .initialization_hypotheses(32)
+        .seed_samples(vec![vec![7, 5], vec![1, 2]]);
+    let _ = arrsac.model(&estimator, 0..1000);
+    let pairs = estimator.pairs.into_inner();
+    // The seed samples come first and random samples follow them.
+    assert_eq!(pairs[..2], [(5, 7), (1, 2)]);
+    assert!(pairs.len() > 32);
+}
+
+#[test]
+#[should_panic]
+fn seed_samples_not_minimal() {
+    let estimator = PairEstimator {
+        pairs: Default::default(),
+    };
+    let _ = Arrsac::new(1.0, Xoshiro256PlusPlus::seed_from_u64(0))
+        .seed_samples(vec![vec![1, 2, 3]])
+        .model(&estimator, 0..1000);
+}
+
+#[test]
+#[should_panic]
+fn seed_samples_out_of_range() {
+    let estimator = PairEstimator {
+        pairs: Default::default(),
+    };
+    let _ = Arrsac::new(1.0, Xoshiro256PlusPlus::seed_from_u64(0))
+        .seed_samples(vec![vec![1, 1000]])
+        .model(&estimator, 0..1000);
+}
+
+#[test]
+fn seed_samples_invalid() {
+    let estimator = PairEstimator {
+        pairs: Default::default(),
+    };
+    for seed in [vec![1, 2, 3], vec![1, 1000]] {
+        let result = Arrsac::new(1.0, Xoshiro256PlusPlus::seed_from_u64(0))
+            .seed_samples(vec![seed])
+            .try_model_inliers(&estimator, 0..1000);
+        assert!(matches!(result, Err(ArrsacError::InvalidConfig)));
+    }
+}
+
+/// A model that fits no datapoint at all.
+struct Nothing;
+
+impl Model<i32> for Nothing {
+    fn residual(&self, _data: &i32) -> f64 {
+        f64::INFINITY
+    }
+}
+
+/// Estimates degenerate models while counting how often it was called.
+struct DegenerateEstimator {
+    estimations: Cell<usize>,
+}
+
+impl Estimator<i32> for DegenerateEstimator {
+    type Model = Nothing;
+    type ModelIter = Option<Nothing>;
+    const MIN_SAMPLES: usize = 2;
+
+    fn estimate<I>(&self, _data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = i32> + Clone,
+    {
+        self.estimations.set(self.estimations.get() + 1);
+        Some(Nothing)
+    }
+}
+
+/// On a tiny dataset, a rejected sample is never estimated again.
+#[test]
+fn dedupe_samples_global() {
+    let estimations = |dedupe| {
+        let estimator = DegenerateEstimator {
+            estimations: Cell::new(0),
+        };
+        let mut arrsac =
+            Arrsac::new(1.0, Xoshiro256PlusPlus::seed_from_u64(0)).dedupe_samples_global(dedupe);
+        // Every model after the initial phase is rejected since it has no inliers.
+        let _ = arrsac.model(&estimator, 0..20);
+        estimator.estimations.get()
+    };
+    // The initial phase is not deduplicated, but afterwards there are only 190 distinct samples.
+    assert_eq!(estimations(false), 256 + 256);
+    let deduplicated = estimations(true);
+    assert!(deduplicated > 256);
+    assert!(deduplicated <= 256 + 190);
+}
+
+#[test]
+fn spatial_coverage() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    for _ in 0..10 {
+        let (spread_norm, spread_c) = random_line(&mut rng);
+        let (clustered_norm, clustered_c) = random_line(&mut rng);
+        if spread_norm.dot(&clustered_norm).abs() > 0.9 {
+            continue;
+        }
+        // Most points are on a line, but they are all within a small region.
+        let along = Vector2::new(clustered_norm.y, -clustered_norm.x);
+        let mut points: Vec<Vector2<f64>> = (0..600)
+            .map(|_| {
+                let distance: f64 = rng.gen_range(-15.0..15.0);
+                let residual: f64 = rng.gen_range(-1.0..1.0);
+                distance * along + (residual - clustered_c) * clustered_norm
+            })
+            .collect();
+        points.extend(line_points(&mut rng, spread_norm, spread_c, 600));
+        points.shuffle(&mut rng);
+        // The columns of a grid over the x coordinate.
+        let cell_of = |point: &Vector2<f64>| (((point.x + 60.0) / 10.0) as usize, 0);
+
+        let mut arrsac = Arrsac::new(3.0, rng.clone()).max_candidate_hypotheses(usize::MAX);
+        let model = arrsac
+            .model(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&clustered_norm).abs() > 0.95);
+
+        let model = arrsac
+            .spatial_coverage((12, 1), 6, cell_of)
+            .model(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert!(model.norm.dot(&spread_norm).abs() > 0.99);
+    }
+}
+
+/// A model of datapoints which are all the same value.
+struct Constant(f64);
+
+impl Model<f64> for Constant {
+    fn residual(&self, data: &f64) -> f64 {
+        (data - self.0).abs()
+    }
+}
+
+struct ConstantEstimator;
+
+impl Estimator<f64> for ConstantEstimator {
+    type Model = Constant;
+    type ModelIter = Option<Constant>;
+    const MIN_SAMPLES: usize = 1;
+
+    fn estimate<I>(&self, mut data: I) -> Self::ModelIter
+    where
+        I: Iterator<Item = f64> + Clone,
+    {
+        data.next().map(Constant)
+    }
+}
+
+#[test]
+fn recency_weight() {
+    // The data changes from mostly one value to only another one, which has fewer datapoints.
+    let data: Vec<f64> = (0..1000)
+        .map(|ix| if ix >= 600 || ix % 10 == 0 { 10.0 } else { 0.0 })
+        .collect();
+    // Never reject or discard hypotheses so that both values are candidates until the end.
+    let arrsac = || {
+        Arrsac::new(1.0, Xoshiro256PlusPlus::seed_from_u64(0))
+            .likelihood_ratio_threshold(f32::INFINITY)
+            .max_candidate_hypotheses(usize::MAX)
+    };
+    let (expected, expected_inliers) = arrsac()
+        .model_inliers(&ConstantEstimator, data.iter().copied())
+        .unwrap();
+    assert_eq!(expected.0, 0.0);
+    assert_eq!(expected_inliers.len(), 540);
+    let (model, inliers) = arrsac()
+        .recency_weight(1.0)
+        .model_inliers(&ConstantEstimator, data.iter().copied())
+        .unwrap();
+    assert_eq!(model.0, expected.0);
+    assert_eq!(inliers, expected_inliers);
+    // The recent value wins once recent datapoints count for more.
+    let (model, inliers) = arrsac()
+        .recency_weight(2.0)
+        .model_inliers(&ConstantEstimator, data.iter().copied())
+        .unwrap();
+    assert_eq!(model.0, 10.0);
+    assert_eq!(inliers.len(), 460);
+    // The survivors of a search on every other datapoint are weighted again once they are scored on
+    // all of the data.
+    let (model, inliers) = arrsac()
+        .recency_weight(2.0)
+        .block_eval_stride(2)
+        .model_inliers(&ConstantEstimator, data.iter().copied())
+        .unwrap();
+    assert_eq!(model.0, 10.0);
+    assert_eq!(inliers.len(), 460);
+}
+
+#[test]
+fn on_tie() {
+    // Every model fits all of the data, but the one through the last datapoint has the highest
+    // residuals and is generated first. Every hypothesis is kept so that only the final selection
+    // breaks the tie, which picks a model that fits more closely with either policy.
+    let data: Vec<f64> = [0.0; 10].into_iter().chain([0.5]).collect();
+    let arrsac = || {
+        Arrsac::new(1.0, Xoshiro256PlusPlus::seed_from_u64(0))
+            .seed_samples(vec![vec![10]])
+            .max_candidate_hypotheses(usize::MAX)
+    };
+    let (model, inliers) = arrsac()
+        .model_inliers(&ConstantEstimator, data.iter().copied())
+        .unwrap();
+    assert_eq!(model.0, 0.0);
+    assert_eq!(inliers.len(), 11);
+    // The candidates, the runners-up and the tied models all start with the returned model.
+    let (first, first_inliers) = arrsac()
+        .model_candidates(&ConstantEstimator, data.iter().copied())
+        .remove(0);
+    assert_eq!((first.0, &first_inliers), (model.0, &inliers));
+    let (nth, nth_inliers) = arrsac()
+        .nth_best_model(&ConstantEstimator, data.iter().copied(), 0)
+        .unwrap();
+    assert_eq!((nth.0, &nth_inliers), (model.0, &inliers));
+    let (model, inliers) = arrsac()
+        .on_tie(TiePolicy::LowestResidual)
+        .model_inliers(&ConstantEstimator, data.iter().copied())
+        .unwrap();
+    assert_eq!(model.0, 0.0);
+    assert_eq!(inliers.len(), 11);
+
+    let models = arrsac().best_models(&ConstantEstimator, data.iter().copied());
+    assert!(models.len() > 1);
+    assert_eq!(models[0].0 .0, 0.0);
+    assert!(models[1..].iter().any(|(model, _)| model.0 == 0.5));
+    assert!(models.iter().all(|(_, inliers)| inliers.len() == 11));
+}
+
+#[test]
+fn residual_sum_breaks_ties() {
+    // Both models fit all of the data, but the one through the last datapoint is generated first
+    // and has ten times the sum of residuals.
+    let data: Vec<f64> = [0.0; 10].into_iter().chain([0.5]).collect();
+    let (model, inliers) = Arrsac::new(1.0, Xoshiro256PlusPlus::seed_from_u64(0))
+        .seed_samples(vec![vec![10]])
+        .max_candidate_hypotheses(1)
+        .model_inliers(&ConstantEstimator, data.iter().copied())
+        .unwrap();
+    // Only one hypothesis survives the initial phase, which is the one that fits more closely.
+    assert_eq!(model.0, 0.0);
+    assert_eq!(inliers.len(), 11);
+}
+
+#[test]
+fn min_full_sampling_rounds() {
+    // Most of the data has one value, but the only initial hypothesis is the other value.
+    let data: Vec<f64> = (0..1000)
+        .map(|ix| if ix % 10 < 3 { 0.0 } else { 10.0 })
+        .collect();
+    let arrsac = || {
+        Arrsac::new(1.0, Xoshiro256PlusPlus::seed_from_u64(0))
+            .initialization_hypotheses(1)
+            .seed_samples(vec![vec![0]])
+    };
+    // Sampling from the inliers of the wrong value can only ever reproduce it.
+    let (model, inliers) = arrsac()
+        .model_inliers(&ConstantEstimator, data.iter().copied())
+        .unwrap();
+    assert_eq!(model.0, 0.0);
+    assert_eq!(inliers.len(), 300);
+    let (model, inliers) = arrsac()
+        .min_full_sampling_rounds(20)
+        .model_inliers(&ConstantEstimator, data.iter().copied())
+        .unwrap();
+    assert_eq!(model.0, 10.0);
+    assert_eq!(inliers.len(), 700);
+}
@@ -0,0 +1,8 @@
+#![cfg(feature = "self-test")]
+
+use arrsac::Arrsac;
+
+#[test]
+fn self_test() {
+    assert!(Arrsac::self_test());
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use arrsac::{Arrsac, ArrsacBuilder, ArrsacError, Event, Report};
+use common::{line_points, random_line, LineEstimator};
+use core::ops::ControlFlow;
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256PlusPlus;
+use sample_consensus::Consensus;
+use std::sync::{Arc, Mutex};
+
+fn assert_send<T: Send>() {}
+fn assert_sync<T: Sync>() {}
+
+/// Compiles only if `Arrsac<R>` is `Send` for every `R: Send`.
+#[allow(dead_code)]
+fn arrsac_is_send<R: Send>() {
+    assert_send::<Arrsac<R>>();
+}
+
+/// Compiles only if `Arrsac<R>` is `Sync` for every `R: Sync`.
+#[allow(dead_code)]
+fn arrsac_is_sync<R: Sync>() {
+    assert_sync::<Arrsac<R>>();
+}
+
+#[test]
+fn send_sync() {
+    assert_send::<Arrsac<Xoshiro256PlusPlus>>();
+    assert_sync::<Arrsac<Xoshiro256PlusPlus>>();
+    assert_send::<ArrsacBuilder>();
+    assert_sync::<ArrsacBuilder>();
+    assert_send::<Report>();
+    assert_sync::<Report>();
+    assert_send::<ArrsacError>();
+    assert_sync::<ArrsacError>();
+}
+
+#[test]
+fn send_with_inspect() {
+    let events = Arc::new(Mutex::new(0));
+    let counter = events.clone();
+    let arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(0)).inspect(move |_: Event| {
+        *counter.lock().unwrap() += 1;
+        ControlFlow::Continue(())
+    });
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    let (norm, c) = random_line(&mut rng);
+    let points = line_points(&mut rng, norm, c, 1000);
+
+    // The instance can be moved to another thread along with its hook.
+    let found = std::thread::spawn(move || {
+        let mut arrsac = arrsac;
+        arrsac.model(&LineEstimator, points.into_iter()).is_some()
+    })
+    .join()
+    .unwrap();
+    assert!(found);
+    assert!(*events.lock().unwrap() > 0);
+}
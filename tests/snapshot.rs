@@ -0,0 +1,76 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use arrsac::{Arrsac, ArrsacSnapshot};
+use common::{line_points, random_line, Line, LineEstimator};
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256PlusPlus;
+use sample_consensus::Consensus;
+
+#[test]
+fn snapshot_and_resume() {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    for seed in 0..20 {
+        let (norm, c) = random_line(&mut rng);
+        let points = line_points(&mut rng, norm, c, 1000);
+
+        let mut arrsac = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(seed));
+        let snapshot = arrsac
+            .snapshot(&LineEstimator, points.iter().copied(), 1)
+            .expect("initialization failed");
+        assert!(snapshot.epsilon() > snapshot.delta());
+        assert_eq!(snapshot.inliers().len(), 64 >> 4);
+        // Resume from a round trip through serialization.
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let snapshot: ArrsacSnapshot<Line> = serde_json::from_str(&json).unwrap();
+        let resumed = arrsac
+            .resume(snapshot, &LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+
+        // The run is the same as if it had not been interrupted.
+        let uninterrupted = Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(seed))
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert_eq!(resumed.1, uninterrupted.1);
+        assert!(resumed.0.norm.dot(&norm).abs() > 0.99);
+    }
+}
+
+#[test]
+fn snapshot_and_resume_state() {
+    // The searched datapoints, the full samples and the rejected samples all carry over as well.
+    let arrsac = |seed| {
+        Arrsac::new(3.0, Xoshiro256PlusPlus::seed_from_u64(seed))
+            .block_eval_stride(2)
+            .score_sample_fraction(0.8)
+            .min_full_sampling_rounds(100)
+            .dedupe_samples_global(true)
+            .auto_likelihood_threshold(1e-2)
+    };
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(0);
+    for seed in 0..20 {
+        let (norm, c) = random_line(&mut rng);
+        let points = line_points(&mut rng, norm, c, 1000);
+
+        let mut interrupted = arrsac(seed);
+        let snapshot = interrupted
+            .snapshot(&LineEstimator, points.iter().copied(), 1)
+            .expect("initialization failed");
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let snapshot: ArrsacSnapshot<Line> = serde_json::from_str(&json).unwrap();
+        let resumed = interrupted
+            .resume(snapshot, &LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+
+        let mut uninterrupted = arrsac(seed);
+        let expected = uninterrupted
+            .model_inliers(&LineEstimator, points.iter().copied())
+            .expect("unable to estimate a model");
+        assert_eq!(resumed.1, expected.1);
+        // JSON doesn't round trip every float of the models, so the residuals can differ slightly.
+        let (report, expected) = (interrupted.report(), uninterrupted.report());
+        assert_eq!(report.hypotheses_generated, expected.hypotheses_generated);
+        assert_eq!(report.epsilon, expected.epsilon);
+    }
+}